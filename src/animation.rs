@@ -0,0 +1,131 @@
+// animations are stepped by the same dt_micros that drives Application::update, so they run at
+// the same speed however often a frame actually gets rendered
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Easing {
+    Linear,
+    // starts slow and speeds up
+    EaseIn,
+    // starts fast and settles into the end value
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    // maps progress 0..=1 onto how far between the two values to be
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => {
+                let u = 1.0 - t;
+                1.0 - u * u * u
+            },
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let u = 2.0 - 2.0 * t;
+                    1.0 - u * u * u / 2.0
+                }
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Tween {
+    from: f32,
+    to: f32,
+    duration_us: u64,
+    elapsed_us: u64,
+    easing: Easing,
+}
+
+impl Tween {
+    // a tween resting at value
+    pub const fn new(value: f32, duration_us: u64, easing: Easing) -> Self {
+        Self {
+            from: value,
+            to: value,
+            duration_us,
+            elapsed_us: duration_us,
+            easing,
+        }
+    }
+
+    pub fn start(&mut self, from: f32, to: f32) {
+        self.from = from;
+        self.to = to;
+        self.elapsed_us = 0;
+    }
+
+    // heads for a new end value from wherever the tween is now, so changing target part way
+    // through doesn't jump
+    pub fn retarget(&mut self, to: f32) {
+        if to != self.to {
+            self.start(self.value(), to);
+        }
+    }
+
+    // jumps straight to value
+    pub fn set(&mut self, value: f32) {
+        self.from = value;
+        self.to = value;
+        self.elapsed_us = self.duration_us;
+    }
+
+    pub fn update(&mut self, dt_micros: u64) {
+        self.elapsed_us = (self.elapsed_us + dt_micros).min(self.duration_us);
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.duration_us == 0 {
+            1.0
+        } else {
+            self.elapsed_us as f32 / self.duration_us as f32
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.from + (self.to - self.from) * self.easing.apply(self.progress())
+    }
+
+    pub fn target(&self) -> f32 {
+        self.to
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed_us >= self.duration_us
+    }
+}
+
+// on for the first half of every period, off for the rest
+#[derive(Copy, Clone, Debug)]
+pub struct Blink {
+    period_us: u64,
+    phase_us: u64,
+}
+
+impl Blink {
+    pub const fn new(period_us: u64) -> Self {
+        Self {
+            period_us,
+            phase_us: 0,
+        }
+    }
+
+    // starts a fresh period, so whatever starts blinking is shown straight away
+    pub fn restart(&mut self) {
+        self.phase_us = 0;
+    }
+
+    pub fn update(&mut self, dt_micros: u64) {
+        self.phase_us = (self.phase_us + dt_micros) % self.period_us;
+    }
+
+    pub fn on(&self) -> bool {
+        self.phase_us < self.period_us / 2
+    }
+}
@@ -1,11 +1,9 @@
-use alloc::vec::Vec;
 use crate::app_views::AppView;
 use crate::application::AppSharedState;
 use crate::application::ComState;
 use crate::application::InputState;
-use crate::gfx::fonts::BASIC_5PX;
 use crate::mn12864k::Framebuffer;
-use qcw_com::{ControllerMessage, RemoteMessage};
+use qcw_com::ControllerMessage;
 
 use super::render_app_frame;
 use super::update_app_frame;
@@ -17,6 +15,12 @@ pub struct DebugLedView {
     buttons: [UiFrameButton; 2],
 }
 
+impl Default for DebugLedView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DebugLedView {
     pub fn new() -> Self {
         Self {
@@ -32,10 +36,10 @@ impl AppView for DebugLedView {
         self.buttons.iter_mut().for_each(|button| button.reset());
     }
 
-    fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
+    fn update(&mut self, _dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, _shared_state: &mut AppSharedState) -> Option<View> {
         update_app_frame(&input_state, &mut self.buttons);
         if self.buttons[1].press {
-            match self.state.clone() {
+            match self.state {
                 None => {
                     self.state = Some(true);
                     com.outbox.push_back(ControllerMessage::SetDebugLed(true));
@@ -55,7 +59,7 @@ impl AppView for DebugLedView {
         }
     }
 
-    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+    fn render(&mut self, framebuffer: &mut Framebuffer, _shared_state: &mut AppSharedState) {
         render_app_frame(framebuffer, "Debug LED Control", &mut self.buttons);
     }
 }
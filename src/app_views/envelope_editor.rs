@@ -0,0 +1,261 @@
+use alloc::format;
+use qcw_com::{Parameter, ParameterValue, RemoteMessage, ControllerMessage};
+
+use crate::application::{AppSharedState, ComState, InputState};
+use crate::envelope::{Envelope, ENVELOPE_SLOTS, NAME_CAPACITY, TIME_SCALE};
+use crate::gfx::draw_target::DrawTarget;
+use crate::gfx::fonts::BASIC_5PX;
+use crate::gfx::primitives::*;
+use crate::mn12864k::Framebuffer;
+use crate::ui::{TextEntry, TextEntryResult};
+
+use super::{invalidate_content, render_app_frame, update_app_frame, AppView, UiFrameButton, View};
+
+const GRAPH_UPPER_LEFT: (isize, isize) = (3, 12);
+const GRAPH_LOWER_RIGHT: (isize, isize) = (124, 43);
+
+// one encoder detent moves a breakpoint this many thousandths of the on time
+const TIME_INCREMENT: i32 = 10;
+const PARAMETER_REQUEST_INTERVAL_US: u64 = 100_000;
+
+#[derive(Copy, Clone, PartialEq)]
+enum EditMode {
+    SelectPoint,
+    MoveTime,
+    MovePower,
+    SelectEnvelope,
+}
+
+impl EditMode {
+    fn next(&self) -> Self {
+        match self {
+            EditMode::SelectPoint => EditMode::MoveTime,
+            EditMode::MoveTime => EditMode::MovePower,
+            EditMode::MovePower => EditMode::SelectEnvelope,
+            EditMode::SelectEnvelope => EditMode::SelectPoint,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            EditMode::SelectPoint => "Point",
+            EditMode::MoveTime => "Time",
+            EditMode::MovePower => "Power",
+            EditMode::SelectEnvelope => "Envelope",
+        }
+    }
+}
+
+pub struct EnvelopeEditorView {
+    buttons: [UiFrameButton; 3],
+    mode: EditMode,
+    slot: usize,
+    selected: usize,
+    // a breakpoint was moved, saved once the move is finished
+    moved: bool,
+    // typing a new name for the envelope
+    name_entry: Option<TextEntry>,
+    on_time_us: Option<u32>,
+    t_elapsed: u64,
+    t_last_request: Option<u64>,
+}
+
+impl Default for EnvelopeEditorView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnvelopeEditorView {
+    pub fn new() -> Self {
+        Self {
+            buttons: [UiFrameButton::new("Back"), UiFrameButton::new("Add"), UiFrameButton::new("Delete")],
+            mode: EditMode::SelectPoint,
+            slot: 0,
+            selected: 0,
+            moved: false,
+            name_entry: None,
+            on_time_us: None,
+            t_elapsed: 0,
+            t_last_request: None,
+        }
+    }
+
+    // the point buttons only make sense with a point selected
+    fn mode_button_labels(&self) -> [&'static str; 3] {
+        match self.mode {
+            EditMode::SelectEnvelope => ["Back", "Apply", "Name"],
+            _ => ["Back", "Add", "Delete"],
+        }
+    }
+
+    fn set_button_labels(&mut self, labels: [&'static str; 3]) {
+        for (button, label) in self.buttons.iter_mut().zip(labels) {
+            button.text = label;
+        }
+    }
+}
+
+fn graph_position(time: f32, power: f32) -> (isize, isize) {
+    let width = (GRAPH_LOWER_RIGHT.0 - GRAPH_UPPER_LEFT.0 - 2) as f32;
+    let height = (GRAPH_LOWER_RIGHT.1 - GRAPH_UPPER_LEFT.1 - 2) as f32;
+    (
+        GRAPH_UPPER_LEFT.0 + 1 + (time / TIME_SCALE as f32 * width) as isize,
+        GRAPH_LOWER_RIGHT.1 - 1 - (power / 100.0 * height) as isize,
+    )
+}
+
+// draws the envelope curve, sampled once per column so it matches what the controller would interpolate
+fn draw_envelope<Target: DrawTarget>(target: &mut Target, envelope: &Envelope, selected: Option<usize>) {
+    draw_rect(target, GRAPH_UPPER_LEFT, GRAPH_LOWER_RIGHT, true);
+    let columns = GRAPH_LOWER_RIGHT.0 - GRAPH_UPPER_LEFT.0 - 2;
+    let mut previous = None;
+    for column in 0..=columns {
+        let time = (column * TIME_SCALE as isize / columns) as u16;
+        let position = graph_position(time as f32, envelope.power_at(time));
+        match previous {
+            Some(previous) => draw_line(target, previous, position, true),
+            None => target.set_pixel(position, true),
+        }
+        previous = Some(position);
+    }
+    for (i, point) in envelope.breakpoints().iter().enumerate() {
+        let (x, y) = graph_position(point.time as f32, point.power as f32);
+        if Some(i) == selected {
+            draw_filled_rect(target, (x - 2, y - 2), (x + 2, y + 2), true);
+        } else {
+            draw_rect(target, (x - 1, y - 1), (x + 1, y + 1), true);
+        }
+    }
+}
+
+impl AppView for EnvelopeEditorView {
+    fn start(&mut self) {
+        self.buttons.iter_mut().for_each(|button| button.reset());
+        self.mode = EditMode::SelectPoint;
+        self.selected = 0;
+        self.moved = false;
+        self.name_entry = None;
+        self.set_button_labels(self.mode_button_labels());
+        self.on_time_us = None;
+        self.t_elapsed = 0;
+        self.t_last_request = None;
+    }
+
+    fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
+        self.t_elapsed += dt_micros;
+        update_app_frame(&input_state, &mut self.buttons);
+
+        // breakpoint times are shown in microseconds once the controller reports its on time
+        let request_due = self.t_last_request.map(|t| self.t_elapsed - t >= PARAMETER_REQUEST_INTERVAL_US).unwrap_or(true);
+        if self.on_time_us.is_none() && request_due {
+            com.outbox.push_back(ControllerMessage::GetParam(Parameter::OnTime));
+            self.t_last_request = Some(self.t_elapsed);
+        }
+        while let Some(message) = com.inbox.pop_front() {
+            if let RemoteMessage::GetParamResult(ParameterValue::OnTimeUs(on_time)) = message {
+                self.on_time_us = Some(on_time as u32);
+                invalidate_content(shared_state);
+            }
+        }
+
+        if let Some(entry) = &mut self.name_entry {
+            let result = entry.update(&input_state);
+            let labels = entry.button_labels();
+            match result {
+                TextEntryResult::Editing => self.set_button_labels(labels),
+                TextEntryResult::Done(name) => {
+                    shared_state.envelopes[self.slot].set_name(&name);
+                    shared_state.envelopes_unsaved = true;
+                    self.name_entry = None;
+                    self.set_button_labels(self.mode_button_labels());
+                },
+                TextEntryResult::Cancelled => {
+                    self.name_entry = None;
+                    self.set_button_labels(self.mode_button_labels());
+                },
+            }
+            return None;
+        }
+
+        if input_state.encoder.button.pressed {
+            shared_state.envelopes_unsaved |= self.moved;
+            self.moved = false;
+            self.mode = self.mode.next();
+            self.set_button_labels(self.mode_button_labels());
+        }
+
+        let envelope = &mut shared_state.envelopes[self.slot];
+        let delta = input_state.encoder.delta;
+        if delta != 0 {
+            match self.mode {
+                EditMode::SelectPoint => self.selected = (self.selected as i32 + delta).clamp(0, envelope.breakpoints().len() as i32 - 1) as usize,
+                EditMode::MoveTime => envelope.move_time(self.selected, delta * TIME_INCREMENT),
+                EditMode::MovePower => envelope.move_power(self.selected, delta),
+                EditMode::SelectEnvelope => {
+                    self.slot = (self.slot as i32 + delta).rem_euclid(ENVELOPE_SLOTS as i32) as usize;
+                    self.selected = 0;
+                },
+            }
+            self.moved |= matches!(self.mode, EditMode::MoveTime | EditMode::MovePower);
+        }
+
+        let envelope = &mut shared_state.envelopes[self.slot];
+        if self.mode == EditMode::SelectEnvelope {
+            if self.buttons[1].press {
+                com.outbox.push_back(ControllerMessage::SetEnvelope(envelope.to_message()));
+            }
+            if self.buttons[2].press {
+                let entry = TextEntry::new(&BASIC_5PX, envelope.name(), NAME_CAPACITY);
+                self.set_button_labels(entry.button_labels());
+                self.name_entry = Some(entry);
+                return None;
+            }
+        } else {
+            if self.buttons[1].press {
+                if let Some(index) = envelope.insert_after(self.selected.min(envelope.breakpoints().len() - 2)) {
+                    self.selected = index;
+                    shared_state.envelopes_unsaved = true;
+                }
+            }
+            if self.buttons[2].press && envelope.remove(self.selected) {
+                self.selected -= 1;
+                shared_state.envelopes_unsaved = true;
+            }
+        }
+
+        if self.buttons[0].press {
+            shared_state.envelopes_unsaved |= self.moved;
+            self.moved = false;
+            Some(View::ViewPicker)
+        } else {
+            None
+        }
+    }
+
+    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+        let envelope = &shared_state.envelopes[self.slot];
+        if let Some(entry) = &self.name_entry {
+            render_app_frame(framebuffer, "Envelope Name", &mut self.buttons);
+            entry.render(framebuffer, (4, 17), 120);
+            return;
+        }
+        render_app_frame(framebuffer, envelope.name(), &mut self.buttons);
+        let mean_string = format!("avg {:.0}%", envelope.mean_power());
+        BASIC_5PX.draw_text_line(framebuffer, (124 - BASIC_5PX.get_text_width(&mean_string), 7), &mean_string, true);
+
+        let selected = if self.mode == EditMode::SelectEnvelope { None } else { Some(self.selected) };
+        draw_envelope(framebuffer, envelope, selected);
+
+        let point = envelope.breakpoints()[self.selected.min(envelope.breakpoints().len() - 1)];
+        let time_string = match self.on_time_us {
+            Some(on_time) => format!("{}us", (point.time as u32 * on_time) / TIME_SCALE as u32),
+            None => format!("{:.1}%", point.time as f32 / 10.0),
+        };
+        let status_string = match self.mode {
+            EditMode::SelectEnvelope => format!("{}: {}/{}", self.mode.label(), self.slot + 1, ENVELOPE_SLOTS),
+            _ => format!("{}: #{} {} {}%", self.mode.label(), self.selected + 1, time_string, point.power),
+        };
+        BASIC_5PX.draw_text_line(framebuffer, (4, 50), &status_string, true);
+    }
+}
@@ -0,0 +1,184 @@
+use alloc::format;
+use alloc::string::String;
+
+use crate::application::{AppSharedState, ComState, InputState};
+use crate::event_log::{LogEntry, Severity};
+use crate::gfx::bitmap_font::BitmapFont;
+use crate::gfx::draw_target::{RectMask, _DTRef, _Maskable};
+use crate::gfx::fonts::BASIC_5PX;
+use crate::mn12864k::Framebuffer;
+use crate::ui::{TextEntry, TextEntryResult};
+
+use super::{render_app_frame, update_app_frame, AppView, UiFrameButton, View};
+
+const VISIBLE_LINES: usize = 5;
+const LINE_SPACING: isize = 8;
+const FIRST_BASELINE: isize = 18;
+const TEXT_X: isize = 3;
+const TEXT_RIGHT: isize = 124;
+const MARKER_MAX_LEN: usize = 20;
+
+pub struct EventLogView {
+    buttons: [UiFrameButton; 3],
+    // index of the wrapped line shown at the top
+    scroll: usize,
+    // keeps the newest entries in view as they arrive
+    follow: bool,
+    // typing a marker to drop into the log
+    marker_entry: Option<TextEntry>,
+    // event_log.total() when the log was last drawn
+    shown_total: u32,
+}
+
+impl Default for EventLogView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventLogView {
+    pub fn new() -> Self {
+        Self {
+            buttons: [UiFrameButton::new("Back"), UiFrameButton::new("Clear"), UiFrameButton::new("Mark")],
+            scroll: 0,
+            follow: true,
+            marker_entry: None,
+            shown_total: 0,
+        }
+    }
+
+    fn max_scroll(shared_state: &AppSharedState) -> usize {
+        let log = &shared_state.event_log;
+        let lines: usize = (0..log.len()).filter_map(|i| log.get(i)).map(|entry| entry_lines(entry).1.count()).sum();
+        lines.saturating_sub(VISIBLE_LINES)
+    }
+
+    fn set_button_labels(&mut self, labels: [&'static str; 3]) {
+        for (button, label) in self.buttons.iter_mut().zip(labels) {
+            button.text = label;
+        }
+    }
+}
+
+impl AppView for EventLogView {
+    fn start(&mut self) {
+        self.buttons.iter_mut().for_each(|button| button.reset());
+        self.scroll = 0;
+        self.follow = true;
+        self.marker_entry = None;
+        self.set_button_labels(["Back", "Clear", "Mark"]);
+    }
+
+    fn update(&mut self, _dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
+        com.inbox.clear();
+        update_app_frame(&input_state, &mut self.buttons);
+        // entries are logged from all over, not just by this view
+        if shared_state.event_log.total() != self.shown_total {
+            self.shown_total = shared_state.event_log.total();
+            shared_state.invalidate_all();
+        }
+
+        if let Some(entry) = &mut self.marker_entry {
+            let result = entry.update(&input_state);
+            let labels = entry.button_labels();
+            match result {
+                TextEntryResult::Editing => self.set_button_labels(labels),
+                TextEntryResult::Done(text) => {
+                    if !text.is_empty() {
+                        shared_state.event_log.push(shared_state.time_us, Severity::Info, format_args!("Marker: {}", text));
+                    }
+                    self.marker_entry = None;
+                    self.follow = true;
+                    self.set_button_labels(["Back", "Clear", "Mark"]);
+                },
+                TextEntryResult::Cancelled => {
+                    self.marker_entry = None;
+                    self.set_button_labels(["Back", "Clear", "Mark"]);
+                },
+            }
+            return None;
+        }
+
+        let max_scroll = Self::max_scroll(shared_state);
+        if input_state.encoder.delta != 0 {
+            self.scroll = (self.scroll as i32 + input_state.encoder.delta).clamp(0, max_scroll as i32) as usize;
+            self.follow = self.scroll == max_scroll;
+        }
+        if self.follow || input_state.encoder.button.pressed {
+            self.scroll = max_scroll;
+            self.follow = true;
+        }
+        self.scroll = self.scroll.min(max_scroll);
+
+        if self.buttons[1].press {
+            shared_state.event_log.clear();
+            self.scroll = 0;
+            self.follow = true;
+        }
+        if self.buttons[2].press {
+            let entry = TextEntry::new(&BASIC_5PX, "", MARKER_MAX_LEN);
+            self.set_button_labels(entry.button_labels());
+            self.marker_entry = Some(entry);
+            return None;
+        }
+        if self.buttons[0].press {
+            Some(View::ViewPicker)
+        } else {
+            None
+        }
+    }
+
+    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+        if let Some(entry) = &self.marker_entry {
+            render_app_frame(framebuffer, "Add Marker", &mut self.buttons);
+            entry.render(framebuffer, (4, 17), 120);
+            return;
+        }
+        render_app_frame(framebuffer, "Event Log", &mut self.buttons);
+        let log = &shared_state.event_log;
+
+        let mut shown_entries: Option<(usize, usize)> = None;
+        {
+            let mut line = 0;
+            let mut target = framebuffer.dt_ref().mask(RectMask {
+                upper_left: (1, 11),
+                lower_right: (126, 52),
+            });
+            for index in 0..log.len() {
+                if line >= self.scroll + VISIBLE_LINES {
+                    break;
+                }
+                let Some(entry) = log.get(index) else { continue };
+                let (prefix, message_lines) = entry_lines(entry);
+                let message_x = TEXT_X + BASIC_5PX.get_text_width(&prefix) + 1;
+                for (i, message_line) in message_lines.enumerate() {
+                    if line >= self.scroll && line < self.scroll + VISIBLE_LINES {
+                        let baseline = FIRST_BASELINE + (line - self.scroll) as isize * LINE_SPACING;
+                        if i == 0 {
+                            BASIC_5PX.draw_text_line(&mut target, (TEXT_X, baseline), &prefix, true);
+                        }
+                        BASIC_5PX.draw_text_line(&mut target, (message_x, baseline), message_line, true);
+                        shown_entries = Some((shown_entries.map(|(first, _)| first).unwrap_or(index), index));
+                    }
+                    line += 1;
+                }
+            }
+        }
+
+        let position_string = match shown_entries {
+            None => String::from("empty"),
+            Some((first, last)) => format!("{}-{}/{}", first + 1, last + 1, log.len()),
+        };
+        BASIC_5PX.draw_text_line(framebuffer, (124 - BASIC_5PX.get_text_width(&position_string), 7), &position_string, true);
+    }
+}
+
+// time and severity, then the message wrapped after them
+fn entry_lines(entry: &LogEntry) -> (String, impl Iterator<Item = &str>) {
+    let seconds = entry.timestamp_us / 1_000_000;
+    let tenths = (entry.timestamp_us / 100_000) % 10;
+    let prefix = format!("{:>4}.{} {} ", seconds, tenths, entry.severity.short_name());
+    let font: &'static BitmapFont = &BASIC_5PX;
+    let message_x = TEXT_X + font.get_text_width(&prefix) + 1;
+    (prefix, font.wrap(entry.message(), TEXT_RIGHT - message_x))
+}
@@ -0,0 +1,185 @@
+use alloc::{format, string::String};
+
+use crate::application::{AppSharedState, ComState, InputState};
+use crate::gfx::fonts::BASIC_5PX;
+use crate::gfx::primitives::*;
+use crate::limiter::{LimiterConfig, LimitMode};
+use crate::mn12864k::Framebuffer;
+use crate::ui::{Align, DigitEditResult, DigitEditor, ListPicker, Rect};
+
+use super::{invalidate_limiter_status, limiter_changing, render_app_frame, render_limiter_status, update_app_frame, AppView, UiFrameButton, View};
+
+#[derive(Copy, Clone, PartialEq)]
+enum LimitField {
+    Mode,
+    MaxDuty,
+    MaxPower,
+    Capacity,
+}
+
+// the fractions are typed in as tenths of a percent and hundredths of a second
+fn field_digit_editor(field: LimitField, config: &LimiterConfig) -> Option<DigitEditor> {
+    match field {
+        LimitField::Mode => None,
+        LimitField::MaxDuty => Some(DigitEditor::new(libm::roundf(config.max_duty * 1000.0) as i32, 1, 200, 1, "%")),
+        LimitField::MaxPower => Some(DigitEditor::new(libm::roundf(config.max_average_power * 1000.0) as i32, 1, 200, 1, "%")),
+        LimitField::Capacity => Some(DigitEditor::new(libm::roundf(config.thermal_capacity * 100.0) as i32, 5, 500, 2, "s")),
+    }
+}
+
+pub struct LimitsView {
+    buttons: [UiFrameButton; 3],
+    editing: bool,
+    field_list: ListPicker<LimitField, 4>,
+    digit_editor: Option<DigitEditor>,
+}
+
+impl Default for LimitsView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LimitsView {
+    pub fn new() -> Self {
+        Self {
+            buttons: [UiFrameButton::new("Back"), UiFrameButton::new("---"), UiFrameButton::new("Type")],
+            editing: false,
+            field_list: ListPicker::new([
+                (LimitField::Mode, "Mode"),
+                (LimitField::MaxDuty, "Max Duty"),
+                (LimitField::MaxPower, "Max Power"),
+                (LimitField::Capacity, "Budget"),
+            ], (4, 20), 45, 30),
+            digit_editor: None,
+        }
+    }
+
+    fn set_button_labels(&mut self, labels: [&'static str; 3]) {
+        for (button, label) in self.buttons.iter_mut().zip(labels) {
+            button.text = label;
+        }
+    }
+
+    fn restore_button_labels(&mut self) {
+        self.set_button_labels(["Back", "---", "Type"]);
+    }
+}
+
+impl AppView for LimitsView {
+    fn start(&mut self) {
+        self.buttons.iter_mut().for_each(|button| button.reset());
+        self.editing = false;
+        self.digit_editor = None;
+        self.restore_button_labels();
+    }
+
+    fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
+        com.inbox.clear();
+        update_app_frame(&input_state, &mut self.buttons);
+        self.field_list.animate(dt_micros, &mut shared_state.dirty);
+        invalidate_limiter_status(shared_state);
+        if limiter_changing(&shared_state.limiter) {
+            shared_state.invalidate((55, 40), (126, 48));
+        }
+
+        // the digit editor has the encoder and the frame buttons to itself while it's open
+        if let Some(editor) = &mut self.digit_editor {
+            let result = editor.update(&input_state);
+            let labels = editor.button_labels();
+            match result {
+                DigitEditResult::Editing => self.set_button_labels(labels),
+                DigitEditResult::Done(value) => {
+                    let config = &mut shared_state.limiter.config;
+                    match self.field_list.selected() {
+                        LimitField::Mode => {},
+                        LimitField::MaxDuty => config.max_duty = value as f32 / 1000.0,
+                        LimitField::MaxPower => config.max_average_power = value as f32 / 1000.0,
+                        LimitField::Capacity => config.thermal_capacity = value as f32 / 100.0,
+                    }
+                    self.digit_editor = None;
+                    self.restore_button_labels();
+                },
+                DigitEditResult::Cancelled => {
+                    self.digit_editor = None;
+                    self.restore_button_labels();
+                },
+            }
+            return None;
+        }
+
+        if self.buttons[2].press && !self.editing {
+            self.digit_editor = field_digit_editor(*self.field_list.selected(), &shared_state.limiter.config);
+            if let Some(editor) = &self.digit_editor {
+                let labels = editor.button_labels();
+                self.set_button_labels(labels);
+                return None;
+            }
+        }
+
+        if !self.editing {
+            self.editing = self.field_list.update(&input_state.encoder).is_some();
+        } else if input_state.encoder.button.pressed {
+            self.editing = false;
+        }
+
+        let delta = input_state.encoder.delta;
+        if self.editing && delta != 0 {
+            let config = &mut shared_state.limiter.config;
+            match self.field_list.selected() {
+                LimitField::Mode => config.mode = match config.mode {
+                    LimitMode::Clamp => LimitMode::Refuse,
+                    LimitMode::Refuse => LimitMode::Clamp,
+                },
+                LimitField::MaxDuty => config.max_duty = (config.max_duty + delta as f32 * 0.001).clamp(0.001, 0.2),
+                LimitField::MaxPower => config.max_average_power = (config.max_average_power + delta as f32 * 0.001).clamp(0.001, 0.2),
+                LimitField::Capacity => config.thermal_capacity = (config.thermal_capacity + delta as f32 * 0.05).clamp(0.05, 5.0),
+            }
+        }
+
+        if self.buttons[0].press {
+            Some(View::ViewPicker)
+        } else {
+            None
+        }
+    }
+
+    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+        render_app_frame(framebuffer, "Limits", &mut self.buttons);
+        render_limiter_status(framebuffer, &shared_state.limiter, &shared_state.warning_blink);
+        if let Some(editor) = &self.digit_editor {
+            let content = Rect::from_corners((6, 14), (124, 51));
+            editor.render(framebuffer, content.align(editor.size(), Align::Center, Align::Center));
+            return;
+        }
+
+        let limiter = &shared_state.limiter;
+        let config = &limiter.config;
+        let value_string = match self.field_list.selected() {
+            LimitField::Mode => String::from(match config.mode {
+                LimitMode::Clamp => "Clamp",
+                LimitMode::Refuse => "Refuse",
+            }),
+            LimitField::MaxDuty => format!("{:.1} %", config.max_duty * 100.0),
+            LimitField::MaxPower => format!("{:.1} %", config.max_average_power * 100.0),
+            LimitField::Capacity => format!("{:.2} s", config.thermal_capacity),
+        };
+        BASIC_5PX.draw_text_line(framebuffer, (4, 18), "Limit:", true);
+        BASIC_5PX.draw_text_line(framebuffer, (55, 18), &value_string, true);
+        if self.editing {
+            let text_width = BASIC_5PX.get_text_width(&value_string);
+            draw_hline(framebuffer, 55, 55 + text_width, 20, true);
+        }
+
+        let parameters = limiter.parameters();
+        BASIC_5PX.draw_text_line(framebuffer, (55, 30), &format!("Duty {:.2}%", parameters.duty_cycle() * 100.0), true);
+        BASIC_5PX.draw_text_line(framebuffer, (55, 38), &format!("Avg {:.2}%", parameters.average_power() * 100.0), true);
+        let budget_string = if limiter.overheated() {
+            format!("Cooling {:.0}%", limiter.remaining_budget() * 100.0)
+        } else {
+            format!("Budget {:.0}%", limiter.remaining_budget() * 100.0)
+        };
+        BASIC_5PX.draw_text_line(framebuffer, (55, 46), &budget_string, true);
+        self.field_list.render(framebuffer);
+    }
+}
@@ -0,0 +1,100 @@
+use alloc::{format, string::String};
+
+use crate::application::{AppSharedState, ComState, InputState};
+use crate::event_log::Severity;
+use crate::gfx::fonts::BASIC_5PX;
+use crate::gfx::primitives::*;
+use crate::mn12864k::Framebuffer;
+use crate::run_time::{DisplayDuration, RunTotals};
+
+use super::{render_app_frame, update_app_frame, AppView, UiFrameButton, View};
+
+// right hand edges of the table columns
+const BURSTS_RIGHT: isize = 62;
+const ON_TIME_RIGHT: isize = 94;
+const FIRED_RIGHT: isize = 124;
+
+pub struct MaintenanceView {
+    buttons: [UiFrameButton; 3],
+}
+
+impl Default for MaintenanceView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MaintenanceView {
+    pub fn new() -> Self {
+        Self {
+            buttons: [UiFrameButton::new("Back"), UiFrameButton::new("Rst A"), UiFrameButton::new("Rst B")],
+        }
+    }
+}
+
+fn draw_right_aligned(framebuffer: &mut Framebuffer, right: isize, y: isize, text: &str) {
+    BASIC_5PX.draw_text_line(framebuffer, (right - BASIC_5PX.get_text_width(text), y), text, true);
+}
+
+// burst counts get large, keep them inside their column
+fn burst_count_string(bursts: u64) -> String {
+    if bursts >= 100_000_000 {
+        format!("{}M", bursts / 1_000_000)
+    } else if bursts >= 100_000 {
+        format!("{}k", bursts / 1_000)
+    } else {
+        format!("{}", bursts)
+    }
+}
+
+fn draw_totals_row(framebuffer: &mut Framebuffer, y: isize, label: &str, totals: &RunTotals) {
+    BASIC_5PX.draw_text_line(framebuffer, (4, y), label, true);
+    draw_right_aligned(framebuffer, BURSTS_RIGHT, y, &burst_count_string(totals.bursts));
+    draw_right_aligned(framebuffer, ON_TIME_RIGHT, y, &format!("{}", DisplayDuration(totals.on_time_us)));
+    draw_right_aligned(framebuffer, FIRED_RIGHT, y, &format!("{}", DisplayDuration(totals.fired_us)));
+}
+
+impl AppView for MaintenanceView {
+    fn start(&mut self) {
+        self.buttons.iter_mut().for_each(|button| button.reset());
+    }
+
+    fn update(&mut self, _dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
+        com.inbox.clear();
+        update_app_frame(&input_state, &mut self.buttons);
+        // the totals count up while firing, and "Firing" shows in the title bar
+        if shared_state.run_time.firing() {
+            shared_state.invalidate((1, 1), (126, 52));
+        }
+
+        for (trip, button) in self.buttons[1..].iter().enumerate() {
+            if button.press {
+                shared_state.run_time.reset_trip(trip);
+                shared_state.log(Severity::Info, format_args!("Trip {} reset", (b'A' + trip as u8) as char));
+            }
+        }
+
+        if self.buttons[0].press {
+            Some(View::ViewPicker)
+        } else {
+            None
+        }
+    }
+
+    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+        render_app_frame(framebuffer, "Maintenance", &mut self.buttons);
+        let run_time = &shared_state.run_time;
+        if run_time.firing() {
+            draw_right_aligned(framebuffer, 124, 7, "Firing");
+        }
+
+        draw_right_aligned(framebuffer, BURSTS_RIGHT, 18, "Bursts");
+        draw_right_aligned(framebuffer, ON_TIME_RIGHT, 18, "On");
+        draw_right_aligned(framebuffer, FIRED_RIGHT, 18, "Fired");
+        draw_hline(framebuffer, 2, 125, 20, true);
+        draw_totals_row(framebuffer, 27, "Session", run_time.session());
+        draw_totals_row(framebuffer, 35, "Life", run_time.lifetime());
+        draw_totals_row(framebuffer, 43, "Trip A", run_time.trip(0));
+        draw_totals_row(framebuffer, 51, "Trip B", run_time.trip(1));
+    }
+}
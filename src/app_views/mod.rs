@@ -1,6 +1,10 @@
+use alloc::format;
+
+use crate::animation::Blink;
 use crate::application::{AppSharedState, ComState, InputState};
-use crate::gfx::fonts::BASIC_5PX;
+use crate::gfx::fonts::{icons, BASIC_5PX};
 use crate::gfx::primitives::*;
+use crate::limiter::Limiter;
 use crate::mn12864k::Framebuffer;
 
 mod phase_tuning;
@@ -9,6 +13,13 @@ mod ping_test;
 mod debug_led;
 mod stat_monitor;
 mod open_loop_test;
+mod event_log;
+mod sequence;
+mod sweep;
+mod envelope_editor;
+mod music;
+mod limits;
+mod maintenance;
 
 pub use phase_tuning::PhaseTuningView;
 pub use view_picker::ViewPickerView;
@@ -16,6 +27,13 @@ pub use ping_test::PingTestView;
 pub use debug_led::DebugLedView;
 pub use stat_monitor::StatMonitorView;
 pub use open_loop_test::OpenLoopTestView;
+pub use event_log::EventLogView;
+pub use sequence::SequenceView;
+pub use sweep::SweepView;
+pub use envelope_editor::EnvelopeEditorView;
+pub use music::MusicView;
+pub use limits::LimitsView;
+pub use maintenance::MaintenanceView;
 
 #[derive(Copy, Clone, Debug)]
 pub enum View {
@@ -25,6 +43,13 @@ pub enum View {
     DebugLed,
     StatMonitor,
     OpenLoopTest,
+    EventLog,
+    Sequence,
+    Sweep,
+    EnvelopeEditor,
+    Music,
+    Limits,
+    Maintenance,
 }
 
 pub trait AppView {
@@ -55,7 +80,7 @@ impl UiFrameButton {
 }
 
 pub fn update_app_frame(input_state: &InputState, buttons: &mut [UiFrameButton]) {
-    if buttons.len() > 0 {
+    if !buttons.is_empty() {
         buttons[0].down = input_state.buttons[0].down;
         buttons[0].press = input_state.buttons[0].released;
     }
@@ -69,10 +94,10 @@ pub fn update_app_frame(input_state: &InputState, buttons: &mut [UiFrameButton])
     }
 }
 
-pub fn render_app_frame(framebuffer: &mut Framebuffer, title: &'static str, buttons: &mut [UiFrameButton]) {
+pub fn render_app_frame(framebuffer: &mut Framebuffer, title: &str, buttons: &mut [UiFrameButton]) {
     draw_rect(framebuffer, (0, 0), (127, 63), true);
     draw_hline(framebuffer, 1, 126, 10, true);
-    if buttons.len() != 0 {
+    if !buttons.is_empty() {
         draw_hline(framebuffer, 1, 126, 53, true);
         draw_vline(framebuffer, 32, 54, 62, true);
         if buttons[0].down {
@@ -102,3 +127,43 @@ pub fn render_app_frame(framebuffer: &mut Framebuffer, title: &'static str, butt
     }
     BASIC_5PX.draw_text_line(framebuffer, (3, 7), title, true);
 }
+
+// everything between the title bar and the buttons
+pub fn invalidate_content(shared_state: &mut AppSharedState) {
+    shared_state.invalidate((1, 11), (126, 52));
+}
+
+// where render_limiter_status draws in the title bar
+const LIMITER_STATUS_UPPER_LEFT: (isize, isize) = (48, 1);
+const LIMITER_STATUS_LOWER_RIGHT: (isize, isize) = (126, 9);
+
+// the thermal budget keeps changing while the coil is firing, and until it has cooled down again
+pub fn limiter_changing(limiter: &Limiter) -> bool {
+    limiter.running() || limiter.overheated() || limiter.remaining_budget() < 1.0
+}
+
+pub fn invalidate_limiter_status(shared_state: &mut AppSharedState) {
+    if limiter_changing(&shared_state.limiter) {
+        shared_state.invalidate(LIMITER_STATUS_UPPER_LEFT, LIMITER_STATUS_LOWER_RIGHT);
+    }
+}
+
+// duty cycle and thermal budget at the right of the title bar. returns where it starts
+pub fn render_limiter_status(framebuffer: &mut Framebuffer, limiter: &Limiter, warning_blink: &Blink) -> isize {
+    let duty_string = if limiter.overheated() {
+        let icon = if warning_blink.on() { icons::WARNING } else { "" };
+        format!("{} HOT", icon)
+    } else if limiter.running() {
+        format!("{} {:.1}%", icons::LIGHTNING, limiter.parameters().duty_cycle() * 100.0)
+    } else {
+        format!("{:.1}%", limiter.parameters().duty_cycle() * 100.0)
+    };
+    let left = 110 - BASIC_5PX.get_text_width(&duty_string);
+    BASIC_5PX.draw_text_line(framebuffer, (left, 7), &duty_string, true);
+    draw_rect(framebuffer, (113, 2), (124, 7), true);
+    let fill = (limiter.remaining_budget() * 10.0) as isize;
+    if fill > 0 {
+        draw_filled_rect(framebuffer, (114, 3), (113 + fill, 6), true);
+    }
+    left
+}
@@ -0,0 +1,179 @@
+use alloc::{format, string::String};
+use qcw_com::ControllerMessage;
+
+use crate::application::{AppSharedState, ComState, InputState};
+use crate::event_log::Severity;
+use crate::gfx::draw_target::{DrawTarget, RectMask, _DTRef, _Maskable};
+use crate::gfx::fonts::BASIC_5PX;
+use crate::gfx::primitives::*;
+use crate::mn12864k::Framebuffer;
+use crate::music::{duty_limited_on_time_us, pitch_name, pitch_period_ms, MusicPlayer, PlayerState, REST, SONGS, SONG_COUNT};
+
+use super::{invalidate_limiter_status, render_app_frame, render_limiter_status, update_app_frame, AppView, UiFrameButton, View};
+
+const ROLL_UPPER_LEFT: (isize, isize) = (3, 12);
+const ROLL_LOWER_RIGHT: (isize, isize) = (124, 43);
+const PLAYHEAD_X: isize = 30;
+const PIXELS_PER_SIXTEENTH: f32 = 3.0;
+
+#[derive(Copy, Clone, PartialEq)]
+enum KnobTarget {
+    Power,
+    OnTime,
+}
+
+pub struct MusicView {
+    buttons: [UiFrameButton; 3],
+    player: MusicPlayer,
+    song: usize,
+    knob: KnobTarget,
+    power: u32,
+    on_time: u32,
+}
+
+impl Default for MusicView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MusicView {
+    pub fn new() -> Self {
+        Self {
+            buttons: [UiFrameButton::new("Back"), UiFrameButton::new("Play"), UiFrameButton::new("Next")],
+            player: MusicPlayer::new(),
+            song: 0,
+            knob: KnobTarget::Power,
+            power: 20,
+            on_time: 100,
+        }
+    }
+
+    fn render_piano_roll(&self, framebuffer: &mut Framebuffer) {
+        draw_rect(framebuffer, ROLL_UPPER_LEFT, ROLL_LOWER_RIGHT, true);
+        let song = &SONGS[self.song];
+        let Some((low, high)) = song.pitch_range() else {
+            return;
+        };
+        let rows = (ROLL_LOWER_RIGHT.1 - ROLL_UPPER_LEFT.1 - 4) as f32;
+        let semitones = (high - low).max(1) as f32;
+        let pitch_y = |pitch: u8| ROLL_LOWER_RIGHT.1 - 2 - ((pitch - low) as f32 / semitones * rows) as isize;
+
+        let mut roll = framebuffer.dt_ref().mask(RectMask {
+            upper_left: (ROLL_UPPER_LEFT.0 + 1, ROLL_UPPER_LEFT.1 + 1),
+            lower_right: (ROLL_LOWER_RIGHT.0 - 1, ROLL_LOWER_RIGHT.1 - 1),
+        });
+        let scroll = self.player.position_sixteenths() * PIXELS_PER_SIXTEENTH;
+        let playing = self.player.current_note().map(|(index, _)| index);
+        let mut start = 0u32;
+        for (index, note) in song.notes.iter().enumerate() {
+            let x_start = PLAYHEAD_X + (start as f32 * PIXELS_PER_SIXTEENTH - scroll) as isize;
+            let x_end = x_start + (note.length as f32 * PIXELS_PER_SIXTEENTH) as isize - 2;
+            start += note.length as u32;
+            if note.pitch == REST || x_end < ROLL_UPPER_LEFT.0 || x_start > ROLL_LOWER_RIGHT.0 {
+                continue;
+            }
+            let y = pitch_y(note.pitch);
+            if Some(index) == playing {
+                draw_filled_rect(&mut roll, (x_start, y - 1), (x_end, y + 1), true);
+            } else {
+                draw_rect(&mut roll, (x_start, y - 1), (x_end, y + 1), true);
+            }
+        }
+        for y in (ROLL_UPPER_LEFT.1 + 1..ROLL_LOWER_RIGHT.1).step_by(2) {
+            roll.set_pixel((PLAYHEAD_X - 1, y), true);
+        }
+    }
+}
+
+impl AppView for MusicView {
+    fn start(&mut self) {
+        self.buttons.iter_mut().for_each(|button| button.reset());
+        self.buttons[1].text = "Play";
+        self.player = MusicPlayer::new();
+        self.knob = KnobTarget::Power;
+    }
+
+    fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
+        com.inbox.clear();
+        update_app_frame(&input_state, &mut self.buttons);
+        if shared_state.limiter_halt().is_some() {
+            self.player.stop(com);
+        }
+
+        if input_state.encoder.button.pressed {
+            self.knob = match self.knob {
+                KnobTarget::Power => KnobTarget::OnTime,
+                KnobTarget::OnTime => KnobTarget::Power,
+            };
+        }
+        if input_state.encoder.delta != 0 {
+            match self.knob {
+                KnobTarget::Power => {
+                    self.power = (self.power as i32 + input_state.encoder.delta).clamp(0, 100) as u32;
+                    self.player.set_power(self.power as f32 / 100.0, com);
+                },
+                // takes effect from the next song
+                KnobTarget::OnTime => self.on_time = (self.on_time as i32 + input_state.encoder.delta * 10).clamp(10, 1000) as u32,
+            }
+        }
+
+        if self.buttons[1].press {
+            if self.player.state() == PlayerState::Playing {
+                self.player.stop(com);
+            } else {
+                shared_state.log(Severity::Info, format_args!("Playing {}", SONGS[self.song].name));
+                self.player.play(&SONGS[self.song], self.on_time as u16, self.power as f32 / 100.0, com);
+            }
+        }
+        if self.buttons[2].press {
+            self.player.stop(com);
+            self.player = MusicPlayer::new();
+            self.song = (self.song + 1) % SONG_COUNT;
+        }
+
+        let previous_state = self.player.state();
+        self.player.update(dt_micros, com);
+        // the piano roll scrolls while a song plays
+        if self.player.state() == PlayerState::Playing || self.player.state() != previous_state {
+            shared_state.invalidate_all();
+        }
+        invalidate_limiter_status(shared_state);
+        self.buttons[1].text = if self.player.state() == PlayerState::Playing { "Stop" } else { "Play" };
+
+        if self.buttons[0].press {
+            self.player.stop(com);
+            com.outbox.push_back(ControllerMessage::Stop);
+            Some(View::ViewPicker)
+        } else {
+            None
+        }
+    }
+
+    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+        render_app_frame(framebuffer, SONGS[self.song].name, &mut self.buttons);
+        let status_left = render_limiter_status(framebuffer, &shared_state.limiter, &shared_state.warning_blink);
+        let index_string = format!("{}/{}", self.song + 1, SONG_COUNT);
+        BASIC_5PX.draw_text_line(framebuffer, (status_left - 5 - BASIC_5PX.get_text_width(&index_string), 7), &index_string, true);
+
+        self.render_piano_roll(framebuffer);
+
+        let note_string = match self.player.current_note() {
+            Some((_, note)) if note.pitch != REST => {
+                let (name, octave) = pitch_name(note.pitch);
+                let period_ms = pitch_period_ms(note.pitch);
+                format!("{}{} {}Hz {}us", name, octave, 1000 / period_ms, duty_limited_on_time_us(self.on_time as u16, period_ms))
+            },
+            Some(_) => String::from("Rest"),
+            None if self.player.state() == PlayerState::Finished => String::from("Done"),
+            None => String::from("Stopped"),
+        };
+        BASIC_5PX.draw_text_line(framebuffer, (4, 50), &note_string, true);
+
+        let knob_string = match self.knob {
+            KnobTarget::Power => format!("Pwr {}%", self.power),
+            KnobTarget::OnTime => format!("On {}us", self.on_time),
+        };
+        BASIC_5PX.draw_text_line(framebuffer, (124 - BASIC_5PX.get_text_width(&knob_string), 50), &knob_string, true);
+    }
+}
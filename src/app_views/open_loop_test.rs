@@ -1,169 +1,262 @@
-use alloc::{format, string::String};
-use libm::roundf;
-use qcw_com::{Parameter, ParameterValue, RemoteMessage};
+use qcw_com::{ControllerMessage, Parameter, ParameterValue, RemoteMessage};
 
-use crate::{gfx::{fonts::BASIC_5PX, primitives::draw_hline}, ui::ListPicker};
+use crate::application::{AppSharedState, ComState, InputState};
+use crate::gfx::fonts::BASIC_5PX;
+use crate::mn12864k::Framebuffer;
+use crate::parameters;
+use crate::ui::{draw_text_in, Align, DigitEditResult, DigitEditor, FocusEvent, FocusManager, Focusable, Length, NumericField, Rect, LINE_HEIGHT};
 
-use super::{render_app_frame, update_app_frame, AppView, UiFrameButton};
+use super::{invalidate_content, invalidate_limiter_status, render_app_frame, render_limiter_status, update_app_frame, AppView, UiFrameButton, View};
 
 const PARAMETER_COUNT: usize = 4;
+const ON_TIME: usize = 0;
+const OFF_TIME: usize = 1;
+const FREQUENCY: usize = 2;
+const POWER: usize = 3;
+
+const PARAMETER_LABELS: [&str; PARAMETER_COUNT] = ["On Time", "Off Time", "Frequency", "Power"];
 
 const PARAMETER_REQUEST_INTERVAL_US: u64 = 100_000;
 const KEEPALIVE_INTERVAL_US: u64 = 10_000;
 
+fn field_parameter(index: usize) -> Parameter {
+    match index {
+        ON_TIME => Parameter::OnTime,
+        OFF_TIME => Parameter::OffTime,
+        FREQUENCY => Parameter::StartupFrequency,
+        _ => Parameter::FlatPower,
+    }
+}
+
+fn field_index(parameter: Parameter) -> Option<usize> {
+    match parameter {
+        Parameter::OnTime => Some(ON_TIME),
+        Parameter::OffTime => Some(OFF_TIME),
+        Parameter::StartupFrequency => Some(FREQUENCY),
+        Parameter::FlatPower => Some(POWER),
+        _ => None,
+    }
+}
+
 pub struct OpenLoopTestView {
-    frame_buttons: [UiFrameButton; 2],
-    editing: bool,
+    frame_buttons: [UiFrameButton; 3],
     running: bool,
     t_last_keepalive: u64,
     t_last_getparams: u64,
     t_elapsed: u64,
-    on_time: u32,
-    off_time: u32,
-    frequency: u32,
-    power: u32,
-    parameter_list: ListPicker<Parameter, PARAMETER_COUNT>,
+    focus: FocusManager,
+    on_time: NumericField,
+    off_time: NumericField,
+    frequency: NumericField,
+    power: NumericField,
+    // typing in an exact value for one of the fields, by index
+    digit_editor: Option<(usize, DigitEditor)>,
+}
+
+impl Default for OpenLoopTestView {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl OpenLoopTestView {
     pub fn new() -> Self {
         Self {
             frame_buttons: [
-                UiFrameButton::new("Back"), UiFrameButton::new("Run"), 
+                UiFrameButton::new("Back"), UiFrameButton::new("Run"), UiFrameButton::new("Type"),
             ],
-            editing: false,
             running: false,
             t_elapsed: 0,
             t_last_keepalive: 0,
             t_last_getparams: 0,
-            on_time: 100,
-            off_time: 100,
-            frequency: 400,
-            power: 0,
-            parameter_list: ListPicker::new([
-                (Parameter::OnTime, "On Time"),
-                (Parameter::OffTime, "Off Time"),
-                (Parameter::StartupFrequency, "Frequency"),
-                (Parameter::FlatPower, "Power"),
-            ], (4, 20), 45, 30),
+            focus: FocusManager::new(),
+            on_time: parameters::numeric_field(Parameter::OnTime, 100, 10).unwrap(),
+            off_time: parameters::numeric_field(Parameter::OffTime, 100, 10).unwrap(),
+            frequency: parameters::numeric_field(Parameter::StartupFrequency, 400, 1).unwrap(),
+            power: parameters::numeric_field(Parameter::FlatPower, 0, 1).unwrap(),
+            digit_editor: None,
+        }
+    }
+
+    fn field(&self, index: usize) -> &NumericField {
+        match index {
+            ON_TIME => &self.on_time,
+            OFF_TIME => &self.off_time,
+            FREQUENCY => &self.frequency,
+            _ => &self.power,
         }
     }
+
+    fn field_mut(&mut self, index: usize) -> &mut NumericField {
+        match index {
+            ON_TIME => &mut self.on_time,
+            OFF_TIME => &mut self.off_time,
+            FREQUENCY => &mut self.frequency,
+            _ => &mut self.power,
+        }
+    }
+
+    // sends a field's value and asks for it back, so the field shows what the controller settled on
+    fn send_parameter(&self, index: usize, com: &mut ComState<'_>) {
+        if let Some(value) = parameters::from_fixed(field_parameter(index), self.field(index).value()) {
+            com.outbox.push_back(ControllerMessage::SetParam(value));
+            com.outbox.push_back(ControllerMessage::GetParam(field_parameter(index)));
+        }
+    }
+
+    fn set_button_labels(&mut self, labels: [&'static str; 3]) {
+        for (button, label) in self.frame_buttons.iter_mut().zip(labels) {
+            button.text = label;
+        }
+    }
+
+    fn restore_button_labels(&mut self) {
+        self.set_button_labels(["Back", if self.running { "Stop" } else { "Run" }, "Type"]);
+    }
 }
 
 impl AppView for OpenLoopTestView {
     fn start(&mut self) {
         self.frame_buttons.iter_mut().for_each(|button| button.reset());
-        self.frame_buttons[1].text = "Run";
         self.running = false;
-        self.editing = false;
+        self.restore_button_labels();
+        self.focus.reset();
+        self.digit_editor = None;
         self.t_elapsed = 0;
         self.t_last_keepalive = 0;
         self.t_last_getparams = 0;
     }
 
-    fn update(&mut self, dt_micros: u64, input_state: crate::application::InputState, com: &mut crate::application::ComState<'_>, shared_state: &mut crate::application::AppSharedState) -> Option<super::View> {
+    fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
         self.t_elapsed += dt_micros;
         update_app_frame(&input_state, &mut self.frame_buttons);
 
-        if !self.editing {
-            self.editing = self.parameter_list.update(&input_state.encoder).is_some();
+        // the digit editor has the encoder and the frame buttons to itself while it's open
+        let typing = self.digit_editor.is_some();
+        if let Some((index, editor)) = &mut self.digit_editor {
+            let index = *index;
+            let result = editor.update(&input_state);
+            let labels = editor.button_labels();
+            match result {
+                DigitEditResult::Editing => self.set_button_labels(labels),
+                DigitEditResult::Done(value) => {
+                    self.field_mut(index).set_value(value);
+                    self.send_parameter(index, com);
+                    self.digit_editor = None;
+                    self.restore_button_labels();
+                },
+                DigitEditResult::Cancelled => {
+                    self.digit_editor = None;
+                    self.restore_button_labels();
+                },
+            }
         } else {
-            if input_state.encoder.button.pressed {
-                self.editing = false;
+            let event = {
+                let mut widgets: [&mut dyn Focusable; PARAMETER_COUNT] = [&mut self.on_time, &mut self.off_time, &mut self.frequency, &mut self.power];
+                self.focus.update(dt_micros, &input_state.encoder, &mut widgets)
+            };
+            match event {
+                FocusEvent::Changed(index) | FocusEvent::Cancel(index) => self.send_parameter(index, com),
+                _ => {}
+            }
+            // a hold cancels an edit without the button being released
+            if !matches!(event, FocusEvent::None) {
+                shared_state.invalidate_all();
             }
-        }
 
-        while let Some(message) = com.inbox.pop_front() {
-            match message {
-                RemoteMessage::GetParamResult(param_value) => {
-                    match param_value {
-                        ParameterValue::OnTimeUs(on_time) => self.on_time = on_time as u32,
-                        ParameterValue::OffTimeMs(off_time) => self.off_time = off_time as u32,
-                        ParameterValue::StartupFrequencykHz(frequency) => self.frequency = frequency as u32,
-                        ParameterValue::FlatPower(power) => self.power = (roundf(power * 100.0) as i32).clamp(0, 100) as u32,
-                        _ => {}
-                    }
-                },
-                _ => {}
+            if self.frame_buttons[2].press && !self.focus.editing() {
+                let index = self.focus.index();
+                let editor = parameters::from_fixed(field_parameter(index), self.field(index).value())
+                    .and_then(|value| parameters::digit_editor(&value));
+                if let Some(editor) = editor {
+                    self.set_button_labels(editor.button_labels());
+                    self.digit_editor = Some((index, editor));
+                }
             }
         }
 
-        if self.editing && input_state.encoder.delta != 0 {
-            match self.parameter_list.selected() {
-                Parameter::OnTime => {
-                    self.on_time = (self.on_time as i32 + input_state.encoder.delta * 10).clamp(0, 1000) as u32;
-                    com.outbox.push_back(qcw_com::ControllerMessage::SetParam(ParameterValue::OnTimeUs(self.on_time as u16)));
-                    com.outbox.push_back(qcw_com::ControllerMessage::GetParam(Parameter::OnTime));
-                },
-                Parameter::OffTime => {
-                    self.off_time = (self.off_time as i32 + input_state.encoder.delta * 10).clamp(10, 5000) as u32;
-                    com.outbox.push_back(qcw_com::ControllerMessage::SetParam(ParameterValue::OffTimeMs(self.off_time as u16)));
-                    com.outbox.push_back(qcw_com::ControllerMessage::GetParam(Parameter::OffTime));
-                },
-                Parameter::StartupFrequency => {
-                    self.frequency = (self.frequency as i32 + input_state.encoder.delta).clamp(300, 700) as u32;
-                    com.outbox.push_back(qcw_com::ControllerMessage::SetParam(ParameterValue::StartupFrequencykHz(self.frequency as f32)));
-                    com.outbox.push_back(qcw_com::ControllerMessage::GetParam(Parameter::StartupFrequency));
-                },
-                Parameter::FlatPower => {
-                    self.power = (self.power as i32 + input_state.encoder.delta).clamp(0, 100) as u32;
-                    com.outbox.push_back(qcw_com::ControllerMessage::SetParam(ParameterValue::FlatPower((self.power as f32) / 100.0f32)));
-                    com.outbox.push_back(qcw_com::ControllerMessage::GetParam(Parameter::FlatPower));
-                },
-                _ => {}
+        while let Some(message) = com.inbox.pop_front() {
+            // a field being edited keeps the value the user is dialing in
+            if let RemoteMessage::GetParamResult(param_value) = message {
+                if let Some((parameter, value)) = parameters::to_fixed(&param_value) {
+                    if let Some(index) = field_index(parameter) {
+                        if !self.focus.is_editing(index) && self.field(index).value() != value {
+                            self.field_mut(index).set_value(value);
+                            invalidate_content(shared_state);
+                        }
+                    }
+                }
             }
         }
 
         if (self.t_elapsed - self.t_last_getparams) >= PARAMETER_REQUEST_INTERVAL_US {
-            com.outbox.push_back(qcw_com::ControllerMessage::GetParam(Parameter::OnTime));
-            com.outbox.push_back(qcw_com::ControllerMessage::GetParam(Parameter::OffTime));
-            com.outbox.push_back(qcw_com::ControllerMessage::GetParam(Parameter::StartupFrequency));
-            com.outbox.push_back(qcw_com::ControllerMessage::GetParam(Parameter::FlatPower));
+            com.outbox.push_back(ControllerMessage::GetParam(Parameter::OnTime));
+            com.outbox.push_back(ControllerMessage::GetParam(Parameter::OffTime));
+            com.outbox.push_back(ControllerMessage::GetParam(Parameter::StartupFrequency));
+            com.outbox.push_back(ControllerMessage::GetParam(Parameter::FlatPower));
             self.t_last_getparams = self.t_elapsed;
         }
 
-        if self.frame_buttons[1].press {
+        // a refused run, or one the limiter stopped, leaves the coil idle
+        if self.running && shared_state.limiter_halt().is_some() && !shared_state.limiter.running() {
+            self.running = false;
+            if !typing {
+                self.restore_button_labels();
+            }
+        }
+
+        if !typing && self.frame_buttons[1].press {
             self.running = !self.running;
             if self.running {
-                com.outbox.push_back(qcw_com::ControllerMessage::SetParam(ParameterValue::RunMode(qcw_com::RunMode::OpenLoop)));
-                com.outbox.push_back(qcw_com::ControllerMessage::Run);
-                self.frame_buttons[1].text = "Stop";
+                com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::RunMode(qcw_com::RunMode::OpenLoop)));
+                com.outbox.push_back(ControllerMessage::Run);
             } else {
-                com.outbox.push_back(qcw_com::ControllerMessage::Stop);
-                self.frame_buttons[1].text = "Run";
+                com.outbox.push_back(ControllerMessage::Stop);
             }
+            self.restore_button_labels();
         }
 
         if self.running && ((self.t_elapsed - self.t_last_keepalive) >= KEEPALIVE_INTERVAL_US) {
-            com.outbox.push_back(qcw_com::ControllerMessage::KeepAlive);
+            com.outbox.push_back(ControllerMessage::KeepAlive);
             self.t_last_keepalive = self.t_elapsed;
         }
 
-        if self.frame_buttons[0].press {
-            com.outbox.push_back(qcw_com::ControllerMessage::Stop);
-            Some(super::View::ViewPicker)
+        invalidate_limiter_status(shared_state);
+
+        if !typing && self.frame_buttons[0].press {
+            com.outbox.push_back(ControllerMessage::Stop);
+            Some(View::ViewPicker)
         } else {
             None
         }
     }
 
-    fn render(&mut self, framebuffer: &mut crate::mn12864k::Framebuffer, shared_state: &mut crate::application::AppSharedState) {
+    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+        if let Some((index, editor)) = &self.digit_editor {
+            render_app_frame(framebuffer, PARAMETER_LABELS[*index], &mut self.frame_buttons);
+            render_limiter_status(framebuffer, &shared_state.limiter, &shared_state.warning_blink);
+            let content = Rect::from_corners((6, 14), (124, 51));
+            editor.render(framebuffer, content.align(editor.size(), Align::Center, Align::Center));
+            return;
+        }
         render_app_frame(framebuffer, "Open Loop Test", &mut self.frame_buttons);
-        BASIC_5PX.draw_text_line(framebuffer, (4, 18), "Param:", true);
-        let param_string = match self.parameter_list.selected() {
-            Parameter::OnTime => format!("Value: {} us", self.on_time),
-            Parameter::OffTime => format!("Value: {} ms", self.off_time),
-            Parameter::StartupFrequency => format!("Value: {} kHz", self.frequency),
-            Parameter::FlatPower => format!("Value: {} %", self.power),
-            _ => String::new(),
-        };
-        BASIC_5PX.draw_text_line(framebuffer, (55, 18), &param_string, true);
-        if self.editing {
-            let text_width = BASIC_5PX.get_text_width(&param_string);
-            draw_hline(framebuffer, 55, 55 + text_width, 20, true);
+        render_limiter_status(framebuffer, &shared_state.limiter, &shared_state.warning_blink);
+
+        let content = Rect::from_corners((6, 14), (124, 51));
+        let [parameters, status] = content.columns([Length::Fill(1), Length::Fixed(34)], 6);
+        let rows = parameters.rows([Length::Fixed(LINE_HEIGHT); PARAMETER_COUNT], 2);
+        for (index, row) in rows.iter().enumerate() {
+            let field = self.field(index);
+            let [label, value] = row.columns([Length::Fill(1), Length::Fixed(field.size().0)], 4);
+            draw_text_in(framebuffer, &BASIC_5PX, label, PARAMETER_LABELS[index], Align::Start, true);
+            field.render(framebuffer, value.position);
+        }
+
+        let status_row = Rect::new(status.position, (status.size.0, LINE_HEIGHT));
+        draw_text_in(framebuffer, &BASIC_5PX, status_row, if self.running { "Running" } else { "Stopped" }, Align::End, true);
+        let hint_row = Rect::new((status.position.0, status.bottom() + 1 - LINE_HEIGHT), (status.size.0, LINE_HEIGHT));
+        if self.focus.editing() {
+            draw_text_in(framebuffer, &BASIC_5PX, hint_row, "hold=undo", Align::End, true);
         }
-        BASIC_5PX.draw_text_line(framebuffer, (55, 26), if self.running { "Running" } else { "Stopped" }, true);
-        self.parameter_list.render(framebuffer);
     }
 }
-
@@ -3,9 +3,13 @@ use crate::application::{AppSharedState, ComState, InputState};
 use crate::gfx::primitives::*;
 use crate::gfx::fonts::BASIC_5PX;
 use crate::mn12864k::Framebuffer;
+use crate::parameters;
+use crate::ui::{draw_text_in, Align, DigitEditResult, DigitEditor, Rect, Slider, LINE_HEIGHT};
+use crate::event_log::Severity;
+use crate::sweep::{most_stable_point, SweepConfig, SweepMetric, SweepParameter, SweepPoint, SweepRunner, SweepState};
 use qcw_com::{ControllerMessage, Parameter, ParameterValue, RemoteMessage};
 
-use super::{render_app_frame, update_app_frame, AppView, UiFrameButton, View};
+use super::{invalidate_limiter_status, render_app_frame, render_limiter_status, update_app_frame, AppView, UiFrameButton, View};
 
 enum PhaseTuningState {
     Init,
@@ -13,6 +17,61 @@ enum PhaseTuningState {
     RunningDisabled,
     RunningEnabled,
     Disabling(bool),
+    AutoTuneSetup,
+    AutoTuning(AutoTuneStage),
+    AutoTuneReview(Option<i16>),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum AutoTuneStage {
+    Coarse,
+    Fine,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum AutoTuneGoal {
+    HighestCurrent,
+    LowestCurrent,
+    HighestFeedbackFrequency,
+    StableFeedbackFrequency,
+}
+
+impl AutoTuneGoal {
+    fn name(&self) -> &'static str {
+        match self {
+            AutoTuneGoal::HighestCurrent => "Max Current",
+            AutoTuneGoal::LowestCurrent => "Min Current",
+            AutoTuneGoal::HighestFeedbackFrequency => "Max Fb Freq",
+            AutoTuneGoal::StableFeedbackFrequency => "Stable Fb Freq",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            AutoTuneGoal::HighestCurrent => AutoTuneGoal::LowestCurrent,
+            AutoTuneGoal::LowestCurrent => AutoTuneGoal::HighestFeedbackFrequency,
+            AutoTuneGoal::HighestFeedbackFrequency => AutoTuneGoal::StableFeedbackFrequency,
+            AutoTuneGoal::StableFeedbackFrequency => AutoTuneGoal::HighestCurrent,
+        }
+    }
+
+    fn previous(&self) -> Self {
+        match self {
+            AutoTuneGoal::HighestCurrent => AutoTuneGoal::StableFeedbackFrequency,
+            AutoTuneGoal::LowestCurrent => AutoTuneGoal::HighestCurrent,
+            AutoTuneGoal::HighestFeedbackFrequency => AutoTuneGoal::LowestCurrent,
+            AutoTuneGoal::StableFeedbackFrequency => AutoTuneGoal::HighestFeedbackFrequency,
+        }
+    }
+
+    fn best_point(&self, runner: &SweepRunner) -> Option<SweepPoint> {
+        match self {
+            AutoTuneGoal::HighestCurrent => runner.optimum(SweepMetric::HighestCurrent),
+            AutoTuneGoal::LowestCurrent => runner.optimum(SweepMetric::LowestCurrent),
+            AutoTuneGoal::HighestFeedbackFrequency => runner.optimum(SweepMetric::HighestFeedbackFrequency),
+            AutoTuneGoal::StableFeedbackFrequency => most_stable_point(runner.points(), |point| point.feedback_frequency),
+        }
+    }
 }
 
 pub struct PhaseTuningView {
@@ -22,10 +81,36 @@ pub struct PhaseTuningView {
     delay_dirty: bool,
     t_last_keepalive: u64,
     t_elapsed: u64,
+    autotune_goal: AutoTuneGoal,
+    autotune_runner: SweepRunner,
+    autotune_original_delay: i16,
+    delay_slider: Slider,
+    // how long the encoder has been held, and whether that hold already switched modes
+    encoder_hold_us: u64,
+    hold_switched: bool,
+    // typing in an exact delay, opened by clicking the encoder while tuning by hand
+    digit_editor: Option<DigitEditor>,
 }
 
 const TUNING_RANGE: i16 = 400;
+// burst settings for tuning, by hand or automatically
+const RUN_POWER: f32 = 1.0;
+const RUN_ON_TIME_US: u16 = 600;
+const RUN_OFF_TIME_MS: u16 = 300;
 const KEEPALIVE_INTERVAL_US: u64 = 10_000;
+// holding the encoder this long switches between tuning by hand and auto-tune
+const MODE_HOLD_US: u64 = 600_000;
+
+// the coarse pass covers the whole tuning range, the fine pass one coarse step either side of its best point
+const AUTOTUNE_COARSE_STEP: i32 = 50;
+const AUTOTUNE_FINE_STEP: i32 = 5;
+const AUTOTUNE_DWELL_MS: u32 = 300;
+
+impl Default for PhaseTuningView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl PhaseTuningView {
     pub fn new() -> Self {
@@ -36,6 +121,133 @@ impl PhaseTuningView {
             delay_dirty: false,
             t_elapsed: 0,
             t_last_keepalive: 0,
+            autotune_goal: AutoTuneGoal::HighestCurrent,
+            autotune_runner: SweepRunner::new(SweepConfig::new(SweepParameter::PhaseDelay)),
+            autotune_original_delay: 0,
+            delay_slider: Slider::new(0, -(TUNING_RANGE as i32), TUNING_RANGE as i32, 1, 81),
+            encoder_hold_us: 0,
+            hold_switched: false,
+            digit_editor: None,
+        }
+    }
+
+    fn enter_manual(&mut self) {
+        self.buttons[1].text = "Run";
+        self.buttons[2].text = "Reset";
+        self.state = PhaseTuningState::RunningDisabled;
+    }
+
+    fn enter_autotune_setup(&mut self) {
+        self.buttons[1].text = "Start";
+        self.buttons[2].text = "---";
+        self.state = PhaseTuningState::AutoTuneSetup;
+    }
+
+    fn restore_button_labels(&mut self) {
+        let labels = match self.state {
+            PhaseTuningState::RunningEnabled => ["Back", "Stop", "Reset"],
+            _ => ["Back", "Run", "Reset"],
+        };
+        for (button, label) in self.buttons.iter_mut().zip(labels) {
+            button.text = label;
+        }
+    }
+
+    fn set_phase_delay(&mut self, phase_delay: i16, com: &mut ComState<'_>) {
+        if phase_delay != self.phase_delay {
+            self.phase_delay = phase_delay;
+            self.delay_dirty = true;
+            com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(self.phase_delay)));
+            com.outbox.push_back(ControllerMessage::GetParam(Parameter::DelayCompensation));
+        }
+    }
+
+    fn push_run_settings(com: &mut ComState<'_>) {
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::FlatPower(RUN_POWER)));
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::OnTimeUs(RUN_ON_TIME_US)));
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::OffTimeMs(RUN_OFF_TIME_MS)));
+    }
+
+    fn start_autotune_pass(&mut self, stage: AutoTuneStage, center: i32, com: &mut ComState<'_>) {
+        let range = TUNING_RANGE as i32;
+        let config = match stage {
+            AutoTuneStage::Coarse => SweepConfig {
+                parameter: SweepParameter::PhaseDelay,
+                start: -range,
+                end: range,
+                step: AUTOTUNE_COARSE_STEP,
+                dwell_ms: AUTOTUNE_DWELL_MS,
+                on_time_us: RUN_ON_TIME_US,
+                off_time_ms: RUN_OFF_TIME_MS,
+                power: RUN_POWER,
+            },
+            AutoTuneStage::Fine => SweepConfig {
+                parameter: SweepParameter::PhaseDelay,
+                start: (center - AUTOTUNE_COARSE_STEP).max(-range),
+                end: (center + AUTOTUNE_COARSE_STEP).min(range),
+                step: AUTOTUNE_FINE_STEP,
+                dwell_ms: AUTOTUNE_DWELL_MS,
+                on_time_us: RUN_ON_TIME_US,
+                off_time_ms: RUN_OFF_TIME_MS,
+                power: RUN_POWER,
+            },
+        };
+        self.autotune_runner.start(config, com);
+        self.state = PhaseTuningState::AutoTuning(stage);
+    }
+
+    fn update_autotune(&mut self, dt_micros: u64, stage: AutoTuneStage, com: &mut ComState<'_>, shared_state: &mut AppSharedState) {
+        while let Some(message) = com.inbox.pop_front() {
+            self.autotune_runner.handle_message(&message);
+        }
+        if self.buttons[1].press {
+            self.autotune_runner.abort(com, "by user");
+        }
+        self.autotune_runner.update(dt_micros, com);
+        match (self.autotune_runner.state(), stage) {
+            (SweepState::Finished, AutoTuneStage::Coarse) => {
+                match self.autotune_goal.best_point(&self.autotune_runner) {
+                    Some(point) => self.start_autotune_pass(AutoTuneStage::Fine, point.value, com),
+                    None => self.finish_autotune(None, shared_state),
+                }
+            },
+            (SweepState::Finished, AutoTuneStage::Fine) => {
+                let best = self.autotune_goal.best_point(&self.autotune_runner).map(|point| point.value as i16);
+                self.finish_autotune(best, shared_state);
+            },
+            (SweepState::Aborted(reason), _) => {
+                shared_state.log(Severity::Warning, format_args!("Auto-tune aborted: {}", reason));
+                self.finish_autotune(None, shared_state);
+            },
+            _ => {},
+        }
+    }
+
+    fn finish_autotune(&mut self, best: Option<i16>, shared_state: &mut AppSharedState) {
+        if let Some(best) = best {
+            shared_state.log(Severity::Info, format_args!("Auto-tune suggests {}ns", best));
+            self.buttons[1].text = "Accept";
+        } else {
+            self.buttons[1].text = "OK";
+        }
+        self.buttons[2].text = "Reject";
+        self.state = PhaseTuningState::AutoTuneReview(best);
+    }
+
+    // states that move on, or show progress, without any input
+    fn busy(&self) -> bool {
+        matches!(self.state, PhaseTuningState::Init | PhaseTuningState::Disabling(_) | PhaseTuningState::AutoTuning(_))
+    }
+
+    // the delay the controller currently has, or is being tested with
+    fn displayed_phase_delay(&self) -> i16 {
+        match self.state {
+            PhaseTuningState::AutoTuning(_) => {
+                let (point, total) = self.autotune_runner.progress();
+                self.autotune_runner.config().point_value(point.min(total.saturating_sub(1))) as i16
+            },
+            PhaseTuningState::AutoTuneReview(Some(best)) => best,
+            _ => self.phase_delay,
         }
     }
 }
@@ -47,11 +259,75 @@ impl AppView for PhaseTuningView {
         self.buttons[1].text = "---";
         self.phase_delay = 0;
         self.delay_dirty = false;
+        self.encoder_hold_us = 0;
+        self.hold_switched = false;
+        self.digit_editor = None;
     }
 
     fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
         self.t_elapsed += dt_micros;
         update_app_frame(&input_state, &mut self.buttons);
+
+        // the editor takes the input while open, the run carries on
+        let typing = self.digit_editor.is_some();
+        if let Some(editor) = &mut self.digit_editor {
+            let result = editor.update(&input_state);
+            let labels = editor.button_labels();
+            match result {
+                DigitEditResult::Editing => {
+                    for (button, label) in self.buttons.iter_mut().zip(labels) {
+                        button.text = label;
+                    }
+                },
+                DigitEditResult::Done(value) => {
+                    self.digit_editor = None;
+                    self.set_phase_delay(value as i16, com);
+                    self.restore_button_labels();
+                },
+                DigitEditResult::Cancelled => {
+                    self.digit_editor = None;
+                    self.restore_button_labels();
+                },
+            }
+            self.buttons.iter_mut().for_each(|button| button.press = false);
+        }
+
+        if let Some(reason) = shared_state.limiter_halt() {
+            match self.state {
+                PhaseTuningState::RunningEnabled => self.state = PhaseTuningState::Disabling(false),
+                PhaseTuningState::AutoTuning(_) => self.autotune_runner.abort(com, reason),
+                _ => {},
+            }
+        }
+
+        // a hold switches modes while the coil is idle, and its release doesn't open the editor
+        if input_state.encoder.button.down {
+            self.encoder_hold_us += dt_micros;
+        } else {
+            self.encoder_hold_us = 0;
+        }
+        let hold_released = input_state.encoder.button.released && self.hold_switched;
+        if hold_released {
+            self.hold_switched = false;
+        }
+        if !typing && !self.hold_switched && self.encoder_hold_us >= MODE_HOLD_US {
+            match self.state {
+                PhaseTuningState::RunningDisabled => {
+                    self.enter_autotune_setup();
+                    self.hold_switched = true;
+                },
+                PhaseTuningState::AutoTuneSetup => {
+                    self.enter_manual();
+                    self.hold_switched = true;
+                },
+                _ => {},
+            }
+        }
+
+        if self.busy() || !com.inbox.is_empty() {
+            shared_state.invalidate_all();
+        }
+        invalidate_limiter_status(shared_state);
         let control_enabled = match self.state {
             PhaseTuningState::Init => {
                 self.state = PhaseTuningState::AwaitingParams;
@@ -61,14 +337,9 @@ impl AppView for PhaseTuningView {
             },
             PhaseTuningState::AwaitingParams => {
                 while let Some(message) = com.inbox.pop_front() {
-                    match message {
-                        RemoteMessage::GetParamResult(ParameterValue::DelayCompensationNS(value)) => {
-                            self.phase_delay = value;
-                            self.state = PhaseTuningState::RunningDisabled;
-                            self.buttons[1].text = "Run";
-                            self.buttons[2].text = "Reset";
-                        },
-                        _ => {}
+                    if let RemoteMessage::GetParamResult(ParameterValue::DelayCompensationNS(value)) = message {
+                        self.phase_delay = value;
+                        self.enter_manual();
                     }
                 }
                 false
@@ -79,9 +350,7 @@ impl AppView for PhaseTuningView {
                     self.state = PhaseTuningState::Init;
                 }
                 if self.buttons[1].press {
-                    com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::FlatPower(1.0)));
-                    com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::OnTimeUs(600)));
-                    com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::OffTimeMs(300)));
+                    Self::push_run_settings(com);
                     com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::RunMode(qcw_com::RunMode::TestClosedLoop)));
                     com.outbox.push_back(ControllerMessage::Run);
                     self.buttons[1].text = "Stop";
@@ -116,31 +385,64 @@ impl AppView for PhaseTuningView {
                     self.state = PhaseTuningState::RunningDisabled;
                 }
                 false
-            }
+            },
+            PhaseTuningState::AutoTuneSetup => {
+                com.inbox.clear();
+                for _ in 0..input_state.encoder.delta.abs() {
+                    self.autotune_goal = if input_state.encoder.delta > 0 { self.autotune_goal.next() } else { self.autotune_goal.previous() };
+                }
+                if self.buttons[1].press {
+                    shared_state.log(Severity::Info, format_args!("Auto-tune for {}", self.autotune_goal.name()));
+                    self.autotune_original_delay = self.phase_delay;
+                    self.buttons[1].text = "Stop";
+                    self.buttons[2].text = "---";
+                    self.start_autotune_pass(AutoTuneStage::Coarse, 0, com);
+                }
+                false
+            },
+            PhaseTuningState::AutoTuning(stage) => {
+                self.update_autotune(dt_micros, stage, com, shared_state);
+                false
+            },
+            PhaseTuningState::AutoTuneReview(best) => {
+                com.inbox.clear();
+                let accepted = match best {
+                    Some(best) if self.buttons[1].press => Some(best),
+                    _ if self.buttons[1].press || self.buttons[2].press => Some(self.autotune_original_delay),
+                    _ => None,
+                };
+                if let Some(delay) = accepted {
+                    // the sweep leaves the last tested delay on the controller, so always send the outcome
+                    self.phase_delay = delay;
+                    com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(delay)));
+                    com.outbox.push_back(ControllerMessage::GetParam(Parameter::DelayCompensation));
+                    self.enter_autotune_setup();
+                }
+                false
+            },
         };
-        if control_enabled {
-            let old_phase_delay = self.phase_delay;
-            self.phase_delay = self.phase_delay.saturating_add(input_state.encoder.delta as i16);
-            self.phase_delay = self.phase_delay.clamp(-TUNING_RANGE, TUNING_RANGE);
-            if input_state.encoder.button.pressed {
-                self.phase_delay = 0;
-            }
-            if self.phase_delay != old_phase_delay {
-                self.delay_dirty = true;
-                com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(self.phase_delay)));
-                com.outbox.push_back(ControllerMessage::GetParam(Parameter::DelayCompensation));
+        if control_enabled && !typing {
+            let phase_delay = self.phase_delay.saturating_add(input_state.encoder.delta as i16);
+            self.set_phase_delay(phase_delay.clamp(-TUNING_RANGE, TUNING_RANGE), com);
+            if input_state.encoder.button.released && !hold_released {
+                self.digit_editor = parameters::digit_editor(&ParameterValue::DelayCompensationNS(self.phase_delay));
+                if let Some(editor) = &self.digit_editor {
+                    for (button, label) in self.buttons.iter_mut().zip(editor.button_labels()) {
+                        button.text = label;
+                    }
+                }
             }
+        }
+        if control_enabled {
             while let Some(message) = com.inbox.pop_front() {
-                match message {
-                    RemoteMessage::GetParamResult(ParameterValue::DelayCompensationNS(value)) => {
-                        self.phase_delay = value as i16;
-                        self.delay_dirty = false;
-                    },
-                    _ => {}
+                if let RemoteMessage::GetParamResult(ParameterValue::DelayCompensationNS(value)) = message {
+                    self.phase_delay = value;
+                    self.delay_dirty = false;
                 }
             }
         }
         if self.buttons[0].press {
+            self.autotune_runner.abort(com, "left view");
             com.outbox.push_back(qcw_com::ControllerMessage::Stop);
             Some(View::ViewPicker)
         } else {
@@ -150,6 +452,12 @@ impl AppView for PhaseTuningView {
 
     fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
         render_app_frame(framebuffer, "Feedback Phase Tuning", &mut self.buttons);
+        render_limiter_status(framebuffer, &shared_state.limiter, &shared_state.warning_blink);
+        if let Some(editor) = &self.digit_editor {
+            let content = Rect::from_corners((6, 14), (124, 51));
+            editor.render(framebuffer, content.align(editor.size(), Align::Center, Align::Center));
+            return;
+        }
 
         let state_string = match self.state {
             PhaseTuningState::Init => "Initializing...",
@@ -157,10 +465,19 @@ impl AppView for PhaseTuningView {
             PhaseTuningState::RunningDisabled => "Disabled",
             PhaseTuningState::RunningEnabled => "Enabled",
             PhaseTuningState::Disabling(_) => "Disabling...",
+            PhaseTuningState::AutoTuneSetup => self.autotune_goal.name(),
+            PhaseTuningState::AutoTuning(AutoTuneStage::Coarse) => "Auto-tune (coarse)",
+            PhaseTuningState::AutoTuning(AutoTuneStage::Fine) => "Auto-tune (fine)",
+            PhaseTuningState::AutoTuneReview(Some(_)) => "Accept suggestion?",
+            PhaseTuningState::AutoTuneReview(None) => "Auto-tune failed",
+        };
+        let state_label = match self.state {
+            PhaseTuningState::AutoTuneSetup => "  Tune For",
+            _ => "        State",
         };
-        BASIC_5PX.draw_text_line(framebuffer, (4, 20), &format!("        State: {}", state_string), true);
+        draw_text_in(framebuffer, &BASIC_5PX, Rect::new((4, 15), (120, LINE_HEIGHT)), &format!("{}: {}", state_label, state_string), Align::Start, true);
 
-        let phase_delay_string = format!("Phase Delay: {}ns", self.phase_delay);
+        let phase_delay_string = format!("Phase Delay: {}ns", self.displayed_phase_delay());
         BASIC_5PX.draw_text_line(framebuffer, (4, 28), &phase_delay_string, true);
         draw_hline(framebuffer, BASIC_5PX.get_text_width("Phase Delay: ") + 4, BASIC_5PX.get_text_width(&phase_delay_string) + 4, 30, true);
 
@@ -168,14 +485,18 @@ impl AppView for PhaseTuningView {
         draw_hline(framebuffer, 64, 63+40, 32, true);
         draw_line(framebuffer, (63, 40), (63, 32), true);
 
-        let cursor_x = 63 + (self.phase_delay as isize * 40) / TUNING_RANGE as isize;
-        draw_line(framebuffer, (cursor_x - 2, 45), (cursor_x, 43), true);
-        draw_line(framebuffer, (cursor_x + 2, 45), (cursor_x, 43), true);
-        draw_line(framebuffer, (cursor_x, 43), (cursor_x, 49), true);
-        
-        draw_hline(framebuffer, 63 - 40, 63 + 40, 49, true);
-        draw_vline(framebuffer, 63-40, 48, 50, true);
-        draw_vline(framebuffer, 63+40, 48, 50, true);
-        draw_vline(framebuffer, 63, 48, 50, true);
+        let hint_row = Rect::new((4, 23), (120, LINE_HEIGHT));
+        match self.state {
+            PhaseTuningState::AutoTuning(_) => {
+                let (measured, total) = self.autotune_runner.progress();
+                BASIC_5PX.draw_text_line(framebuffer, (90, 28), &format!("{}/{}", measured, total), true);
+            },
+            PhaseTuningState::RunningDisabled => draw_text_in(framebuffer, &BASIC_5PX, hint_row, "hold=auto", Align::End, true),
+            PhaseTuningState::AutoTuneSetup => draw_text_in(framebuffer, &BASIC_5PX, hint_row, "hold=manual", Align::End, true),
+            _ => {},
+        }
+
+        self.delay_slider.set_value(self.displayed_phase_delay() as i32);
+        self.delay_slider.render(framebuffer, (63 - 40, 43));
     }
 }
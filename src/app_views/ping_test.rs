@@ -1,5 +1,4 @@
 use alloc::format;
-use alloc::vec::Vec;
 use crate::app_views::AppView;
 use crate::application::AppSharedState;
 use crate::application::ComState;
@@ -8,6 +7,7 @@ use crate::gfx::fonts::BASIC_5PX;
 use crate::mn12864k::Framebuffer;
 use qcw_com::{ControllerMessage, RemoteMessage};
 
+use super::invalidate_content;
 use super::render_app_frame;
 use super::update_app_frame;
 use super::UiFrameButton;
@@ -22,6 +22,12 @@ pub struct PingTestView {
     buttons: [UiFrameButton; 1],
 }
 
+impl Default for PingTestView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PingTestView {
     pub fn new() -> Self {
         Self {
@@ -54,9 +60,11 @@ impl AppView for PingTestView {
             self.received_seq = None;
             com.outbox.push_back(ControllerMessage::Ping(self.sent_seq));
             self.time_last_sent = self.t;
+            invalidate_content(shared_state);
         }
         if let Some(RemoteMessage::Ping(seq)) = com.inbox.pop_front() {
             self.received_seq = Some(seq);
+            invalidate_content(shared_state);
         }
         if self.buttons[0].press {
             Some(View::ViewPicker)
@@ -65,7 +73,7 @@ impl AppView for PingTestView {
         }
     }
 
-    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+    fn render(&mut self, framebuffer: &mut Framebuffer, _shared_state: &mut AppSharedState) {
         render_app_frame(framebuffer, "Fiber Ping Test", &mut self.buttons);
         BASIC_5PX.draw_text_line(framebuffer, (5, 16), &format!("Tx: {:08x}", self.sent_seq), true);
         if let Some(seq) = &self.received_seq {
@@ -0,0 +1,151 @@
+use alloc::format;
+use qcw_com::ControllerMessage;
+
+use crate::application::{AppSharedState, ComState, InputState};
+use crate::event_log::Severity;
+use crate::gfx::draw_target::{RectMask, _DTRef, _Maskable};
+use crate::gfx::fonts::BASIC_5PX;
+use crate::mn12864k::Framebuffer;
+use crate::sequence::{RunnerState, SequenceRunner, SEQUENCES, SEQUENCE_COUNT};
+use crate::ui::{ListPicker, ProgressBar};
+
+use super::{invalidate_limiter_status, render_app_frame, render_limiter_status, update_app_frame, AppView, UiFrameButton, View};
+
+const PANEL_X: isize = 66;
+
+pub struct SequenceView {
+    buttons: [UiFrameButton; 3],
+    picker: ListPicker<usize, SEQUENCE_COUNT>,
+    runner: SequenceRunner,
+    progress_bar: ProgressBar,
+}
+
+impl Default for SequenceView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SequenceView {
+    pub fn new() -> Self {
+        Self {
+            buttons: [UiFrameButton::new("Back"), UiFrameButton::new("Run"), UiFrameButton::new("Abort")],
+            picker: ListPicker::new(core::array::from_fn(|i| (i, SEQUENCES[i].name)), (3, 13), 58, 38),
+            runner: SequenceRunner::new(),
+            progress_bar: ProgressBar::new((125 - PANEL_X, 5)),
+        }
+    }
+
+    fn log_state_change(&self, previous: RunnerState, shared_state: &mut AppSharedState) {
+        let state = self.runner.state();
+        if state == previous {
+            return;
+        }
+        let name = self.runner.sequence().map(|sequence| sequence.name).unwrap_or("");
+        match state {
+            RunnerState::Running if previous == RunnerState::Paused => shared_state.log(Severity::Info, format_args!("Sequence {} resumed", name)),
+            RunnerState::Running => shared_state.log(Severity::Info, format_args!("Sequence {} started", name)),
+            RunnerState::Paused => shared_state.log(Severity::Info, format_args!("Sequence {} paused", name)),
+            RunnerState::Finished => shared_state.log(Severity::Info, format_args!("Sequence {} done", name)),
+            RunnerState::Aborted(reason) => shared_state.log(Severity::Warning, format_args!("{} aborted: {}", name, reason)),
+            RunnerState::Idle => {},
+        }
+    }
+}
+
+impl AppView for SequenceView {
+    fn start(&mut self) {
+        self.buttons.iter_mut().for_each(|button| button.reset());
+        self.buttons[1].text = "Run";
+        self.picker.reset();
+        self.runner = SequenceRunner::new();
+    }
+
+    fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
+        update_app_frame(&input_state, &mut self.buttons);
+        let previous_state = self.runner.state();
+
+        let received = !com.inbox.is_empty();
+        while let Some(message) = com.inbox.pop_front() {
+            self.runner.handle_message(&message);
+        }
+        if let Some(reason) = shared_state.limiter_halt() {
+            self.runner.abort(com, reason);
+        }
+
+        self.picker.animate(dt_micros, &mut shared_state.dirty);
+        if !self.runner.is_active() {
+            self.picker.update(&input_state.encoder);
+        }
+
+        if self.buttons[1].press {
+            match self.runner.state() {
+                RunnerState::Running => self.runner.pause(com),
+                RunnerState::Paused => self.runner.resume(),
+                _ => self.runner.start(&SEQUENCES[*self.picker.selected()]),
+            }
+        }
+        if self.buttons[2].press {
+            self.runner.abort(com, "by user");
+        }
+
+        self.runner.update(dt_micros, com);
+        self.log_state_change(previous_state, shared_state);
+        if received || self.runner.is_active() || self.runner.state() != previous_state {
+            shared_state.invalidate_all();
+        }
+        invalidate_limiter_status(shared_state);
+
+        self.buttons[1].text = match self.runner.state() {
+            RunnerState::Running => "Pause",
+            RunnerState::Paused => "Resume",
+            _ => "Run",
+        };
+
+        if self.buttons[0].press {
+            self.runner.abort(com, "left view");
+            com.outbox.push_back(ControllerMessage::Stop);
+            Some(View::ViewPicker)
+        } else {
+            None
+        }
+    }
+
+    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+        render_app_frame(framebuffer, "Sequences", &mut self.buttons);
+        render_limiter_status(framebuffer, &shared_state.limiter, &shared_state.warning_blink);
+        self.picker.render(framebuffer);
+
+        let mut panel = framebuffer.dt_ref().mask(RectMask {
+            upper_left: (PANEL_X, 11),
+            lower_right: (126, 52),
+        });
+
+        let state_string = match self.runner.state() {
+            RunnerState::Idle => "Idle",
+            RunnerState::Running => "Running",
+            RunnerState::Paused => "Paused",
+            RunnerState::Finished => "Done",
+            RunnerState::Aborted(_) => "Aborted",
+        };
+        BASIC_5PX.draw_text_line(&mut panel, (PANEL_X, 18), state_string, true);
+
+        let (step, total) = self.runner.progress();
+        if total == 0 {
+            return;
+        }
+        BASIC_5PX.draw_text_line(&mut panel, (PANEL_X, 26), &format!("Step {}/{}", (step + 1).min(total), total), true);
+        match (self.runner.state(), self.runner.current_step()) {
+            (RunnerState::Aborted(reason), _) => BASIC_5PX.draw_text_line(&mut panel, (PANEL_X, 34), reason, true),
+            (_, Some(current_step)) => BASIC_5PX.draw_text_line(&mut panel, (PANEL_X, 34), &format!("{}", current_step), true),
+            _ => {},
+        }
+
+        self.progress_bar.set_progress(step as f32 + self.runner.step_progress(), total as f32);
+        self.progress_bar.render(&mut panel, (PANEL_X, 37));
+
+        if let Some(record) = self.runner.records().last() {
+            BASIC_5PX.draw_text_line(&mut panel, (PANEL_X, 49), &format!("Imax {:.1} A", record.max_current), true);
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use alloc::format;
 use qcw_com::{ControllerMessage, RemoteMessage, Statistic, StatisticValue};
 
-use crate::{application::{AppSharedState, ComState, InputState}, gfx::fonts::BASIC_5PX, mn12864k::Framebuffer};
+use crate::{application::{AppSharedState, ComState, InputState}, gfx::fonts::BASIC_5PX, mn12864k::Framebuffer, ui::{draw_readout, draw_text_in, Align, Rect, ValueTable, LINE_HEIGHT}};
 
-use super::{render_app_frame, update_app_frame, AppView, UiFrameButton, View};
+use super::{invalidate_content, render_app_frame, update_app_frame, AppView, UiFrameButton, View};
 
 pub struct StatMonitorView {
     buttons: [UiFrameButton; 2],
@@ -11,6 +11,13 @@ pub struct StatMonitorView {
     t_last_request: u64,
     max_current_value: f32,
     feedback_frequency_value: f32,
+    table: ValueTable<1>,
+}
+
+impl Default for StatMonitorView {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StatMonitorView {
@@ -21,6 +28,7 @@ impl StatMonitorView {
             t_last_request: 0,
             max_current_value: 0.0,
             feedback_frequency_value: 0.0,
+            table: ValueTable::new(["Feedback Frequency"], 120),
         }
     }
 }
@@ -42,14 +50,12 @@ impl AppView for StatMonitorView {
             self.t_last_request = self.t_elapsed;
         }
         while let Some(message) = com.inbox.pop_front() {
-            match message {
-                RemoteMessage::GetStatResult(stat) => {
-                    match stat {
-                        StatisticValue::MaxPrimaryCurrentA(current) => self.max_current_value = current,
-                        StatisticValue::FeedbackFrequencykHz(frequency) => self.feedback_frequency_value = frequency,
-                    }
-                },
-                _ => {}
+            if let RemoteMessage::GetStatResult(stat) = message {
+                match stat {
+                    StatisticValue::MaxPrimaryCurrentA(current) => self.max_current_value = current,
+                    StatisticValue::FeedbackFrequencykHz(frequency) => self.feedback_frequency_value = frequency,
+                }
+                invalidate_content(shared_state);
             }
         }
         update_app_frame(&input_state, &mut self.buttons);
@@ -63,12 +69,13 @@ impl AppView for StatMonitorView {
         }
     }
 
-    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+    fn render(&mut self, framebuffer: &mut Framebuffer, _shared_state: &mut AppSharedState) {
         render_app_frame(framebuffer, "Home", &mut self.buttons);
-        BASIC_5PX.draw_text_line(framebuffer, (4, 20), &format!("Max Current:"), true);
-        BASIC_5PX.draw_text_line(framebuffer, (80, 20), &format!("{:.2} A", self.max_current_value), true);
-        BASIC_5PX.draw_text_line(framebuffer, (4, 28), &format!("Feedback Frequency:"), true);
-        BASIC_5PX.draw_text_line(framebuffer, (80, 28), &format!("{:.2} kHz", self.feedback_frequency_value), true);
+        // the current gets the big digits, it's the number to watch while the coil is running
+        draw_text_in(framebuffer, &BASIC_5PX, Rect::new((4, 13), (120, LINE_HEIGHT)), "Max Current", Align::Start, true);
+        draw_readout(framebuffer, Rect::new((4, 21), (120, 18)), &format!("{:.2}", self.max_current_value), "A", Align::Center, true);
+        self.table.set(0, format_args!("{:.2} kHz", self.feedback_frequency_value));
+        self.table.render(framebuffer, (4, 42));
     }
 }
 
@@ -0,0 +1,352 @@
+use alloc::{format, string::String};
+use qcw_com::ControllerMessage;
+
+use crate::application::{AppSharedState, ComState, InputState};
+use crate::event_log::{DisplayParameterValue, Severity};
+use crate::gfx::draw_target::DrawTarget;
+use crate::gfx::fonts::BASIC_5PX;
+use crate::gfx::primitives::*;
+use crate::mn12864k::Framebuffer;
+use crate::sweep::{SweepConfig, SweepMetric, SweepParameter, SweepPoint, SweepRunner, SweepState};
+use crate::ui::{Align, DigitEditResult, DigitEditor, ListPicker, Rect};
+
+use super::{invalidate_limiter_status, render_app_frame, render_limiter_status, update_app_frame, AppView, UiFrameButton, View};
+
+#[derive(Copy, Clone, PartialEq)]
+enum SweepField {
+    Parameter,
+    Start,
+    End,
+    Step,
+    Dwell,
+    Metric,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum SweepMode {
+    Config,
+    Results,
+}
+
+const PLOT_UPPER_LEFT: (isize, isize) = (3, 13);
+const PLOT_LOWER_RIGHT: (isize, isize) = (124, 43);
+
+pub struct SweepView {
+    buttons: [UiFrameButton; 3],
+    field_list: ListPicker<SweepField, 6>,
+    editing: bool,
+    mode: SweepMode,
+    config: SweepConfig,
+    metric: SweepMetric,
+    runner: SweepRunner,
+    // typing in an exact value for the selected field
+    digit_editor: Option<DigitEditor>,
+}
+
+impl Default for SweepView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SweepView {
+    pub fn new() -> Self {
+        let config = SweepConfig::new(SweepParameter::PhaseDelay);
+        Self {
+            buttons: [UiFrameButton::new("Back"), UiFrameButton::new("Run"), UiFrameButton::new("Type")],
+            field_list: ListPicker::new([
+                (SweepField::Parameter, "Param"),
+                (SweepField::Start, "Start"),
+                (SweepField::End, "End"),
+                (SweepField::Step, "Step"),
+                (SweepField::Dwell, "Dwell"),
+                (SweepField::Metric, "Metric"),
+            ], (4, 13), 40, 38),
+            editing: false,
+            mode: SweepMode::Config,
+            config,
+            metric: SweepMetric::HighestCurrent,
+            runner: SweepRunner::new(config),
+            digit_editor: None,
+        }
+    }
+
+    fn field_digit_editor(&self) -> Option<DigitEditor> {
+        let parameter = self.config.parameter;
+        let (min, max) = parameter.limits();
+        let unit = parameter.unit();
+        match self.field_list.selected() {
+            SweepField::Start => Some(DigitEditor::new(self.config.start, min, max, 0, unit)),
+            SweepField::End => Some(DigitEditor::new(self.config.end, min, max, 0, unit)),
+            SweepField::Step => Some(DigitEditor::new(self.config.step, parameter.increment(), max - min, 0, unit)),
+            SweepField::Dwell => Some(DigitEditor::new(self.config.dwell_ms as i32, 50, 5000, 0, "ms")),
+            SweepField::Parameter | SweepField::Metric => None,
+        }
+    }
+
+    fn set_field(&mut self, value: i32) {
+        match self.field_list.selected() {
+            SweepField::Start => self.config.start = value,
+            SweepField::End => self.config.end = value,
+            SweepField::Step => self.config.step = value,
+            SweepField::Dwell => self.config.dwell_ms = value as u32,
+            SweepField::Parameter | SweepField::Metric => {},
+        }
+        self.config.step = self.config.step();
+    }
+
+    fn adjust_field(&mut self, delta: i32) {
+        let parameter = self.config.parameter;
+        let (min, max) = parameter.limits();
+        let increment = parameter.increment();
+        match self.field_list.selected() {
+            SweepField::Parameter => {
+                let mut parameter = parameter;
+                for _ in 0..delta.abs() {
+                    parameter = if delta > 0 { parameter.next() } else { parameter.previous() };
+                }
+                let (start, end, step) = parameter.default_range();
+                self.config = SweepConfig {
+                    parameter,
+                    start,
+                    end,
+                    step,
+                    ..self.config
+                };
+            },
+            SweepField::Start => self.config.start = (self.config.start + delta * increment).clamp(min, max),
+            SweepField::End => self.config.end = (self.config.end + delta * increment).clamp(min, max),
+            SweepField::Step => self.config.step = (self.config.step + delta * increment).clamp(increment, max - min),
+            SweepField::Dwell => self.config.dwell_ms = (self.config.dwell_ms as i32 + delta * 50).clamp(50, 5000) as u32,
+            SweepField::Metric => {
+                for _ in 0..delta.abs() {
+                    self.metric = if delta > 0 { self.metric.next() } else { self.metric.previous() };
+                }
+            },
+        }
+        // show the step the sweep will really use if the range needs it widened
+        self.config.step = self.config.step();
+    }
+
+    fn field_string(&self) -> String {
+        let unit = self.config.parameter.unit();
+        match self.field_list.selected() {
+            SweepField::Parameter => String::from(self.config.parameter.name()),
+            SweepField::Start => format!("{} {}", self.config.start, unit),
+            SweepField::End => format!("{} {}", self.config.end, unit),
+            SweepField::Step => format!("{} {}", self.config.step, unit),
+            SweepField::Dwell => format!("{} ms", self.config.dwell_ms),
+            SweepField::Metric => String::from(self.metric.name()),
+        }
+    }
+
+    fn log_state_change(&self, previous: SweepState, shared_state: &mut AppSharedState) {
+        let state = self.runner.state();
+        if state == previous {
+            return;
+        }
+        let name = self.runner.config().parameter.name();
+        match state {
+            SweepState::Running => shared_state.log(Severity::Info, format_args!("{} sweep started", name)),
+            SweepState::Finished => shared_state.log(Severity::Info, format_args!("{} sweep done", name)),
+            SweepState::Aborted(reason) => shared_state.log(Severity::Warning, format_args!("Sweep aborted: {}", reason)),
+            SweepState::Idle => {},
+        }
+    }
+
+    fn render_config(&mut self, framebuffer: &mut Framebuffer) {
+        self.field_list.render(framebuffer);
+        let field_string = self.field_string();
+        BASIC_5PX.draw_text_line(framebuffer, (50, 18), &field_string, true);
+        if self.editing {
+            let text_width = BASIC_5PX.get_text_width(&field_string);
+            draw_hline(framebuffer, 50, 50 + text_width, 20, true);
+        }
+        let config = &self.config;
+        BASIC_5PX.draw_text_line(framebuffer, (50, 30), &format!("{}..{} {}", config.start, config.end, config.parameter.unit()), true);
+        let point_count = config.point_count();
+        let seconds = (point_count as u32 * config.dwell_ms) / 1000;
+        BASIC_5PX.draw_text_line(framebuffer, (50, 38), &format!("{} points, {}s", point_count, seconds), true);
+        BASIC_5PX.draw_text_line(framebuffer, (50, 46), &format!("Goal: {}", self.metric.name()), true);
+    }
+
+    fn render_results(&mut self, framebuffer: &mut Framebuffer) {
+        let config = *self.runner.config();
+        let optimum = self.runner.optimum(self.metric);
+        plot_points(framebuffer, &config, self.metric, self.runner.points(), optimum.map(|point| point.value));
+
+        let status_string = match (self.runner.state(), optimum) {
+            (SweepState::Running, _) => {
+                let (measured, total) = self.runner.progress();
+                format!("Point {}/{}", measured + 1, total)
+            },
+            (SweepState::Aborted(reason), _) => format!("Aborted: {}", reason),
+            (_, Some(point)) => format!("Best {}{}: {:.2}{}", point.value, config.parameter.unit(), self.metric.measurement(&point), self.metric.unit()),
+            (_, None) => String::new(),
+        };
+        BASIC_5PX.draw_text_line(framebuffer, (4, 50), &status_string, true);
+    }
+}
+
+// plots the chosen measurement against the swept value, marking the optimum with a vertical line
+fn plot_points<Target: DrawTarget>(target: &mut Target, config: &SweepConfig, metric: SweepMetric, points: &[SweepPoint], optimum: Option<i32>) {
+    draw_rect(target, PLOT_UPPER_LEFT, PLOT_LOWER_RIGHT, true);
+    if points.is_empty() {
+        return;
+    }
+    let (x_min, x_max) = (config.start.min(config.end), config.start.max(config.end));
+    let (y_min, y_max) = points.iter()
+        .map(|point| metric.measurement(point))
+        .fold((f32::MAX, f32::MIN), |(min, max), value| (min.min(value), max.max(value)));
+    let plot_width = (PLOT_LOWER_RIGHT.0 - PLOT_UPPER_LEFT.0 - 2) as f32;
+    let plot_height = (PLOT_LOWER_RIGHT.1 - PLOT_UPPER_LEFT.1 - 2) as f32;
+    let to_x = |value: i32| {
+        let span = (x_max - x_min).max(1) as f32;
+        PLOT_UPPER_LEFT.0 + 1 + (((value - x_min) as f32 / span) * plot_width) as isize
+    };
+    let to_y = |measurement: f32| {
+        let span = if y_max > y_min { y_max - y_min } else { 1.0 };
+        PLOT_LOWER_RIGHT.1 - 1 - (((measurement - y_min) / span) * plot_height) as isize
+    };
+
+    if let Some(optimum) = optimum {
+        let x = to_x(optimum);
+        for y in (PLOT_UPPER_LEFT.1 + 1..PLOT_LOWER_RIGHT.1).step_by(2) {
+            target.set_pixel((x, y), true);
+        }
+    }
+
+    let mut previous = None;
+    for point in points {
+        let position = (to_x(point.value), to_y(metric.measurement(point)));
+        match previous {
+            Some(previous) => draw_line(target, previous, position, true),
+            None => target.set_pixel(position, true),
+        }
+        previous = Some(position);
+    }
+}
+
+impl AppView for SweepView {
+    fn start(&mut self) {
+        self.buttons.iter_mut().for_each(|button| button.reset());
+        self.field_list.reset();
+        self.editing = false;
+        self.mode = SweepMode::Config;
+        self.runner = SweepRunner::new(self.config);
+        self.digit_editor = None;
+    }
+
+    fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<View> {
+        update_app_frame(&input_state, &mut self.buttons);
+        self.field_list.animate(dt_micros, &mut shared_state.dirty);
+        let previous_state = self.runner.state();
+
+        let received = !com.inbox.is_empty();
+        while let Some(message) = com.inbox.pop_front() {
+            self.runner.handle_message(&message);
+        }
+        if let Some(reason) = shared_state.limiter_halt() {
+            self.runner.abort(com, reason);
+        }
+
+        let typing = self.digit_editor.is_some();
+        match self.mode {
+            // the digit editor has the encoder and the frame buttons to itself while it's open
+            SweepMode::Config if self.digit_editor.is_some() => {
+                let result = self.digit_editor.as_mut().map(|editor| editor.update(&input_state));
+                match result {
+                    Some(DigitEditResult::Done(value)) => {
+                        self.set_field(value);
+                        self.digit_editor = None;
+                    },
+                    Some(DigitEditResult::Cancelled) => self.digit_editor = None,
+                    _ => {},
+                }
+            },
+            SweepMode::Config => {
+                if self.buttons[2].press && !self.editing {
+                    self.digit_editor = self.field_digit_editor();
+                }
+                if !self.editing {
+                    self.editing = self.field_list.update(&input_state.encoder).is_some();
+                } else if input_state.encoder.button.pressed {
+                    self.editing = false;
+                }
+                if self.editing && input_state.encoder.delta != 0 {
+                    self.adjust_field(input_state.encoder.delta);
+                }
+                if self.buttons[1].press {
+                    self.editing = false;
+                    self.runner.start(self.config, com);
+                    self.mode = SweepMode::Results;
+                }
+            },
+            SweepMode::Results => {
+                if self.buttons[1].press {
+                    if self.runner.state() == SweepState::Running {
+                        self.runner.abort(com, "by user");
+                    } else {
+                        self.mode = SweepMode::Config;
+                    }
+                }
+                if self.buttons[2].press && self.runner.state() == SweepState::Finished {
+                    if let Some(point) = self.runner.optimum(self.metric) {
+                        let parameter = self.runner.config().parameter;
+                        let value = parameter.parameter_value(point.value);
+                        shared_state.log(Severity::Info, format_args!("Sweep applied {}", DisplayParameterValue(&value)));
+                        com.outbox.push_back(ControllerMessage::SetParam(value));
+                    }
+                }
+            },
+        }
+
+        self.runner.update(dt_micros, com);
+        self.log_state_change(previous_state, shared_state);
+        if received || self.runner.state() == SweepState::Running || self.runner.state() != previous_state {
+            shared_state.invalidate_all();
+        }
+        invalidate_limiter_status(shared_state);
+
+        if let Some(editor) = &self.digit_editor {
+            for (button, label) in self.buttons.iter_mut().zip(editor.button_labels()) {
+                button.text = label;
+            }
+            return None;
+        }
+
+        let running = self.runner.state() == SweepState::Running;
+        self.buttons[1].text = match (self.mode, running) {
+            (SweepMode::Config, _) => "Run",
+            (SweepMode::Results, true) => "Stop",
+            (SweepMode::Results, false) => "Edit",
+        };
+        self.buttons[2].text = match (self.mode, self.runner.state()) {
+            (SweepMode::Config, _) => "Type",
+            (SweepMode::Results, SweepState::Finished) => "Apply",
+            (SweepMode::Results, _) => "",
+        };
+
+        if !typing && self.buttons[0].press {
+            self.runner.abort(com, "left view");
+            com.outbox.push_back(ControllerMessage::Stop);
+            Some(View::ViewPicker)
+        } else {
+            None
+        }
+    }
+
+    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+        render_app_frame(framebuffer, "Parameter Sweep", &mut self.buttons);
+        render_limiter_status(framebuffer, &shared_state.limiter, &shared_state.warning_blink);
+        if let Some(editor) = &self.digit_editor {
+            let content = Rect::from_corners((6, 14), (124, 51));
+            editor.render(framebuffer, content.align(editor.size(), Align::Center, Align::Center));
+            return;
+        }
+        match self.mode {
+            SweepMode::Config => self.render_config(framebuffer),
+            SweepMode::Results => self.render_results(framebuffer),
+        }
+    }
+}
@@ -1,11 +1,15 @@
-use alloc::format;
-
 use crate::{application::{AppSharedState, ComState, InputState}, gfx::fonts::BASIC_5PX, ui::ListPicker, mn12864k::Framebuffer};
 
 use super::{render_app_frame, AppView, View};
 
 pub struct ViewPickerView {
-    picker: ListPicker<View, 5>
+    picker: ListPicker<View, 12>
+}
+
+impl Default for ViewPickerView {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ViewPickerView {
@@ -17,6 +21,13 @@ impl ViewPickerView {
                 (View::PhaseTuning, "Feedback Phase Tuning"),
                 (View::StatMonitor, "Stat Monitor"),
                 (View::OpenLoopTest, "Open Loop Test"),
+                (View::Sequence, "Sequences"),
+                (View::Sweep, "Parameter Sweep"),
+                (View::EnvelopeEditor, "Envelope Editor"),
+                (View::Music, "Music"),
+                (View::Limits, "Limits"),
+                (View::Maintenance, "Maintenance"),
+                (View::EventLog, "Event Log"),
             ], (2, 20), 122, 40),
         }
     }
@@ -29,10 +40,11 @@ impl AppView for ViewPickerView {
 
     fn update(&mut self, dt_micros: u64, input_state: InputState, com: &mut ComState<'_>, shared_state: &mut AppSharedState) -> Option<super::View> {
         com.inbox.clear();
+        self.picker.animate(dt_micros, &mut shared_state.dirty);
         self.picker.update(&input_state.encoder)
     }
 
-    fn render(&mut self, framebuffer: &mut Framebuffer, shared_state: &mut AppSharedState) {
+    fn render(&mut self, framebuffer: &mut Framebuffer, _shared_state: &mut AppSharedState) {
         render_app_frame(framebuffer, "Home", &mut []);
         BASIC_5PX.draw_text_line(framebuffer, (5, 17), "Open Tool:", true);
         self.picker.render(framebuffer);
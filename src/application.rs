@@ -1,11 +1,31 @@
+use core::fmt;
+
 use alloc::collections::vec_deque::VecDeque;
 use alloc::vec::Vec;
+use libm::roundf;
 
+use crate::animation::{Blink, Easing, Tween};
 use crate::app_views::*;
-use crate::mn12864k::Framebuffer;
-use crate::gfx;
+use crate::mn12864k::{Framebuffer, HEIGHT, WIDTH};
+use crate::gfx::dirty::DirtyRegion;
+use crate::gfx::draw_target::DrawTarget;
+use crate::event_log::{log_sent_message, DisplayParameterValue, EventLog, Severity};
+use crate::envelope::{self, default_envelopes, read_envelopes, write_envelopes, Envelope, ENVELOPE_SLOTS};
+use crate::limiter::{Limiter, Verdict};
+use crate::run_time::{self, RunTimeAccountant};
 use qcw_com::{ControllerMessage, RemoteMessage};
 
+pub const SETTINGS_SIZE: usize = run_time::PERSISTED_SIZE + envelope::PERSISTED_SIZE;
+
+// how long a request may go unanswered before the link is considered down
+const LINK_TIMEOUT_US: u64 = 500_000;
+
+// how long the old view takes to slide out of the way of a new one
+const TRANSITION_TIME_US: u64 = 250_000;
+
+// one on/off cycle of anything flashing to draw attention to a warning
+const WARNING_BLINK_US: u64 = 600_000;
+
 pub struct ButtonState {
     pub down: bool,
     pub pressed: bool,
@@ -23,6 +43,19 @@ pub struct InputState {
     pub buttons: [ButtonState; 3],
 }
 
+impl ButtonState {
+    pub fn changed(&self) -> bool {
+        self.pressed || self.released
+    }
+}
+
+impl InputState {
+    // anything turned, pressed or released this update
+    pub fn changed(&self) -> bool {
+        self.encoder.delta != 0 || self.encoder.button.changed() || self.buttons.iter().any(ButtonState::changed)
+    }
+}
+
 pub struct Application {
     view_picker_view: ViewPickerView,
     phase_tuning_view: PhaseTuningView,
@@ -30,9 +63,49 @@ pub struct Application {
     debug_led_view: DebugLedView,
     stat_monitor_view: StatMonitorView,
     open_loop_test_view: OpenLoopTestView,
+    event_log_view: EventLogView,
+    sequence_view: SequenceView,
+    sweep_view: SweepView,
+    envelope_editor_view: EnvelopeEditorView,
+    music_view: MusicView,
+    limits_view: LimitsView,
+    maintenance_view: MaintenanceView,
     incoming_view: Option<View>,
     current_view: Option<View>,
+    transition: Option<Transition>,
     shared_state: AppSharedState,
+    link_up: bool,
+    t_oldest_unanswered: Option<u64>,
+}
+
+// the view being left slides off one side of the screen as the new one slides in from the other
+struct Transition {
+    from: View,
+    // 1 when the new view comes in from the right, -1 when it comes in from the left
+    direction: isize,
+    // fraction of the screen the views have moved so far
+    slide: Tween,
+    // the old view's last frame, drawn the first time the transition is drawn, and the new
+    // view's latest. held here rather than on the heap, which is too small for two frames
+    outgoing: Framebuffer,
+    outgoing_drawn: bool,
+    incoming: Framebuffer,
+}
+
+impl Transition {
+    fn new(from: View, to: View) -> Self {
+        let mut slide = Tween::new(0.0, TRANSITION_TIME_US, Easing::EaseOut);
+        slide.start(0.0, 1.0);
+        Self {
+            from,
+            // going back to the picker retraces the way in
+            direction: if matches!(to, View::ViewPicker) { -1 } else { 1 },
+            slide,
+            outgoing: Framebuffer::new(),
+            outgoing_drawn: false,
+            incoming: Framebuffer::new(),
+        }
+    }
 }
 
 pub struct ComState<'a> {
@@ -41,6 +114,75 @@ pub struct ComState<'a> {
 }
 
 pub struct AppSharedState {
+    pub time_us: u64,
+    pub event_log: EventLog,
+    pub envelopes: [Envelope; ENVELOPE_SLOTS],
+    // edited since they were last written to flash
+    pub envelopes_unsaved: bool,
+    pub limiter: Limiter,
+    pub run_time: RunTimeAccountant,
+    // what the limiter did with the view's last update, for the view to see on its next one: the
+    // reason it refused a message, and whether it stopped the coil itself
+    pub refused: Option<&'static str>,
+    pub stopped: bool,
+    // shared so every flashing warning on screen flashes together
+    pub warning_blink: Blink,
+    // what the current view needs redrawn on the next frame
+    pub dirty: DirtyRegion,
+}
+
+impl Default for AppSharedState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppSharedState {
+    pub fn new() -> Self {
+        Self {
+            time_us: 0,
+            event_log: EventLog::new(),
+            envelopes: default_envelopes(),
+            envelopes_unsaved: false,
+            limiter: Limiter::new(),
+            run_time: RunTimeAccountant::new(),
+            refused: None,
+            stopped: false,
+            warning_blink: Blink::new(WARNING_BLINK_US),
+            dirty: DirtyRegion::new(WIDTH, HEIGHT),
+        }
+    }
+
+    pub fn log(&mut self, severity: Severity, message: fmt::Arguments<'_>) {
+        self.event_log.push(self.time_us, severity, message);
+    }
+
+    // from a settings record made by Application::take_settings_save
+    pub fn restore(&mut self, bytes: &[u8]) {
+        self.run_time.restore(bytes);
+        if let Some(envelopes) = read_envelopes(&bytes[run_time::PERSISTED_SIZE..]) {
+            self.envelopes = envelopes;
+        }
+    }
+
+    // why a run has to end, if the limiter refused part of it or stopped the coil
+    pub fn limiter_halt(&self) -> Option<&'static str> {
+        match (self.refused, self.stopped) {
+            (Some(reason), _) => Some(reason),
+            (None, true) => Some("thermal budget used up"),
+            (None, false) => None,
+        }
+    }
+
+    // views call these for anything that changes on screen without any input, input already
+    // redraws everything
+    pub fn invalidate(&mut self, upper_left: (isize, isize), lower_right: (isize, isize)) {
+        self.dirty.mark(upper_left, lower_right);
+    }
+
+    pub fn invalidate_all(&mut self) {
+        self.dirty.mark_all();
+    }
 }
 
 impl Application {
@@ -53,12 +195,89 @@ impl Application {
             debug_led_view: DebugLedView::new(),
             stat_monitor_view: StatMonitorView::new(),
             open_loop_test_view: OpenLoopTestView::new(),
+            event_log_view: EventLogView::new(),
+            sequence_view: SequenceView::new(),
+            sweep_view: SweepView::new(),
+            envelope_editor_view: EnvelopeEditorView::new(),
+            music_view: MusicView::new(),
+            limits_view: LimitsView::new(),
+            maintenance_view: MaintenanceView::new(),
             incoming_view: Some(View::ViewPicker),
             current_view: None,
+            transition: None,
+            link_up: false,
+            t_oldest_unanswered: None,
+        }
+    }
+
+    pub fn log(&mut self, severity: Severity, message: fmt::Arguments<'_>) {
+        self.shared_state.log(severity, message);
+    }
+
+    fn update_link_state(&mut self, com: &ComState<'_>) {
+        let now = self.shared_state.time_us;
+        if !com.inbox.is_empty() {
+            self.t_oldest_unanswered = None;
+            if !self.link_up {
+                self.link_up = true;
+                self.shared_state.log(Severity::Info, format_args!("Link up"));
+            }
+        } else if let Some(t_sent) = self.t_oldest_unanswered {
+            if self.link_up && now - t_sent >= LINK_TIMEOUT_US {
+                self.link_up = false;
+                self.shared_state.log(Severity::Warning, format_args!("Link lost"));
+            }
+        }
+    }
+
+    // runs everything the view just queued past the limiter, and stops the coil once the thermal budget runs out
+    fn enforce_limits(&mut self, dt_micros: u64, com: &mut ComState<'_>, queued_before: usize) {
+        let queued: Vec<ControllerMessage> = com.outbox.drain(queued_before..).collect();
+        for message in queued {
+            match self.shared_state.limiter.check(message) {
+                Verdict::Allow(message) => com.outbox.push_back(message),
+                Verdict::Clamped(message) => {
+                    if let ControllerMessage::SetParam(value) = &message {
+                        self.shared_state.log(Severity::Warning, format_args!("Limited {}", DisplayParameterValue(value)));
+                    }
+                    com.outbox.push_back(message);
+                },
+                Verdict::Corrected { correction, message } => {
+                    if let ControllerMessage::SetParam(value) = &correction {
+                        self.shared_state.log(Severity::Warning, format_args!("Limited {}", DisplayParameterValue(value)));
+                    }
+                    com.outbox.push_back(correction);
+                    com.outbox.push_back(message);
+                },
+                Verdict::Refused(reason) => {
+                    self.shared_state.log(Severity::Warning, format_args!("Refused: {}", reason));
+                    self.shared_state.refused = Some(reason);
+                },
+            }
+        }
+        if self.shared_state.limiter.update(dt_micros) {
+            self.shared_state.log(Severity::Error, format_args!("Thermal budget used up"));
+            if let Verdict::Allow(message) = self.shared_state.limiter.check(ControllerMessage::Stop) {
+                com.outbox.push_back(message);
+            }
+            self.shared_state.stopped = true;
         }
     }
 
     pub fn update(&mut self, dt_micros: u64, input_state: InputState, mut com: ComState<'_>) {
+        self.shared_state.time_us += dt_micros;
+        self.shared_state.warning_blink.update(dt_micros);
+        self.update_link_state(&com);
+        if input_state.changed() {
+            self.shared_state.invalidate_all();
+        }
+        if let Some(transition) = &mut self.transition {
+            self.shared_state.invalidate_all();
+            transition.slide.update(dt_micros);
+            if transition.slide.finished() {
+                self.transition = None;
+            }
+        }
         if let Some(incoming_view) = self.incoming_view.take() {
             let view: &mut dyn AppView = match incoming_view {
                 View::ViewPicker => &mut self.view_picker_view,
@@ -67,8 +286,23 @@ impl Application {
                 View::DebugLed => &mut self.debug_led_view,
                 View::StatMonitor => &mut self.stat_monitor_view,
                 View::OpenLoopTest => &mut self.open_loop_test_view,
+                View::EventLog => &mut self.event_log_view,
+                View::Sequence => &mut self.sequence_view,
+                View::Sweep => &mut self.sweep_view,
+                View::EnvelopeEditor => &mut self.envelope_editor_view,
+                View::Music => &mut self.music_view,
+                View::Limits => &mut self.limits_view,
+                View::Maintenance => &mut self.maintenance_view,
             };
+            view.start();
+            self.shared_state.refused = None;
+            self.shared_state.stopped = false;
+            self.shared_state.log(Severity::Debug, format_args!("View {:?}", incoming_view));
+            if let Some(current_view) = self.current_view {
+                self.transition = Some(Transition::new(current_view, incoming_view));
+            }
             self.current_view = Some(incoming_view);
+            self.shared_state.invalidate_all();
         }
         if let Some(current_view) = &self.current_view {
             let view: &mut dyn AppView = match current_view {
@@ -78,22 +312,110 @@ impl Application {
                 View::DebugLed => &mut self.debug_led_view,
                 View::StatMonitor => &mut self.stat_monitor_view,
                 View::OpenLoopTest => &mut self.open_loop_test_view,
+                View::EventLog => &mut self.event_log_view,
+                View::Sequence => &mut self.sequence_view,
+                View::Sweep => &mut self.sweep_view,
+                View::EnvelopeEditor => &mut self.envelope_editor_view,
+                View::Music => &mut self.music_view,
+                View::Limits => &mut self.limits_view,
+                View::Maintenance => &mut self.maintenance_view,
             };
+            for message in com.inbox.iter() {
+                self.shared_state.limiter.observe_received(message);
+            }
+            let was_running = self.shared_state.limiter.running();
+            let queued_before = com.outbox.len();
             self.incoming_view = view.update(dt_micros, input_state, &mut com, &mut self.shared_state);
+            self.shared_state.refused = None;
+            self.shared_state.stopped = false;
+            self.enforce_limits(dt_micros, &mut com, queued_before);
+            // views show whether the coil is firing in more places than the limiter status
+            if self.shared_state.limiter.running() != was_running {
+                self.shared_state.invalidate_all();
+            }
+            for message in com.outbox.iter().skip(queued_before) {
+                log_sent_message(&mut self.shared_state.event_log, self.shared_state.time_us, message);
+                self.shared_state.run_time.observe_sent(message, self.shared_state.time_us);
+                let expects_reply = matches!(message, ControllerMessage::GetParam(_) | ControllerMessage::GetStat(_) | ControllerMessage::Ping(_));
+                if expects_reply && self.t_oldest_unanswered.is_none() {
+                    self.t_oldest_unanswered = Some(self.shared_state.time_us);
+                }
+            }
+        }
+        let shared_state = &mut self.shared_state;
+        shared_state.run_time.update(dt_micros, shared_state.time_us, &shared_state.limiter.parameters());
+    }
+
+    // run time totals and envelopes, when they are due to be written to flash. both go in every
+    // record, since only the newest record is read back
+    pub fn take_settings_save(&mut self) -> Option<[u8; SETTINGS_SIZE]> {
+        let shared_state = &mut self.shared_state;
+        if shared_state.envelopes_unsaved {
+            shared_state.run_time.mark_unsaved();
+            shared_state.envelopes_unsaved = false;
         }
+        let run_time_totals = shared_state.run_time.take_save(shared_state.time_us)?;
+        let mut bytes = [0u8; SETTINGS_SIZE];
+        bytes[..run_time::PERSISTED_SIZE].copy_from_slice(&run_time_totals);
+        write_envelopes(&shared_state.envelopes, &mut bytes[run_time::PERSISTED_SIZE..]);
+        Some(bytes)
     }
 
+    pub fn needs_render(&self) -> bool {
+        !self.shared_state.dirty.is_empty()
+    }
+
+    // for when the frame being drawn doesn't start as a copy of the last one
+    pub fn invalidate_all(&mut self) {
+        self.shared_state.invalidate_all();
+    }
+
+    // redraws what has changed since the last frame, which framebuffer has to start out holding
     pub fn render(&mut self, framebuffer: &mut Framebuffer) {
-        if let Some(current_view) = &self.current_view {
-            let view: &mut dyn AppView = match current_view {
-                View::ViewPicker => &mut self.view_picker_view,
-                View::PingTest => &mut self.ping_test_view,
-                View::PhaseTuning => &mut self.phase_tuning_view,
-                View::DebugLed => &mut self.debug_led_view,
-                View::StatMonitor => &mut self.stat_monitor_view,
-                View::OpenLoopTest => &mut self.open_loop_test_view,
-            };
-            view.render(framebuffer, &mut self.shared_state);
+        let dirty = self.shared_state.dirty.take();
+        let Some(current_view) = self.current_view else {
+            return;
+        };
+        let Some(mut transition) = self.transition.take() else {
+            // the view draws all of itself each time, the clip keeps it to one rect
+            for &(upper_left, lower_right) in dirty.rects() {
+                framebuffer.set_clip(upper_left, lower_right);
+                framebuffer.fill_rect(upper_left, lower_right, false);
+                self.render_view(current_view, framebuffer);
+            }
+            framebuffer.reset_clip();
+            return;
+        };
+        if !transition.outgoing_drawn {
+            self.render_view(transition.from, &mut transition.outgoing);
+            transition.outgoing_drawn = true;
         }
+        transition.incoming.clear(false);
+        self.render_view(current_view, &mut transition.incoming);
+
+        let shift = roundf(transition.slide.value() * WIDTH as f32) as isize;
+        framebuffer.clear(false);
+        framebuffer.blit_framebuffer(&transition.outgoing, -transition.direction * shift);
+        framebuffer.blit_framebuffer(&transition.incoming, transition.direction * (WIDTH as isize - shift));
+        self.transition = Some(transition);
     }
-}
+
+    fn render_view(&mut self, view: View, framebuffer: &mut Framebuffer) {
+        let view: &mut dyn AppView = match view {
+            View::ViewPicker => &mut self.view_picker_view,
+            View::PingTest => &mut self.ping_test_view,
+            View::PhaseTuning => &mut self.phase_tuning_view,
+            View::DebugLed => &mut self.debug_led_view,
+            View::StatMonitor => &mut self.stat_monitor_view,
+            View::OpenLoopTest => &mut self.open_loop_test_view,
+            View::EventLog => &mut self.event_log_view,
+            View::Sequence => &mut self.sequence_view,
+            View::Sweep => &mut self.sweep_view,
+            View::EnvelopeEditor => &mut self.envelope_editor_view,
+            View::Music => &mut self.music_view,
+            View::Limits => &mut self.limits_view,
+            View::Maintenance => &mut self.maintenance_view,
+        };
+        view.render(framebuffer, &mut self.shared_state);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,232 @@
+use qcw_com::{EnvelopePoint, PowerEnvelope, MAX_ENVELOPE_POINTS};
+
+pub const MAX_BREAKPOINTS: usize = MAX_ENVELOPE_POINTS;
+// in bytes of utf-8
+pub const NAME_CAPACITY: usize = 12;
+// breakpoint times are in thousandths of the on time, so an envelope stretches with it
+pub const TIME_SCALE: u16 = 1000;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Breakpoint {
+    pub time: u16,
+    pub power: u8,
+}
+
+impl Breakpoint {
+    pub const fn new(time: u16, power: u8) -> Self {
+        Self { time, power }
+    }
+}
+
+// piecewise linear power envelope across the on time. the first breakpoint is always at time 0
+// and the last at TIME_SCALE, breakpoints are kept in time order
+#[derive(Clone)]
+pub struct Envelope {
+    name: [u8; NAME_CAPACITY],
+    name_len: usize,
+    points: [Breakpoint; MAX_BREAKPOINTS],
+    len: usize,
+}
+
+impl Envelope {
+    // name length, point count, then time and power of each point
+    const SERIALIZED_SIZE: usize = 1 + NAME_CAPACITY + 1 + MAX_BREAKPOINTS * 3;
+
+    pub fn new(name: &str, breakpoints: &[Breakpoint]) -> Self {
+        let mut points = [Breakpoint::new(0, 0); MAX_BREAKPOINTS];
+        let len = breakpoints.len().clamp(2, MAX_BREAKPOINTS);
+        for (i, point) in breakpoints.iter().take(len).enumerate() {
+            points[i] = *point;
+        }
+        if breakpoints.len() < 2 {
+            points[1] = Breakpoint::new(TIME_SCALE, points[0].power);
+        }
+        points[0].time = 0;
+        points[len - 1].time = TIME_SCALE;
+        let mut envelope = Self { name: [0u8; NAME_CAPACITY], name_len: 0, points, len };
+        envelope.set_name(name);
+        envelope
+    }
+
+    pub fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("")
+    }
+
+    // cut short to fit, never splitting a character
+    pub fn set_name(&mut self, name: &str) {
+        self.name_len = 0;
+        for c in name.chars() {
+            let mut encoded = [0u8; 4];
+            let encoded = c.encode_utf8(&mut encoded).as_bytes();
+            if self.name_len + encoded.len() > NAME_CAPACITY {
+                break;
+            }
+            self.name[self.name_len..self.name_len + encoded.len()].copy_from_slice(encoded);
+            self.name_len += encoded.len();
+        }
+    }
+
+    fn write_to(&self, bytes: &mut [u8]) {
+        bytes[0] = self.name_len as u8;
+        bytes[1..1 + NAME_CAPACITY].copy_from_slice(&self.name);
+        bytes[1 + NAME_CAPACITY] = self.len as u8;
+        for (i, point) in self.points.iter().enumerate() {
+            let offset = 2 + NAME_CAPACITY + i * 3;
+            bytes[offset..offset + 2].copy_from_slice(&point.time.to_le_bytes());
+            bytes[offset + 2] = point.power;
+        }
+    }
+
+    // None unless the bytes hold an envelope the editor could have made
+    fn read_from(bytes: &[u8]) -> Option<Self> {
+        let name_len = bytes[0] as usize;
+        let len = bytes[1 + NAME_CAPACITY] as usize;
+        if name_len > NAME_CAPACITY || core::str::from_utf8(&bytes[1..1 + name_len]).is_err() || !(2..=MAX_BREAKPOINTS).contains(&len) {
+            return None;
+        }
+        let mut envelope = Self { name: [0u8; NAME_CAPACITY], name_len, points: [Breakpoint::new(0, 0); MAX_BREAKPOINTS], len };
+        envelope.name.copy_from_slice(&bytes[1..1 + NAME_CAPACITY]);
+        for (i, point) in envelope.points.iter_mut().enumerate() {
+            let offset = 2 + NAME_CAPACITY + i * 3;
+            *point = Breakpoint::new(u16::from_le_bytes([bytes[offset], bytes[offset + 1]]), bytes[offset + 2]);
+        }
+        let points = envelope.breakpoints();
+        let in_order = points.windows(2).all(|pair| pair[0].time <= pair[1].time);
+        let in_range = points.iter().all(|point| point.power <= 100);
+        if points[0].time != 0 || points[len - 1].time != TIME_SCALE || !in_order || !in_range {
+            return None;
+        }
+        Some(envelope)
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.points[..self.len]
+    }
+
+    // power in percent at a time in thousandths of the on time
+    pub fn power_at(&self, time: u16) -> f32 {
+        let time = time.min(TIME_SCALE);
+        for pair in self.breakpoints().windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if time <= b.time {
+                if b.time == a.time {
+                    return b.power as f32;
+                }
+                let t = (time - a.time) as f32 / (b.time - a.time) as f32;
+                return a.power as f32 + (b.power as f32 - a.power as f32) * t;
+            }
+        }
+        self.points[self.len - 1].power as f32
+    }
+
+    // average power in percent over the whole on time
+    pub fn mean_power(&self) -> f32 {
+        mean_power(self.breakpoints().iter().map(|point| (point.time, point.power)))
+    }
+
+    pub fn to_message(&self) -> PowerEnvelope {
+        let mut points = [EnvelopePoint { time: 0, power: 0 }; MAX_ENVELOPE_POINTS];
+        for (point, breakpoint) in points.iter_mut().zip(self.breakpoints()) {
+            *point = EnvelopePoint { time: breakpoint.time, power: breakpoint.power };
+        }
+        PowerEnvelope { points, len: self.len as u8 }
+    }
+
+    // adds a breakpoint halfway between `index` and the next one, returning the new index
+    pub fn insert_after(&mut self, index: usize) -> Option<usize> {
+        if self.len == MAX_BREAKPOINTS || index + 1 >= self.len {
+            return None;
+        }
+        let (a, b) = (self.points[index], self.points[index + 1]);
+        if b.time - a.time < 2 {
+            return None;
+        }
+        let time = a.time + (b.time - a.time) / 2;
+        let point = Breakpoint::new(time, self.power_at(time) as u8);
+        self.points.copy_within(index + 1..self.len, index + 2);
+        self.points[index + 1] = point;
+        self.len += 1;
+        Some(index + 1)
+    }
+
+    // the end points can't be removed
+    pub fn remove(&mut self, index: usize) -> bool {
+        if index == 0 || index + 1 >= self.len {
+            return false;
+        }
+        self.points.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+        true
+    }
+
+    // moves a breakpoint in time, keeping it between its neighbours. the end points stay put
+    pub fn move_time(&mut self, index: usize, delta: i32) {
+        if index == 0 || index + 1 >= self.len {
+            return;
+        }
+        let min = self.points[index - 1].time as i32;
+        let max = self.points[index + 1].time as i32;
+        self.points[index].time = (self.points[index].time as i32 + delta).clamp(min, max) as u16;
+    }
+
+    pub fn move_power(&mut self, index: usize, delta: i32) {
+        if index < self.len {
+            self.points[index].power = (self.points[index].power as i32 + delta).clamp(0, 100) as u8;
+        }
+    }
+}
+
+// trapezoids between (time, power) points in order
+fn mean_power(points: impl Iterator<Item = (u16, u8)> + Clone) -> f32 {
+    points.clone().zip(points.skip(1))
+        .map(|(a, b)| b.0.saturating_sub(a.0) as f32 * (a.1 as f32 + b.1 as f32) / 2.0)
+        .sum::<f32>() / TIME_SCALE as f32
+}
+
+// average power in percent of an envelope as it is sent
+pub fn message_mean_power(envelope: &PowerEnvelope) -> f32 {
+    let len = (envelope.len as usize).min(MAX_ENVELOPE_POINTS);
+    mean_power(envelope.points[..len].iter().map(|point| (point.time, point.power)))
+}
+
+// the same envelope with every point's power scaled by factor
+pub fn scale_message(envelope: &PowerEnvelope, factor: f32) -> PowerEnvelope {
+    let mut scaled = envelope.clone();
+    for point in scaled.points.iter_mut() {
+        point.power = (point.power as f32 * factor.clamp(0.0, 1.0)) as u8;
+    }
+    scaled
+}
+
+pub const ENVELOPE_SLOTS: usize = 4;
+// marks the envelopes in a settings record, which older records don't have
+const PERSISTED_MARKER: u8 = 0xe1;
+pub const PERSISTED_SIZE: usize = 1 + ENVELOPE_SLOTS * Envelope::SERIALIZED_SIZE;
+
+pub fn write_envelopes(envelopes: &[Envelope; ENVELOPE_SLOTS], bytes: &mut [u8]) {
+    bytes[0] = PERSISTED_MARKER;
+    for (i, envelope) in envelopes.iter().enumerate() {
+        envelope.write_to(&mut bytes[1 + i * Envelope::SERIALIZED_SIZE..]);
+    }
+}
+
+// all the slots or nothing, so a damaged record falls back to the defaults as a whole
+pub fn read_envelopes(bytes: &[u8]) -> Option<[Envelope; ENVELOPE_SLOTS]> {
+    if bytes.len() < PERSISTED_SIZE || bytes[0] != PERSISTED_MARKER {
+        return None;
+    }
+    let mut envelopes = default_envelopes();
+    for (i, envelope) in envelopes.iter_mut().enumerate() {
+        *envelope = Envelope::read_from(&bytes[1 + i * Envelope::SERIALIZED_SIZE..])?;
+    }
+    Some(envelopes)
+}
+
+pub fn default_envelopes() -> [Envelope; ENVELOPE_SLOTS] {
+    [
+        Envelope::new("Linear Ramp", &[Breakpoint::new(0, 0), Breakpoint::new(TIME_SCALE, 100)]),
+        Envelope::new("Soft Start", &[Breakpoint::new(0, 10), Breakpoint::new(200, 60), Breakpoint::new(TIME_SCALE, 100)]),
+        Envelope::new("Ramp Hold", &[Breakpoint::new(0, 0), Breakpoint::new(600, 80), Breakpoint::new(TIME_SCALE, 80)]),
+        Envelope::new("Flat", &[Breakpoint::new(0, 50), Breakpoint::new(TIME_SCALE, 50)]),
+    ]
+}
@@ -0,0 +1,202 @@
+use core::fmt::{self, Write};
+use core::mem::{self, Discriminant};
+
+use qcw_com::{ControllerMessage, ParameterValue, RunMode};
+
+use crate::envelope::message_mean_power;
+
+pub const LOG_CAPACITY: usize = 64;
+pub const MESSAGE_CAPACITY: usize = 40;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Severity::Debug => "D",
+            Severity::Info => "I",
+            Severity::Warning => "W",
+            Severity::Error => "E",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LogEntry {
+    pub timestamp_us: u64,
+    pub severity: Severity,
+    message: [u8; MESSAGE_CAPACITY],
+    message_len: usize,
+    // set on entries that later ones about the same parameter replace rather than add to
+    parameter: Option<Discriminant<ParameterValue>>,
+}
+
+impl LogEntry {
+    const fn empty() -> Self {
+        Self {
+            timestamp_us: 0,
+            severity: Severity::Debug,
+            message: [0u8; MESSAGE_CAPACITY],
+            message_len: 0,
+            parameter: None,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        core::str::from_utf8(&self.message[..self.message_len]).unwrap_or("")
+    }
+}
+
+impl Write for LogEntry {
+    // silently truncates, never splitting a character
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            let mut encoded = [0u8; 4];
+            let encoded = c.encode_utf8(&mut encoded).as_bytes();
+            if self.message_len + encoded.len() > MESSAGE_CAPACITY {
+                break;
+            }
+            self.message[self.message_len..self.message_len + encoded.len()].copy_from_slice(encoded);
+            self.message_len += encoded.len();
+        }
+        Ok(())
+    }
+}
+
+// fixed size ring buffer of the most recent events. once full, the oldest entry is overwritten
+pub struct EventLog {
+    entries: [LogEntry; LOG_CAPACITY],
+    head: usize,
+    len: usize,
+    total: u32,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventLog {
+    pub const fn new() -> Self {
+        const EMPTY: LogEntry = LogEntry::empty();
+        Self {
+            entries: [EMPTY; LOG_CAPACITY],
+            head: 0,
+            len: 0,
+            total: 0,
+        }
+    }
+
+    pub fn push(&mut self, timestamp_us: u64, severity: Severity, message: fmt::Arguments<'_>) {
+        let index = (self.head + self.len) % LOG_CAPACITY;
+        if self.len == LOG_CAPACITY {
+            self.head = (self.head + 1) % LOG_CAPACITY;
+        } else {
+            self.len += 1;
+        }
+        self.total = self.total.wrapping_add(1);
+        self.write_entry(index, timestamp_us, severity, None, message);
+    }
+
+    // like push, but if the newest entry is about the same parameter it's overwritten instead, so
+    // scrolling a value through its range leaves one entry with where it ended up
+    pub fn push_parameter(&mut self, timestamp_us: u64, severity: Severity, value: &ParameterValue, message: fmt::Arguments<'_>) {
+        let parameter = Some(mem::discriminant(value));
+        if self.len > 0 {
+            let newest = (self.head + self.len - 1) % LOG_CAPACITY;
+            if self.entries[newest].parameter == parameter {
+                self.write_entry(newest, timestamp_us, severity, parameter, message);
+                return;
+            }
+        }
+        self.push(timestamp_us, severity, message);
+        let newest = (self.head + self.len - 1) % LOG_CAPACITY;
+        self.entries[newest].parameter = parameter;
+    }
+
+    fn write_entry(&mut self, index: usize, timestamp_us: u64, severity: Severity, parameter: Option<Discriminant<ParameterValue>>, message: fmt::Arguments<'_>) {
+        let entry = &mut self.entries[index];
+        entry.timestamp_us = timestamp_us;
+        entry.severity = severity;
+        entry.parameter = parameter;
+        entry.message_len = 0;
+        _ = entry.write_fmt(message);
+
+        // host builds of the log have no rtt to copy it to
+        #[cfg(target_os = "none")]
+        {
+            let text = entry.message();
+            match severity {
+                Severity::Debug => defmt::debug!("[{=u64}us] {=str}", timestamp_us, text),
+                Severity::Info => defmt::info!("[{=u64}us] {=str}", timestamp_us, text),
+                Severity::Warning => defmt::warn!("[{=u64}us] {=str}", timestamp_us, text),
+                Severity::Error => defmt::error!("[{=u64}us] {=str}", timestamp_us, text),
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // number of entries ever logged, including ones that have since been overwritten
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    // index 0 is the oldest entry still held
+    pub fn get(&self, index: usize) -> Option<&LogEntry> {
+        if index < self.len {
+            Some(&self.entries[(self.head + index) % LOG_CAPACITY])
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+// keeps a record of the outgoing messages that change what the controller is doing
+pub fn log_sent_message(log: &mut EventLog, timestamp_us: u64, message: &ControllerMessage) {
+    match message {
+        ControllerMessage::Run => log.push(timestamp_us, Severity::Info, format_args!("Sent Run")),
+        ControllerMessage::Stop => log.push(timestamp_us, Severity::Info, format_args!("Sent Stop")),
+        ControllerMessage::ResetStats => log.push(timestamp_us, Severity::Info, format_args!("Sent Reset Stats")),
+        ControllerMessage::SetParam(value) => log.push_parameter(timestamp_us, Severity::Info, value, format_args!("Set {}", DisplayParameterValue(value))),
+        ControllerMessage::SetEnvelope(envelope) => log.push(timestamp_us, Severity::Info, format_args!("Set Envelope avg {:.0}%", message_mean_power(envelope))),
+        _ => {}
+    }
+}
+
+pub struct DisplayParameterValue<'a>(pub &'a ParameterValue);
+
+impl fmt::Display for DisplayParameterValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            ParameterValue::OnTimeUs(on_time) => write!(f, "On Time {}µs", on_time),
+            ParameterValue::OffTimeMs(off_time) => write!(f, "Off Time {}ms", off_time),
+            ParameterValue::StartupFrequencykHz(frequency) => write!(f, "Frequency {:.1}kHz", frequency),
+            ParameterValue::FlatPower(power) => write!(f, "Power {:.0}%", power * 100.0),
+            ParameterValue::DelayCompensationNS(delay) => write!(f, "Phase Delay {}ns", delay),
+            ParameterValue::RunMode(RunMode::OpenLoop) => write!(f, "Mode Open Loop"),
+            ParameterValue::RunMode(RunMode::TestClosedLoop) => write!(f, "Mode Closed Loop Test"),
+            // for anything a newer qcw_com adds
+            #[allow(unreachable_patterns)]
+            _ => write!(f, "Parameter"),
+        }
+    }
+}
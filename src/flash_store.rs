@@ -0,0 +1,154 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rp235x_hal as hal;
+
+// settings that have to survive a power cycle live in the last sector of flash, as a run of
+// page sized records. each save goes in the next blank page and the sector is only erased once
+// it fills up, the newest valid record wins. memory.x keeps FLASH out of this sector
+const FLASH_SIZE: u32 = 2048 * 1024;
+const SECTOR_SIZE: u32 = 4096;
+pub const PAGE_SIZE: usize = 256;
+const STORE_OFFSET: u32 = FLASH_SIZE - SECTOR_SIZE;
+const PAGES_PER_SECTOR: usize = SECTOR_SIZE as usize / PAGE_SIZE;
+const XIP_BASE: u32 = 0x1000_0000;
+
+const RECORD_MAGIC: u32 = 0x5157_4353;
+// magic and sequence number up front, checksum at the end
+const HEADER_SIZE: usize = 8;
+pub const PAYLOAD_SIZE: usize = PAGE_SIZE - HEADER_SIZE - 4;
+
+const SECTOR_ERASE_COMMAND: u8 = 0x20;
+
+// qmi window 0 read setup, which flash_exit_xip throws away
+const QMI_BASE: u32 = 0x400d_0000;
+const QMI_M0_TIMING: u32 = QMI_BASE + 0x0c;
+const QMI_M0_RFMT: u32 = QMI_BASE + 0x10;
+const QMI_M0_RCMD: u32 = QMI_BASE + 0x14;
+
+static PARK_REQUEST: AtomicBool = AtomicBool::new(false);
+static PARKED: AtomicBool = AtomicBool::new(false);
+
+fn checksum(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn page(index: usize) -> &'static [u8; PAGE_SIZE] {
+    unsafe { &*((XIP_BASE + STORE_OFFSET + (index * PAGE_SIZE) as u32) as *const [u8; PAGE_SIZE]) }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn valid_record(page: &[u8; PAGE_SIZE]) -> Option<u32> {
+    let stored_checksum = read_u32(page, PAGE_SIZE - 4);
+    if read_u32(page, 0) == RECORD_MAGIC && stored_checksum == checksum(&page[..PAGE_SIZE - 4]) {
+        Some(read_u32(page, 4))
+    } else {
+        None
+    }
+}
+
+fn latest_record() -> Option<(usize, u32)> {
+    (0..PAGES_PER_SECTOR)
+        .filter_map(|index| valid_record(page(index)).map(|sequence| (index, sequence)))
+        .max_by_key(|(_, sequence)| *sequence)
+}
+
+pub fn read() -> Option<[u8; PAYLOAD_SIZE]> {
+    let (index, _) = latest_record()?;
+    let mut payload = [0u8; PAYLOAD_SIZE];
+    payload.copy_from_slice(&page(index)[HEADER_SIZE..HEADER_SIZE + PAYLOAD_SIZE]);
+    Some(payload)
+}
+
+// core1 runs the display out of flash, so it has to be parked somewhere else while flash is busy
+pub fn park_requested() -> bool {
+    PARK_REQUEST.load(Ordering::Acquire)
+}
+
+#[inline(never)]
+#[link_section = ".data.ram_func"]
+pub fn park() {
+    PARKED.store(true, Ordering::Release);
+    while PARK_REQUEST.load(Ordering::Acquire) {
+        cortex_m::asm::nop();
+    }
+    PARKED.store(false, Ordering::Release);
+}
+
+struct RomFlashFunctions {
+    connect_internal_flash: unsafe extern "C" fn(),
+    flash_exit_xip: unsafe extern "C" fn(),
+    flash_range_erase: unsafe extern "C" fn(u32, usize, u32, u8),
+    flash_range_program: unsafe extern "C" fn(u32, *const u8, usize),
+    flash_flush_cache: unsafe extern "C" fn(),
+}
+
+// nothing in here may touch flash, including the rom function wrappers, so the rom function
+// pointers are looked up beforehand and the qmi registers are poked directly
+#[inline(never)]
+#[link_section = ".data.ram_func"]
+unsafe fn write_page_from_ram(functions: &RomFlashFunctions, page_offset: u32, erase: bool, data: &[u8; PAGE_SIZE]) {
+    let timing = core::ptr::read_volatile(QMI_M0_TIMING as *const u32);
+    let rfmt = core::ptr::read_volatile(QMI_M0_RFMT as *const u32);
+    let rcmd = core::ptr::read_volatile(QMI_M0_RCMD as *const u32);
+
+    (functions.connect_internal_flash)();
+    (functions.flash_exit_xip)();
+    if erase {
+        (functions.flash_range_erase)(STORE_OFFSET, SECTOR_SIZE as usize, SECTOR_SIZE, SECTOR_ERASE_COMMAND);
+    }
+    (functions.flash_range_program)(page_offset, data.as_ptr(), PAGE_SIZE);
+    (functions.flash_flush_cache)();
+
+    core::ptr::write_volatile(QMI_M0_TIMING as *mut u32, timing);
+    core::ptr::write_volatile(QMI_M0_RFMT as *mut u32, rfmt);
+    core::ptr::write_volatile(QMI_M0_RCMD as *mut u32, rcmd);
+}
+
+// stores a new record, taking the display offline for as long as the erase and program take
+pub fn write(payload: &[u8; PAYLOAD_SIZE]) {
+    let (next_index, sequence) = match latest_record() {
+        Some((index, sequence)) => ((index + 1) % PAGES_PER_SECTOR, sequence.wrapping_add(1)),
+        None => (0, 0),
+    };
+    let erase = page(next_index).iter().any(|byte| *byte != 0xff);
+    let (next_index, erase) = if erase && next_index != 0 {
+        // stale records past the newest one, start the sector over
+        (0, true)
+    } else {
+        (next_index, erase)
+    };
+
+    let mut data = [0xffu8; PAGE_SIZE];
+    data[0..4].copy_from_slice(&RECORD_MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&sequence.to_le_bytes());
+    data[HEADER_SIZE..HEADER_SIZE + PAYLOAD_SIZE].copy_from_slice(payload);
+    let record_checksum = checksum(&data[..PAGE_SIZE - 4]);
+    data[PAGE_SIZE - 4..].copy_from_slice(&record_checksum.to_le_bytes());
+
+    let functions = unsafe {
+        RomFlashFunctions {
+            connect_internal_flash: core::mem::transmute(hal::rom_data::connect_internal_flash::ptr()),
+            flash_exit_xip: core::mem::transmute(hal::rom_data::flash_exit_xip::ptr()),
+            flash_range_erase: core::mem::transmute(hal::rom_data::flash_range_erase::ptr()),
+            flash_range_program: core::mem::transmute(hal::rom_data::flash_range_program::ptr()),
+            flash_flush_cache: core::mem::transmute(hal::rom_data::flash_flush_cache::ptr()),
+        }
+    };
+
+    PARK_REQUEST.store(true, Ordering::Release);
+    while !PARKED.load(Ordering::Acquire) {
+        cortex_m::asm::nop();
+    }
+    cortex_m::interrupt::free(|_| unsafe {
+        write_page_from_ram(&functions, STORE_OFFSET + (next_index * PAGE_SIZE) as u32, erase, &data);
+    });
+    PARK_REQUEST.store(false, Ordering::Release);
+}
@@ -0,0 +1,126 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::draw_target::DrawTarget;
+
+// how a bitmap's pixels combine with what's already on the target
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RasterOp {
+    // the bitmap replaces what was there
+    Copy,
+    // set pixels light the target, clear ones leave it alone
+    Or,
+    // clear pixels turn the target off, set ones leave it alone
+    And,
+    // set pixels flip the target
+    Xor,
+    // the bitmap with set and clear swapped replaces what was there
+    Invert,
+}
+
+// one bit image, packed like glyphs
+pub struct Bitmap<Data = Vec<u8>> {
+    width: usize,
+    height: usize,
+    data: Data,
+}
+
+impl Bitmap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; (width * height).div_ceil(8)],
+        }
+    }
+
+    pub fn clear(&mut self, color: bool) {
+        self.data.fill(if color { 0xFF } else { 0x00 });
+    }
+}
+
+impl Bitmap<&'static [u8]> {
+    pub const fn from_static(width: usize, height: usize, data: &'static [u8]) -> Self {
+        Self { width, height, data }
+    }
+}
+
+impl<Data: AsRef<[u8]>> Bitmap<Data> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn size(&self) -> (isize, isize) {
+        (self.width as isize, self.height as isize)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    fn bit(&self, position: (isize, isize)) -> Option<usize> {
+        let inside = (0..self.width as isize).contains(&position.0) && (0..self.height as isize).contains(&position.1);
+        inside.then(|| position.1 as usize * self.width + position.0 as usize)
+    }
+
+    // outside the bitmap is clear
+    pub fn get(&self, position: (isize, isize)) -> bool {
+        self.bit(position).map(|bit| self.data.as_ref()[bit / 8] & (1 << (bit % 8)) != 0).unwrap_or(false)
+    }
+
+    // the bitmap with its upper left corner at the position, combined with the target by the op
+    pub fn blit<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize), op: RasterOp) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let lower_right = (position.0 + self.width as isize - 1, position.1 + self.height as isize - 1);
+        let data = self.data.as_ref();
+        match op {
+            RasterOp::Copy => {
+                target.fill_rect(position, lower_right, false);
+                target.blit_bitmap(position, data, self.width, self.height, true);
+            },
+            RasterOp::Or => target.blit_bitmap(position, data, self.width, self.height, true),
+            RasterOp::And => {
+                let inverse: Vec<u8> = data.iter().map(|byte| !byte).collect();
+                target.blit_bitmap(position, &inverse, self.width, self.height, false);
+            },
+            RasterOp::Xor => {
+                for y in 0..self.height as isize {
+                    for x in 0..self.width as isize {
+                        if self.get((x, y)) {
+                            let pixel = (position.0 + x, position.1 + y);
+                            let color = !target.get_pixel(pixel);
+                            target.set_pixel(pixel, color);
+                        }
+                    }
+                }
+            },
+            RasterOp::Invert => {
+                target.fill_rect(position, lower_right, true);
+                target.blit_bitmap(position, data, self.width, self.height, false);
+            },
+        }
+    }
+}
+
+impl<Data: AsRef<[u8]> + AsMut<[u8]>> DrawTarget for Bitmap<Data> {
+    fn set_pixel(&mut self, position: (isize, isize), color: bool) {
+        if let Some(bit) = self.bit(position) {
+            let byte = &mut self.data.as_mut()[bit / 8];
+            if color {
+                *byte |= 1 << (bit % 8);
+            } else {
+                *byte &= !(1 << (bit % 8));
+            }
+        }
+    }
+
+    fn get_pixel(&self, position: (isize, isize)) -> bool {
+        self.get(position)
+    }
+}
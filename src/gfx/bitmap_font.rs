@@ -1,5 +1,25 @@
 use super::draw_target::DrawTarget;
 
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Align {
+    // left or top
+    Start,
+    Center,
+    // right or bottom
+    End,
+}
+
+impl Align {
+    // where something `used` long starts in `available` space
+    pub fn offset(&self, available: isize, used: isize) -> isize {
+        match self {
+            Align::Start => 0,
+            Align::Center => (available - used) / 2,
+            Align::End => available - used,
+        }
+    }
+}
+
 
 
 pub struct Glyph {
@@ -12,20 +32,7 @@ pub struct Glyph {
 
 impl Glyph {
     pub fn draw<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize), color: bool) {
-        for gy in 0..self.height {
-            for gx in 0..self.width {
-                let bit = gx + gy * self.width;
-                let byte = bit / 8;
-                let bit = bit % 8;
-                if (self.bitmap[byte] & (1 << bit)) != 0 {
-                    let position = (
-                        position.0 + gx as isize,
-                        position.1 + gy as isize - self.baseline
-                    );
-                    target.set_pixel(position, color);
-                }
-            }
-        }
+        target.blit_bitmap((position.0, position.1 - self.baseline), self.bitmap, self.width, self.height, color);
     }
 
     pub fn advance(&self) -> usize {
@@ -41,10 +48,41 @@ pub struct GlyphRange {
 pub struct BitmapFont {
     pub line_height: usize,
     pub ranges: &'static [&'static GlyphRange],
+    // drawn in place of characters the font doesn't have, which are skipped when there isn't one
+    pub fallback: Option<&'static Glyph>,
+    // ascii glyphs looked up directly instead of searching the ranges
+    ascii: [Option<&'static Glyph>; 128],
 }
 
 impl BitmapFont {
+    pub const fn new(line_height: usize, ranges: &'static [&'static GlyphRange], fallback: Option<&'static Glyph>) -> Self {
+        let mut ascii = [None; 128];
+        let mut r = 0;
+        while r < ranges.len() {
+            let range = ranges[r];
+            let mut i = 0;
+            while i < range.glyphs.len() {
+                // the first range with the character wins, like find_glyph's search
+                let c = range.start_char as usize + i;
+                if c < ascii.len() && ascii[c].is_none() {
+                    ascii[c] = Some(range.glyphs[i]);
+                }
+                i += 1;
+            }
+            r += 1;
+        }
+        Self {
+            line_height,
+            ranges,
+            fallback,
+            ascii,
+        }
+    }
+
     pub fn find_glyph(&self, c: char) -> Option<&'static Glyph> {
+        if let Some(glyph) = self.ascii.get(c as usize) {
+            return *glyph;
+        }
         for range in self.ranges {
             if (range.start_char as usize) <= (c as usize) && (c as usize) < (range.start_char as usize + range.glyphs.len()) {
                 let index = c as usize - range.start_char as usize;
@@ -54,26 +92,117 @@ impl BitmapFont {
         None
     }
 
+    // the glyph text is drawn and measured with, the fallback when the font doesn't have it
+    pub fn glyph(&self, c: char) -> Option<&'static Glyph> {
+        self.find_glyph(c).or(self.fallback)
+    }
+
     pub fn draw_text_line<Target: DrawTarget>(&self, target: &mut Target, baseline_position: (isize, isize), line: &str, color: bool) {
         let mut position = baseline_position;
         for c in line.chars() {
-            if let Some(glyph) = self.find_glyph(c) {
+            if let Some(glyph) = self.glyph(c) {
                 glyph.draw(target, position, color);
                 position.0 += glyph.advance() as isize;
             }
         }
     }
 
+    fn glyphs(&self) -> impl Iterator<Item = &'static Glyph> + '_ {
+        self.ranges.iter().flat_map(|range| range.glyphs.iter().copied())
+    }
+
+    // rows the tallest glyph reaches above the baseline
+    pub fn ascent(&self) -> isize {
+        self.glyphs().map(|glyph| glyph.baseline).max().unwrap_or(0)
+    }
+
+    // rows the deepest descender reaches below the baseline
+    pub fn descent(&self) -> isize {
+        self.glyphs().map(|glyph| glyph.height as isize - 1 - glyph.baseline).max().unwrap_or(0)
+    }
+
+    // where the baseline sits in a line_height tall line, with the glyphs centered in it
+    pub fn baseline(&self) -> isize {
+        let used = self.ascent() + 1 + self.descent();
+        (self.line_height as isize - used) / 2 + self.ascent()
+    }
+
+    pub fn has_glyphs(&self, text: &str) -> bool {
+        text.chars().all(|c| self.find_glyph(c).is_some())
+    }
+
     pub fn get_text_width(&self, line: &str) -> isize {
         let mut width = 0;
         for c in line.chars() {
-            if let Some(glyph) = self.find_glyph(c) {
+            if let Some(glyph) = self.glyph(c) {
                 width += glyph.advance() as isize;
             }
         }
         width.saturating_sub(1)
     }
+
+    // line breaks for text wrapped to max_width
+    pub fn wrap<'a>(&'a self, text: &'a str, max_width: isize) -> WrappedLines<'a> {
+        WrappedLines {
+            font: self,
+            rest: Some(text),
+            max_width,
+        }
+    }
+
+    // size of the text as draw_text_block lays it out
+    pub fn measure_text(&self, text: &str, max_width: Option<isize>) -> (isize, isize) {
+        let mut size = (0, 0);
+        for line in self.wrap(text, max_width.unwrap_or(isize::MAX)) {
+            size.0 = size.0.max(self.get_text_width(line));
+            size.1 += self.line_height as isize;
+        }
+        size
+    }
+
+    // wrapped text from the upper left corner. returns the size drawn
+    pub fn draw_text_block<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize), text: &str, max_width: isize, align: Align, color: bool) -> (isize, isize) {
+        let mut size = (0, 0);
+        for line in self.wrap(text, max_width) {
+            let width = self.get_text_width(line);
+            let x = position.0 + align.offset(max_width, width);
+            self.draw_text_line(target, (x, position.1 + size.1 + self.baseline()), line, color);
+            size.0 = size.0.max(width);
+            size.1 += self.line_height as isize;
+        }
+        size
+    }
 }
 
+pub struct WrappedLines<'a> {
+    font: &'a BitmapFont,
+    rest: Option<&'a str>,
+    max_width: isize,
+}
 
+impl<'a> Iterator for WrappedLines<'a> {
+    type Item = &'a str;
 
+    fn next(&mut self) -> Option<&'a str> {
+        let text = self.rest?;
+        let paragraph_end = text.find('\n').unwrap_or(text.len());
+        let mut width = 0;
+        // where the line would end and the next one start if it broke at the last space
+        let mut last_space = None;
+        for (index, c) in text[..paragraph_end].char_indices() {
+            if c == ' ' && index > 0 {
+                last_space = Some((index, index + 1));
+            }
+            let advance = self.font.glyph(c).map(|glyph| glyph.advance() as isize).unwrap_or(0);
+            if index > 0 && width + advance - 1 > self.max_width {
+                let (end, next) = last_space.unwrap_or((index, index));
+                let rest = text[next..].trim_start_matches(' ');
+                self.rest = if rest.is_empty() { None } else { Some(rest) };
+                return Some(text[..end].trim_end_matches(' '));
+            }
+            width += advance;
+        }
+        self.rest = text.get(paragraph_end + 1..);
+        Some(text[..paragraph_end].trim_end_matches(' '))
+    }
+}
@@ -0,0 +1,81 @@
+use super::draw_target::{intersect, Corners};
+
+// more rects than this and the whole screen is redrawn
+const MAX_RECTS: usize = 2;
+
+// changed parts of the screen, as up to two rects
+#[derive(Copy, Clone, Debug)]
+pub struct DirtyRegion {
+    bounds: Corners,
+    rects: [Corners; MAX_RECTS],
+    count: usize,
+}
+
+impl DirtyRegion {
+    pub const fn new(width: usize, height: usize) -> Self {
+        Self {
+            bounds: ((0, 0), (width as isize - 1, height as isize - 1)),
+            rects: [((0, 0), (0, 0)); MAX_RECTS],
+            count: 0,
+        }
+    }
+
+    pub fn mark(&mut self, upper_left: (isize, isize), lower_right: (isize, isize)) {
+        let Some(mut rect) = intersect((upper_left, lower_right), self.bounds) else {
+            return;
+        };
+        loop {
+            // a merged rect can reach ones the original didn't, so keep going until nothing touches
+            if let Some(index) = self.rects().iter().position(|other| touching(rect, *other)) {
+                rect = union(rect, self.remove(index));
+            } else if self.count == MAX_RECTS {
+                self.count = 0;
+                rect = self.bounds;
+            } else {
+                break;
+            }
+        }
+        self.rects[self.count] = rect;
+        self.count += 1;
+    }
+
+    pub fn mark_all(&mut self) {
+        self.mark(self.bounds.0, self.bounds.1);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn rects(&self) -> &[Corners] {
+        &self.rects[..self.count]
+    }
+
+    pub fn clear(&mut self) {
+        self.count = 0;
+    }
+
+    // the region as it is, leaving this one empty
+    pub fn take(&mut self) -> Self {
+        let region = *self;
+        self.clear();
+        region
+    }
+
+    fn remove(&mut self, index: usize) -> Corners {
+        let rect = self.rects[index];
+        self.count -= 1;
+        self.rects[index] = self.rects[self.count];
+        rect
+    }
+}
+
+// overlapping or right next to each other, so drawing the two as one rect costs little extra
+fn touching(a: Corners, b: Corners) -> bool {
+    a.0.0 <= b.1.0 + 1 && b.0.0 <= a.1.0 + 1 &&
+    a.0.1 <= b.1.1 + 1 && b.0.1 <= a.1.1 + 1
+}
+
+fn union(a: Corners, b: Corners) -> Corners {
+    ((a.0.0.min(b.0.0), a.0.1.min(b.0.1)), (a.1.0.max(b.1.0), a.1.1.max(b.1.1)))
+}
@@ -1,10 +1,61 @@
 pub trait Mask {
     fn get_mask(&self, position: (isize, isize)) -> bool;
+
+    // the rect a mask lets through, if that's all it does
+    fn as_rect(&self) -> Option<((isize, isize), (isize, isize))> {
+        None
+    }
+}
+
+// corners of a rect, inclusive
+pub(crate) type Corners = ((isize, isize), (isize, isize));
+
+pub(crate) fn intersect(a: Corners, b: Corners) -> Option<Corners> {
+    let upper_left = (a.0.0.max(b.0.0), a.0.1.max(b.0.1));
+    let lower_right = (a.1.0.min(b.1.0), a.1.1.min(b.1.1));
+    if upper_left.0 <= lower_right.0 && upper_left.1 <= lower_right.1 {
+        Some((upper_left, lower_right))
+    } else {
+        None
+    }
 }
 
 pub trait DrawTarget {
     fn set_pixel(&mut self, position: (isize, isize), color: bool);
     fn get_pixel(&self, position: (isize, isize)) -> bool;
+
+    // pixel by pixel defaults, override where faster
+
+    // the pixels from x_start to x_end inclusive on one row, x_start being the leftmost
+    fn fill_span(&mut self, x_start: isize, x_end: isize, y: isize, color: bool) {
+        for x in x_start..=x_end {
+            self.set_pixel((x, y), color);
+        }
+    }
+
+    // every pixel between the corners inclusive
+    fn fill_rect(&mut self, upper_left: (isize, isize), lower_right: (isize, isize), color: bool) {
+        for y in upper_left.1..=lower_right.1 {
+            self.fill_span(upper_left.0, lower_right.0, y, color);
+        }
+    }
+
+    // one bit bitmap, glyph layout. clear bits are left alone
+    fn blit_bitmap(&mut self, position: (isize, isize), bitmap: &[u8], width: usize, height: usize, color: bool) {
+        blit_bitmap_pixels(self, position, bitmap, width, height, color);
+    }
+}
+
+// blit_bitmap a pixel at a time, for targets to fall back on when their faster way can't be used
+pub fn blit_bitmap_pixels<Target: DrawTarget + ?Sized>(target: &mut Target, position: (isize, isize), bitmap: &[u8], width: usize, height: usize, color: bool) {
+    for y in 0..height {
+        for x in 0..width {
+            let bit = x + y * width;
+            if bitmap.get(bit / 8).map(|byte| byte & (1 << (bit % 8)) != 0).unwrap_or(false) {
+                target.set_pixel((position.0 + x as isize, position.1 + y as isize), color);
+            }
+        }
+    }
 }
 
 pub trait _DTRef: DrawTarget + Sized {
@@ -29,6 +80,18 @@ impl<T: DrawTarget> DrawTarget for DTRef<'_, T> {
     fn set_pixel(&mut self, position: (isize, isize), color: bool) {
         self.dt.set_pixel(position, color);
     }
+
+    fn fill_span(&mut self, x_start: isize, x_end: isize, y: isize, color: bool) {
+        self.dt.fill_span(x_start, x_end, y, color);
+    }
+
+    fn fill_rect(&mut self, upper_left: (isize, isize), lower_right: (isize, isize), color: bool) {
+        self.dt.fill_rect(upper_left, lower_right, color);
+    }
+
+    fn blit_bitmap(&mut self, position: (isize, isize), bitmap: &[u8], width: usize, height: usize, color: bool) {
+        self.dt.blit_bitmap(position, bitmap, width, height, color);
+    }
 }
 
 pub struct TranslatedDrawTarget<Inner: DrawTarget> {
@@ -45,7 +108,7 @@ impl <Inner: DrawTarget> TranslatedDrawTarget<Inner> {
     }
 }
 
-impl<'a, Inner: DrawTarget> DrawTarget for TranslatedDrawTarget<Inner> {
+impl<Inner: DrawTarget> DrawTarget for TranslatedDrawTarget<Inner> {
     fn set_pixel(&mut self, position: (isize, isize), color: bool) {
         self.inner.set_pixel((position.0 + self.offset.0, position.1 + self.offset.1), color);
     }
@@ -53,6 +116,20 @@ impl<'a, Inner: DrawTarget> DrawTarget for TranslatedDrawTarget<Inner> {
     fn get_pixel(&self, position: (isize, isize)) -> bool {
         self.inner.get_pixel((position.0 + self.offset.0, position.1 + self.offset.1))
     }
+
+    fn fill_span(&mut self, x_start: isize, x_end: isize, y: isize, color: bool) {
+        self.inner.fill_span(x_start + self.offset.0, x_end + self.offset.0, y + self.offset.1, color);
+    }
+
+    fn fill_rect(&mut self, upper_left: (isize, isize), lower_right: (isize, isize), color: bool) {
+        let upper_left = (upper_left.0 + self.offset.0, upper_left.1 + self.offset.1);
+        let lower_right = (lower_right.0 + self.offset.0, lower_right.1 + self.offset.1);
+        self.inner.fill_rect(upper_left, lower_right, color);
+    }
+
+    fn blit_bitmap(&mut self, position: (isize, isize), bitmap: &[u8], width: usize, height: usize, color: bool) {
+        self.inner.blit_bitmap((position.0 + self.offset.0, position.1 + self.offset.1), bitmap, width, height, color);
+    }
 }
 
 pub trait _Translatable: DrawTarget + Sized {
@@ -90,6 +167,33 @@ impl<Inner: DrawTarget, M: Mask> DrawTarget for MaskedDrawTarget<Inner, M> {
     fn get_pixel(&self, position: (isize, isize)) -> bool {
         self.inner.get_pixel(position)
     }
+
+    // rect masks are clips, and cut what's drawn down to the part inside them
+    fn fill_span(&mut self, x_start: isize, x_end: isize, y: isize, color: bool) {
+        self.fill_rect((x_start, y), (x_end, y), color);
+    }
+
+    fn fill_rect(&mut self, upper_left: (isize, isize), lower_right: (isize, isize), color: bool) {
+        match self.mask.as_rect() {
+            Some(clip) => if let Some((upper_left, lower_right)) = intersect((upper_left, lower_right), clip) {
+                self.inner.fill_rect(upper_left, lower_right, color);
+            },
+            None => for y in upper_left.1..=lower_right.1 {
+                for x in upper_left.0..=lower_right.0 {
+                    self.set_pixel((x, y), color);
+                }
+            },
+        }
+    }
+
+    // a bitmap only skips the mask when all of it is inside
+    fn blit_bitmap(&mut self, position: (isize, isize), bitmap: &[u8], width: usize, height: usize, color: bool) {
+        let bounds = (position, (position.0 + width as isize - 1, position.1 + height as isize - 1));
+        match self.mask.as_rect() {
+            Some(clip) if intersect(bounds, clip) == Some(bounds) => self.inner.blit_bitmap(position, bitmap, width, height, color),
+            _ => blit_bitmap_pixels(self, position, bitmap, width, height, color),
+        }
+    }
 }
 
 pub struct RectMask {
@@ -102,14 +206,58 @@ impl Mask for RectMask {
         (self.upper_left.0..=self.lower_right.0).contains(&position.0) &&
         (self.upper_left.1..=self.lower_right.1).contains(&position.1)
     }
+
+    fn as_rect(&self) -> Option<((isize, isize), (isize, isize))> {
+        Some((self.upper_left, self.lower_right))
+    }
+}
+
+// ordered dither, level out of 16, for grey on the one bit display
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DitherMask {
+    pub level: u8,
+}
+
+impl DitherMask {
+    // 4x4 bayer matrix, each pixel is let through once the level passes its threshold
+    const THRESHOLDS: [[u8; 4]; 4] = [
+        [0, 8, 2, 10],
+        [12, 4, 14, 6],
+        [3, 11, 1, 9],
+        [15, 7, 13, 5],
+    ];
+
+    pub const fn new(level: u8) -> Self {
+        Self { level }
+    }
+
+    // the nearest level to a brightness from 0 to 1
+    pub fn from_fraction(fraction: f32) -> Self {
+        Self { level: libm::roundf(fraction.clamp(0.0, 1.0) * 16.0) as u8 }
+    }
+}
+
+impl Mask for DitherMask {
+    fn get_mask(&self, position: (isize, isize)) -> bool {
+        Self::THRESHOLDS[position.1.rem_euclid(4) as usize][position.0.rem_euclid(4) as usize] < self.level
+    }
+
+    // all or nothing at the ends
+    fn as_rect(&self) -> Option<((isize, isize), (isize, isize))> {
+        match self.level {
+            0 => Some(((0, 0), (-1, -1))),
+            16.. => Some(((isize::MIN, isize::MIN), (isize::MAX, isize::MAX))),
+            _ => None,
+        }
+    }
 }
 
 pub trait _Maskable: DrawTarget + Sized {
-    fn mask<'a, M: Mask>(self, mask: M) -> MaskedDrawTarget<Self, M>;
+    fn mask<M: Mask>(self, mask: M) -> MaskedDrawTarget<Self, M>;
 }
 
 impl<T: Sized + DrawTarget> _Maskable for T {
-    fn mask<'a, M: Mask>(self, mask: M) -> MaskedDrawTarget<Self, M> {
+    fn mask<M: Mask>(self, mask: M) -> MaskedDrawTarget<Self, M> {
         MaskedDrawTarget { inner: self, mask }
     }
 }
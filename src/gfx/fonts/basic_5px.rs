@@ -857,15 +857,211 @@ const PUNCT_4: GlyphRange = GlyphRange {
     ]
 };
 
-pub const FONT: BitmapFont = BitmapFont {
-    line_height: 8,
-    ranges: &[
-        &PUNCT_1,
-        &NUMBERS,
-        &PUNCT_2,
-        &UPPER_CASE,
-        &PUNCT_3,
-        &LOWERCASE,
-        &PUNCT_4,
+bitmap_glyph!(DEGREE, r#"
+ *
+* *
+ *
+   
+   <,
+"#);
+
+bitmap_glyph!(PLUS_MINUS, r#"
+ *
+***
+ *
+
+***<,
+"#);
+
+const LATIN_1: GlyphRange = GlyphRange {
+    start_char: '°',
+    glyphs: &[
+        &DEGREE,
+        &PLUS_MINUS,
+    ]
+};
+
+bitmap_glyph!(MICRO, r#"
+* *
+* *
+* *
+***<,
+*
+"#);
+
+const LATIN_1_MICRO: GlyphRange = GlyphRange {
+    start_char: 'µ',
+    glyphs: &[
+        &MICRO,
+    ]
+};
+
+bitmap_glyph!(OMEGA, r#"
+ ***
+*   *
+*   *
+ * *
+** **<,
+"#);
+
+const GREEK: GlyphRange = GlyphRange {
+    start_char: 'Ω',
+    glyphs: &[
+        &OMEGA,
+    ]
+};
+
+bitmap_glyph!(ARROW_LEFT, r#"
+  *
+ *
+*****
+ *
+  *  <,
+"#);
+
+bitmap_glyph!(ARROW_UP, r#"
+  *
+ ***
+* * *
+  *
+  *  <,
+"#);
+
+bitmap_glyph!(ARROW_RIGHT, r#"
+  *
+   *
+*****
+   *
+  *  <,
+"#);
+
+bitmap_glyph!(ARROW_DOWN, r#"
+  *
+  *
+* * *
+ ***
+  *  <,
+"#);
+
+const ARROWS: GlyphRange = GlyphRange {
+    start_char: '←',
+    glyphs: &[
+        &ARROW_LEFT,
+        &ARROW_UP,
+        &ARROW_RIGHT,
+        &ARROW_DOWN,
     ]
 };
+
+// icons in the private use area, see fonts::icons. they stay within the height of the letters
+// so they can go anywhere text does
+
+bitmap_glyph!(ICON_WARNING, r#"
+   *
+  ***
+ ** **
+*******
+*** ***<,
+"#);
+
+bitmap_glyph!(ICON_LINK, r#"
+
+ **  **
+*  **  *
+ **  ** 
+        <,
+"#);
+
+bitmap_glyph!(ICON_NO_LINK, r#"
+
+ **    **
+*  *  *  *
+ **    **
+          <,
+"#);
+
+bitmap_glyph!(ICON_LIGHTNING, r#"
+  **
+ **
+****
+  **
+ ** <,
+"#);
+
+bitmap_glyph!(ICON_PLAY, r#"
+*
+**
+***
+**
+*  <,
+"#);
+
+bitmap_glyph!(ICON_STOP, r#"
+
+****
+****
+****
+****<,
+"#);
+
+bitmap_glyph!(ICON_PAUSE, r#"
+** **
+** **
+** **
+** **
+** **<,
+"#);
+
+bitmap_glyph!(ICON_CHECK, r#"
+    *
+   *
+* *
+ *
+     <,
+"#);
+
+bitmap_glyph!(ICON_CROSS, r#"
+* *
+ *
+* *
+   <,
+"#);
+
+const ICONS: GlyphRange = GlyphRange {
+    start_char: '\u{E000}',
+    glyphs: &[
+        &ICON_WARNING,
+        &ICON_LINK,
+        &ICON_NO_LINK,
+        &ICON_LIGHTNING,
+        &ICON_PLAY,
+        &ICON_STOP,
+        &ICON_PAUSE,
+        &ICON_CHECK,
+        &ICON_CROSS,
+    ]
+};
+
+// an empty box, so missing characters show up rather than vanishing
+bitmap_glyph!(MISSING, r#"
+****
+*  *
+*  *
+*  *
+****<,
+"#);
+
+pub const FONT: BitmapFont = BitmapFont::new(8, &[
+    &PUNCT_1,
+    &NUMBERS,
+    &PUNCT_2,
+    &UPPER_CASE,
+    &PUNCT_3,
+    &LOWERCASE,
+    &PUNCT_4,
+    &LATIN_1,
+    &LATIN_1_MICRO,
+    &GREEK,
+    &ARROWS,
+    &ICONS,
+], Some(&MISSING));
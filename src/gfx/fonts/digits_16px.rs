@@ -0,0 +1,310 @@
+use crate::gfx::bitmap_font::{BitmapFont, Glyph, GlyphRange};
+use proc_bitmap_font::bitmap_glyph;
+
+bitmap_glyph!(NUM_0, r#"
+ ********
+**********
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**********
+ ******** < ,
+"#);
+
+bitmap_glyph!(NUM_1, r#"
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **< ,
+"#);
+
+bitmap_glyph!(NUM_2, r#"
+*********
+**********
+        **
+        **
+        **
+        **
+        **
+**********
+**********
+**
+**
+**
+**
+**
+**********
+ *********< ,
+"#);
+
+bitmap_glyph!(NUM_3, r#"
+*********
+**********
+        **
+        **
+        **
+        **
+        **
+**********
+**********
+        **
+        **
+        **
+        **
+        **
+**********
+********* < ,
+"#);
+
+bitmap_glyph!(NUM_4, r#"
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**      **
+**********
+**********
+        **
+        **
+        **
+        **
+        **
+        **
+        **< ,
+"#);
+
+bitmap_glyph!(NUM_5, r#"
+ *********
+**********
+**
+**
+**
+**
+**
+**********
+**********
+        **
+        **
+        **
+        **
+        **
+**********
+********* < ,
+"#);
+
+bitmap_glyph!(NUM_6, r#"
+ *********
+**********
+**
+**
+**
+**
+**
+**********
+**********
+**      **
+**      **
+**      **
+**      **
+**      **
+**********
+ ******** < ,
+"#);
+
+bitmap_glyph!(NUM_7, r#"
+*********
+**********
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **
+        **< ,
+"#);
+
+bitmap_glyph!(NUM_8, r#"
+ ********
+**********
+**      **
+**      **
+**      **
+**      **
+**      **
+**********
+**********
+**      **
+**      **
+**      **
+**      **
+**      **
+**********
+ ******** < ,
+"#);
+
+bitmap_glyph!(NUM_9, r#"
+ ********
+**********
+**      **
+**      **
+**      **
+**      **
+**      **
+**********
+**********
+        **
+        **
+        **
+        **
+        **
+**********
+********* < ,
+"#);
+
+bitmap_glyph!(COLON, r#"
+**
+**
+
+
+
+
+
+
+**
+**
+
+
+  < ,
+"#);
+
+const NUMBERS: GlyphRange = GlyphRange {
+    start_char: '0',
+    glyphs: &[
+        &NUM_0,
+        &NUM_1,
+        &NUM_2,
+        &NUM_3,
+        &NUM_4,
+        &NUM_5,
+        &NUM_6,
+        &NUM_7,
+        &NUM_8,
+        &NUM_9,
+        &COLON,
+    ]
+};
+
+bitmap_glyph!(PLUS, r#"
+   **
+   **
+   **
+********
+********
+   **
+   **
+   **
+
+
+
+        < ,
+"#);
+
+bitmap_glyph!(COMMA, r#"
+**
+**
+ *< ,
+"#);
+
+bitmap_glyph!(MINUS, r#"
+********
+********
+
+
+
+
+
+
+        < ,
+"#);
+
+bitmap_glyph!(DOT, r#"
+**
+**< ,
+"#);
+
+bitmap_glyph!(SLASH, r#"
+      **
+      **
+     **
+     **
+    **
+    **
+   **
+   **
+  **
+  **
+ **
+ **
+**
+**
+*
+*       < ,
+"#);
+
+const PUNCT_2: GlyphRange = GlyphRange {
+    start_char: '+',
+    glyphs: &[
+        &PLUS,
+        &COMMA,
+        &MINUS,
+        &DOT,
+        &SLASH,
+    ]
+};
+
+bitmap_glyph!(SPACE, r#"
+<           ,
+"#);
+
+const PUNCT_1: GlyphRange = GlyphRange {
+    start_char: ' ',
+    glyphs: &[
+        &SPACE,
+    ]
+};
+
+pub const FONT: BitmapFont = BitmapFont::new(18, &[
+    &PUNCT_1,
+    &PUNCT_2,
+    &NUMBERS,
+], None);
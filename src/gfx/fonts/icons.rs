@@ -0,0 +1,13 @@
+// icons drawn by BASIC_5PX from its private use area range. they're strings so they can be used
+// as button labels as they are, or put into text with format!. button labels that need an icon
+// and a word have to spell the character out, as in "\u{E004} Run"
+
+pub const WARNING: &str = "\u{E000}";
+pub const LINK: &str = "\u{E001}";
+pub const NO_LINK: &str = "\u{E002}";
+pub const LIGHTNING: &str = "\u{E003}";
+pub const PLAY: &str = "\u{E004}";
+pub const STOP: &str = "\u{E005}";
+pub const PAUSE: &str = "\u{E006}";
+pub const CHECK: &str = "\u{E007}";
+pub const CROSS: &str = "\u{E008}";
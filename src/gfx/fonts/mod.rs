@@ -1,3 +1,22 @@
+use super::bitmap_font::BitmapFont;
+
 mod basic_5px;
+mod ui_7px;
+mod digits_16px;
+pub mod icons;
 
 pub use basic_5px::FONT as BASIC_5PX;
+pub use ui_7px::FONT as UI_7PX;
+pub use digits_16px::FONT as DIGITS_16PX;
+
+// smallest first
+pub const FONTS: [&BitmapFont; 3] = [&BASIC_5PX, &UI_7PX, &DIGITS_16PX];
+
+// the biggest font that can draw all of the text within the size, for putting a value on screen
+// as large as it'll go. falls back to BASIC_5PX when nothing fits
+pub fn largest_fitting(text: &str, max_size: (isize, isize)) -> &'static BitmapFont {
+    FONTS.iter().rev()
+        .copied()
+        .find(|font| font.has_glyphs(text) && font.get_text_width(text) <= max_size.0 && font.line_height as isize <= max_size.1)
+        .unwrap_or(&BASIC_5PX)
+}
@@ -0,0 +1,5 @@
+use crate::gfx::bitmap_font::{BitmapFont, Glyph, GlyphRange};
+use proc_bitmap_font::png_font;
+
+// printable ascii in 8x10 cells, baseline on the seventh row
+png_font!(FONT, "src/gfx/fonts/ui_7px.png", 8, 10, 6, ' ');
@@ -1,5 +1,7 @@
 pub mod draw_target;
 pub mod primitives;
 pub mod bitmap_font;
+pub mod bitmap;
+pub mod dirty;
 pub mod fonts;
 
@@ -1,27 +1,14 @@
+use alloc::vec::Vec;
+use libm::{atan2f, roundf, sqrtf};
+
 use super::draw_target::DrawTarget;
 
 pub fn draw_hline<Target: DrawTarget>(target: &mut Target, x_start: isize, x_end: isize, y: isize, color: bool) {
-    if x_start <= x_end {
-        for x in x_start..=x_end {
-            target.set_pixel((x, y), color);
-        }
-    } else {
-        for x in x_end..=x_start {
-            target.set_pixel((x, y), color);
-        }
-    }
+    target.fill_span(x_start.min(x_end), x_start.max(x_end), y, color);
 }
 
 pub fn draw_vline<Target: DrawTarget>(target: &mut Target, x: isize, y_start: isize, y_end: isize, color: bool) {
-    if y_start <= y_end {
-        for y in y_start..=y_end {
-            target.set_pixel((x, y), color);
-        }
-    } else {
-        for y in y_end..=y_start {
-            target.set_pixel((x, y), color);
-        }
-    }
+    target.fill_rect((x, y_start.min(y_end)), (x, y_start.max(y_end)), color);
 }
 
 pub fn draw_rect<Target: DrawTarget>(target: &mut Target, start: (isize, isize), end: (isize, isize), color: bool) {
@@ -32,15 +19,9 @@ pub fn draw_rect<Target: DrawTarget>(target: &mut Target, start: (isize, isize),
 }
 
 pub fn draw_filled_rect<Target: DrawTarget>(target: &mut Target, start: (isize, isize), end: (isize, isize), color: bool) {
-    let x_min = start.0.min(end.0);
-    let x_max = start.0.max(end.0);
-    let y_min = start.1.min(end.1);
-    let y_max = start.1.max(end.1);
-    for y in y_min..=y_max {
-        for x in x_min..=x_max {
-            target.set_pixel((x, y), color);
-        }
-    }
+    let upper_left = (start.0.min(end.0), start.1.min(end.1));
+    let lower_right = (start.0.max(end.0), start.1.max(end.1));
+    target.fill_rect(upper_left, lower_right, color);
 }
 
 pub fn draw_line<Target: DrawTarget>(target: &mut Target, start: (isize, isize), end: (isize, isize), color: bool) {
@@ -87,3 +68,165 @@ pub fn draw_line<Target: DrawTarget>(target: &mut Target, start: (isize, isize),
         }
     }
 }
+
+// one octant of a circle by the midpoint method
+fn octant_points(radius: isize, mut f: impl FnMut(isize, isize)) {
+    let mut x = radius;
+    let mut y = 0;
+    let mut error = 1 - radius;
+    while x >= y {
+        f(x, y);
+        y += 1;
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x) + 1;
+        }
+    }
+}
+
+pub fn draw_circle<Target: DrawTarget>(target: &mut Target, center: (isize, isize), radius: isize, color: bool) {
+    octant_points(radius, |x, y| {
+        for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+            target.set_pixel((center.0 + dx, center.1 + dy), color);
+        }
+    });
+}
+
+pub fn draw_filled_circle<Target: DrawTarget>(target: &mut Target, center: (isize, isize), radius: isize, color: bool) {
+    octant_points(radius, |x, y| {
+        draw_hline(target, center.0 - x, center.0 + x, center.1 + y, color);
+        draw_hline(target, center.0 - x, center.0 + x, center.1 - y, color);
+        draw_hline(target, center.0 - y, center.0 + y, center.1 + x, color);
+        draw_hline(target, center.0 - y, center.0 + y, center.1 - x, color);
+    });
+}
+
+// angles in degrees, clockwise from pointing right
+fn in_arc(offset: (isize, isize), start_degrees: f32, end_degrees: f32) -> bool {
+    let sweep = end_degrees - start_degrees;
+    if sweep >= 360.0 {
+        return true;
+    }
+    let angle = atan2f(offset.1 as f32, offset.0 as f32).to_degrees();
+    (angle - start_degrees).rem_euclid(360.0) <= sweep.rem_euclid(360.0)
+}
+
+// the part of the circle outline from start_degrees clockwise to end_degrees, see in_arc
+pub fn draw_arc<Target: DrawTarget>(target: &mut Target, center: (isize, isize), radius: isize, start_degrees: f32, end_degrees: f32, color: bool) {
+    octant_points(radius, |x, y| {
+        for offset in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+            if in_arc(offset, start_degrees, end_degrees) {
+                target.set_pixel((center.0 + offset.0, center.1 + offset.1), color);
+            }
+        }
+    });
+}
+
+// an arc width pixels thick, from radius inwards
+pub fn draw_thick_arc<Target: DrawTarget>(target: &mut Target, center: (isize, isize), radius: isize, width: isize, start_degrees: f32, end_degrees: f32, color: bool) {
+    // a pixel is in the band when its center is within half a pixel of the circles
+    let outer = (2 * radius + 1) * (2 * radius + 1);
+    let inner = (2 * (radius - width) + 1).max(0).pow(2);
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let distance = 4 * (dx * dx + dy * dy);
+            if distance <= outer && (width > radius || distance > inner) && in_arc((dx, dy), start_degrees, end_degrees) {
+                target.set_pixel((center.0 + dx, center.1 + dy), color);
+            }
+        }
+    }
+}
+
+// the radius is limited to what fits in the rect
+fn corner_radius(start: (isize, isize), end: (isize, isize), radius: isize) -> isize {
+    let width = (end.0 - start.0).abs();
+    let height = (end.1 - start.1).abs();
+    radius.min(width / 2).min(height / 2).max(0)
+}
+
+pub fn draw_rounded_rect<Target: DrawTarget>(target: &mut Target, start: (isize, isize), end: (isize, isize), radius: isize, color: bool) {
+    let (left, right) = (start.0.min(end.0), start.0.max(end.0));
+    let (top, bottom) = (start.1.min(end.1), start.1.max(end.1));
+    let radius = corner_radius(start, end, radius);
+    draw_hline(target, left + radius, right - radius, top, color);
+    draw_hline(target, left + radius, right - radius, bottom, color);
+    draw_vline(target, left, top + radius, bottom - radius, color);
+    draw_vline(target, right, top + radius, bottom - radius, color);
+    octant_points(radius, |x, y| {
+        for (dx, dy) in [(x, y), (y, x)] {
+            target.set_pixel((left + radius - dx, top + radius - dy), color);
+            target.set_pixel((right - radius + dx, top + radius - dy), color);
+            target.set_pixel((left + radius - dx, bottom - radius + dy), color);
+            target.set_pixel((right - radius + dx, bottom - radius + dy), color);
+        }
+    });
+}
+
+pub fn draw_filled_rounded_rect<Target: DrawTarget>(target: &mut Target, start: (isize, isize), end: (isize, isize), radius: isize, color: bool) {
+    let (left, right) = (start.0.min(end.0), start.0.max(end.0));
+    let (top, bottom) = (start.1.min(end.1), start.1.max(end.1));
+    let radius = corner_radius(start, end, radius);
+    draw_filled_rect(target, (left, top + radius), (right, bottom - radius), color);
+    octant_points(radius, |x, y| {
+        for (dx, dy) in [(x, y), (y, x)] {
+            draw_hline(target, left + radius - dx, right - radius + dx, top + radius - dy, color);
+            draw_hline(target, left + radius - dx, right - radius + dx, bottom - radius + dy, color);
+        }
+    });
+}
+
+// the outline through the points, closed back to the first
+pub fn draw_polygon<Target: DrawTarget>(target: &mut Target, points: &[(isize, isize)], color: bool) {
+    for (index, point) in points.iter().enumerate() {
+        draw_line(target, *point, points[(index + 1) % points.len()], color);
+    }
+}
+
+// even-odd fill plus outline, so it covers what draw_polygon does
+pub fn draw_filled_polygon<Target: DrawTarget>(target: &mut Target, points: &[(isize, isize)], color: bool) {
+    if points.is_empty() {
+        return;
+    }
+    let top = points.iter().map(|point| point.1).min().unwrap_or(0);
+    let bottom = points.iter().map(|point| point.1).max().unwrap_or(0);
+    let mut crossings = Vec::new();
+    for y in top..bottom {
+        crossings.clear();
+        for (index, a) in points.iter().enumerate() {
+            let b = points[(index + 1) % points.len()];
+            // count edges at their start row only, so shared vertices count once
+            if (a.1 <= y && y < b.1) || (b.1 <= y && y < a.1) {
+                // rounded the way draw_line rounds the edge
+                crossings.push(a.0 + ((y - a.1) * (b.0 - a.0)) / (b.1 - a.1));
+            }
+        }
+        crossings.sort_unstable();
+        for span in crossings.chunks_exact(2) {
+            draw_hline(target, span[0], span[1], y, color);
+        }
+    }
+    draw_polygon(target, points, color);
+}
+
+pub fn draw_filled_triangle<Target: DrawTarget>(target: &mut Target, a: (isize, isize), b: (isize, isize), c: (isize, isize), color: bool) {
+    draw_filled_polygon(target, &[a, b, c], color);
+}
+
+// a line width pixels across with square ends
+pub fn draw_thick_line<Target: DrawTarget>(target: &mut Target, start: (isize, isize), end: (isize, isize), width: isize, color: bool) {
+    let (dx, dy) = ((end.0 - start.0) as f32, (end.1 - start.1) as f32);
+    let length = sqrtf(dx * dx + dy * dy);
+    if width <= 1 || length == 0.0 {
+        draw_line(target, start, end, color);
+        return;
+    }
+    // half the width along the perpendicular, less the pixel the line itself is drawn on
+    let half = (width - 1) as f32 / 2.0;
+    let normal = (-dy / length * half, dx / length * half);
+    let corner = |point: (isize, isize), side: f32| {
+        (point.0 + roundf(normal.0 * side) as isize, point.1 + roundf(normal.1 * side) as isize)
+    };
+    draw_filled_polygon(target, &[corner(start, 1.0), corner(end, 1.0), corner(end, -1.0), corner(start, -1.0)], color);
+}
@@ -3,7 +3,7 @@ use syn::Result;
 use quote::{format_ident, quote, ToTokens, TokenStreamExt};
 
 #[derive(Debug)]
-struct GlyphDef {
+pub struct GlyphDef {
     pub bit_vec: Vec<u8>,
     pub width: usize,
     pub height: usize,
@@ -11,6 +11,34 @@ struct GlyphDef {
     pub baseline: isize,
 }
 
+impl GlyphDef {
+    // pixels row by row, top first, packed the way Glyph::draw reads them
+    pub fn from_pixels(pixels: &[bool], width: usize, height: usize, baseline: isize, advance: usize) -> Self {
+        let n_bytes = (pixels.len() / 8) + if (pixels.len() % 8) != 0 { 1 } else { 0 };
+        let mut byte_vec = Vec::new();
+        for i in 0..n_bytes {
+            let base_bit = i * 8;
+            let byte = 
+                get_bit(pixels, base_bit, 0) |
+                get_bit(pixels, base_bit, 1) |
+                get_bit(pixels, base_bit, 2) |
+                get_bit(pixels, base_bit, 3) |
+                get_bit(pixels, base_bit, 4) |
+                get_bit(pixels, base_bit, 5) |
+                get_bit(pixels, base_bit, 6) |
+                get_bit(pixels, base_bit, 7);
+            byte_vec.push(byte);
+        }
+        GlyphDef {
+            bit_vec: byte_vec,
+            width,
+            height,
+            baseline,
+            advance
+        }
+    }
+}
+
 fn parse_bitmap_glyph(glyph_def: &str) -> GlyphDef {
     let mut first_line_found = false;
     let mut height = 0;
@@ -26,7 +54,7 @@ fn parse_bitmap_glyph(glyph_def: &str) -> GlyphDef {
         if first_line_found {
             if let Some(line_width) = line.rfind("*").map(|x| x + 1) {
                 width = width.max(line_width);
-                height = i;
+                height = i - start_line + 1;
             }
         }
     }
@@ -64,31 +92,10 @@ fn parse_bitmap_glyph(glyph_def: &str) -> GlyphDef {
             parse_line += 1;
         }
     }
-    let n_bytes = (bit_vec.len() / 8) + if (bit_vec.len() % 8) != 0 { 1 } else { 0 };
-    let mut byte_vec = Vec::new();
-    for i in 0..n_bytes {
-        let base_bit = i * 8;
-        let byte = 
-            get_bit(&bit_vec, base_bit, 0) |
-            get_bit(&bit_vec, base_bit, 1) |
-            get_bit(&bit_vec, base_bit, 2) |
-            get_bit(&bit_vec, base_bit, 3) |
-            get_bit(&bit_vec, base_bit, 4) |
-            get_bit(&bit_vec, base_bit, 5) |
-            get_bit(&bit_vec, base_bit, 6) |
-            get_bit(&bit_vec, base_bit, 7);
-        byte_vec.push(byte);
-    }
-    GlyphDef {
-        bit_vec: byte_vec,
-        width,
-        height,
-        baseline,
-        advance
-    }
+    GlyphDef::from_pixels(&bit_vec, width, height, baseline, advance)
 }
 
-fn get_bit(bit_vec: &Vec<bool>, base_bit: usize, bit_n: usize) -> u8 {
+fn get_bit(bit_vec: &[bool], base_bit: usize, bit_n: usize) -> u8 {
     let bit = base_bit + bit_n;
     if bit < bit_vec.len() {
         if bit_vec[bit] {
@@ -126,6 +133,10 @@ pub fn bitmap_glyph_impl(tokens: TokenStream) -> Result<TokenStream> {
         Some(other) => return Err(syn::Error::new(other.span(), "Expected a string depicting the glyph after comma")),
         None => return Err(syn::Error::new(Span::call_site(), "Expected a string depicting the glyph after comma")),
     };
+    Ok(glyph_tokens(&glyph_name, &glyph_def))
+}
+
+pub fn glyph_tokens(glyph_name: &proc_macro2::Ident, glyph_def: &GlyphDef) -> TokenStream {
     let glyph_bitmap_name = format_ident!("{}_BITMAP", glyph_name);
     let mut glyph_def_byte_tokens: TokenStream = TokenStream::new();
     for byte in glyph_def.bit_vec.iter() {
@@ -152,19 +163,17 @@ pub fn bitmap_glyph_impl(tokens: TokenStream) -> Result<TokenStream> {
     let glyph_advance = proc_macro2::TokenTree::Literal(
         proc_macro2::Literal::usize_unsuffixed(glyph_def.advance)
     );
-    Ok(
-        quote! {
-            const #glyph_bitmap_name: &'static [u8] = &[
-                #glyph_def_byte_tokens
-            ];
+    quote! {
+        const #glyph_bitmap_name: &'static [u8] = &[
+            #glyph_def_byte_tokens
+        ];
 
-            const #glyph_name: Glyph = Glyph {
-                width: #glyph_width,
-                height: #glyph_height,
-                bitmap: #glyph_bitmap_name,
-                baseline: #glyph_baseline,
-                advance: #glyph_advance
-            };
-        }
-    )
-}
\ No newline at end of file
+        const #glyph_name: Glyph = Glyph {
+            width: #glyph_width,
+            height: #glyph_height,
+            bitmap: #glyph_bitmap_name,
+            baseline: #glyph_baseline,
+            advance: #glyph_advance
+        };
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Result, Token};
+
+use crate::bitmap_font::GlyphDef;
+use crate::font_file::{png_ink, read_file};
+
+// bitmap!(NAME, r#"..."#) and png_bitmap!(NAME, "path/to/image.png")
+pub struct BitmapInput {
+    name: Ident,
+    source: LitStr,
+}
+
+impl Parse for BitmapInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let source = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { name, source })
+    }
+}
+
+// drawn with * for set pixels like bitmap_glyph!. blank lines above and below the picture are
+// left out and it's as wide as its longest line, so mark the corners if the blank edges matter
+fn parse_picture(picture: &str) -> (usize, usize, Vec<bool>) {
+    let lines: Vec<&str> = picture.lines().collect();
+    let first = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(0);
+    let last = lines.iter().rposition(|line| !line.trim().is_empty()).map(|last| last + 1).unwrap_or(0);
+    let lines = &lines[first..last.max(first)];
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let pixels = lines.iter()
+        .flat_map(|line| {
+            let mut chars = line.chars();
+            (0..width).map(move |_| chars.next() == Some('*'))
+        })
+        .collect();
+    (width, lines.len(), pixels)
+}
+
+fn bitmap_tokens(name: &Ident, width: usize, height: usize, pixels: &[bool], path: Option<&Path>) -> TokenStream {
+    let bytes = GlyphDef::from_pixels(pixels, width, height, 0, 0).bit_vec;
+    // rebuilds when the image changes
+    let dependency = path.map(|path| {
+        let path = path.to_string_lossy().into_owned();
+        quote! { const _: &[u8] = include_bytes!(#path); }
+    });
+    quote! {
+        #dependency
+
+        pub const #name: Bitmap<&'static [u8]> = Bitmap::from_static(#width, #height, &[#(#bytes),*]);
+    }
+}
+
+pub fn bitmap_impl(input: BitmapInput) -> Result<TokenStream> {
+    let (width, height, pixels) = parse_picture(&input.source.value());
+    Ok(bitmap_tokens(&input.name, width, height, &pixels, None))
+}
+
+pub fn png_bitmap_impl(input: BitmapInput) -> Result<TokenStream> {
+    let (full_path, bytes) = read_file(&input.source)?;
+    let (width, height, pixels) = png_ink(&bytes)
+        .map_err(|e| syn::Error::new(input.source.span(), format!("Couldn't read the image {}: {}", full_path.display(), e)))?;
+    Ok(bitmap_tokens(&input.name, width, height, &pixels, Some(&full_path)))
+}
@@ -0,0 +1,301 @@
+use std::path::{Path, PathBuf};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitChar, LitInt, LitStr, Result, Token};
+
+use crate::bitmap_font::{glyph_tokens, GlyphDef};
+
+// bdf_font!(NAME, "path/to/font.bdf") or bdf_font!(NAME, "path/to/font.bdf", ' '..='~')
+pub struct BdfFontInput {
+    name: Ident,
+    path: LitStr,
+    chars: (char, char),
+}
+
+impl Parse for BdfFontInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let path = input.parse()?;
+        let mut chars = (' ', '~');
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let first: LitChar = input.parse()?;
+            input.parse::<Token![..=]>()?;
+            let last: LitChar = input.parse()?;
+            chars = (first.value(), last.value());
+        }
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { name, path, chars })
+    }
+}
+
+// png_font!(NAME, "path/to/sheet.png", cell_width, cell_height, baseline_row, first_char)
+pub struct PngFontInput {
+    name: Ident,
+    path: LitStr,
+    cell_width: usize,
+    cell_height: usize,
+    baseline: usize,
+    first_char: char,
+}
+
+impl Parse for PngFontInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let path = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let cell_width = input.parse::<LitInt>()?.base10_parse()?;
+        input.parse::<Token![,]>()?;
+        let cell_height = input.parse::<LitInt>()?.base10_parse()?;
+        input.parse::<Token![,]>()?;
+        let baseline = input.parse::<LitInt>()?.base10_parse()?;
+        input.parse::<Token![,]>()?;
+        let first_char = input.parse::<LitChar>()?.value();
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self { name, path, cell_width, cell_height, baseline, first_char })
+    }
+}
+
+// paths are relative to the Cargo.toml of the crate using the macro, like include_bytes! from the
+// crate root would be. host tools under tools/ that include the firmware's fonts point
+// BITMAP_FONT_ROOT at the firmware's directory in their .cargo/config.toml instead
+fn resolve_path(path: &LitStr) -> PathBuf {
+    let root = std::env::var("BITMAP_FONT_ROOT")
+        .or_else(|_| std::env::var("CARGO_MANIFEST_DIR"))
+        .unwrap_or_default();
+    PathBuf::from(root).join(path.value())
+}
+
+pub fn read_file(path: &LitStr) -> Result<(PathBuf, Vec<u8>)> {
+    let full_path = resolve_path(path);
+    std::fs::read(&full_path)
+        .map(|bytes| (full_path.clone(), bytes))
+        .map_err(|e| syn::Error::new(path.span(), format!("Couldn't read {}: {}", full_path.display(), e)))
+}
+
+pub fn bdf_font_impl(input: BdfFontInput) -> Result<TokenStream> {
+    let (full_path, bytes) = read_file(&input.path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let (line_height, glyphs) = parse_bdf(&text)
+        .map_err(|e| syn::Error::new(input.path.span(), format!("Couldn't parse {}: {}", full_path.display(), e)))?;
+    let glyphs = glyphs.into_iter()
+        .filter(|(c, _)| input.chars.0 <= *c && *c <= input.chars.1)
+        .collect();
+    Ok(font_tokens(&input.name, &full_path, line_height, glyphs))
+}
+
+pub fn png_font_impl(input: PngFontInput) -> Result<TokenStream> {
+    let (full_path, bytes) = read_file(&input.path)?;
+    if input.cell_width == 0 || input.cell_height == 0 || input.baseline >= input.cell_height {
+        return Err(syn::Error::new(Span::call_site(), "Cells need a size, with the baseline row inside them"));
+    }
+    let glyphs = parse_png_sheet(&bytes, &input)
+        .map_err(|e| syn::Error::new(input.path.span(), format!("Couldn't read the glyph sheet {}: {}", full_path.display(), e)))?;
+    Ok(font_tokens(&input.name, &full_path, input.cell_height, glyphs))
+}
+
+struct BdfGlyph {
+    encoding: i64,
+    advance: usize,
+    // width, height, x offset, y offset of the bottom row from the baseline
+    bbx: (usize, usize, isize, isize),
+    rows: Vec<Vec<bool>>,
+}
+
+impl BdfGlyph {
+    fn glyph_def(&self) -> GlyphDef {
+        let (width, height, x_offset, y_offset) = self.bbx;
+        if width == 0 || height == 0 {
+            return GlyphDef::from_pixels(&[], 0, 0, 0, self.advance);
+        }
+        // Glyph has no x offset, so glyphs that start right of the origin get blank columns and
+        // ones that start left of it lose what's over the edge
+        let skip = (-x_offset).max(0) as usize;
+        let pad = x_offset.max(0) as usize;
+        let out_width = (pad + width).saturating_sub(skip);
+        let mut pixels = Vec::with_capacity(out_width * height);
+        for row in self.rows.iter().take(height) {
+            let padded = std::iter::repeat_n(false, pad).chain(row.iter().copied().chain(std::iter::repeat(false)).take(width));
+            pixels.extend(padded.skip(skip).take(out_width));
+        }
+        pixels.resize(out_width * height, false);
+        GlyphDef::from_pixels(&pixels, out_width, height, y_offset + height as isize - 1, self.advance)
+    }
+}
+
+fn parse_bdf(text: &str) -> std::result::Result<(usize, Vec<(char, GlyphDef)>), String> {
+    let mut bounding_height = None;
+    let mut ascent = None;
+    let mut descent = None;
+    let mut glyphs = Vec::new();
+    let mut current: Option<BdfGlyph> = None;
+    let mut in_bitmap = false;
+
+    for (line_number, line) in text.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let numbers: Vec<i64> = words.filter_map(|word| word.parse().ok()).collect();
+        let number = |i: usize| numbers.get(i).copied().ok_or_else(|| format!("line {}: {} is missing a value", line_number + 1, keyword));
+
+        if in_bitmap && keyword != "ENDCHAR" {
+            let glyph = current.as_mut().ok_or("BITMAP outside of a character")?;
+            let bits = (0..keyword.len() / 2)
+                .map(|i| u8::from_str_radix(&keyword[i * 2..i * 2 + 2], 16))
+                .collect::<std::result::Result<Vec<u8>, _>>()
+                .map_err(|_| format!("line {}: bad bitmap row {}", line_number + 1, keyword))?;
+            glyph.rows.push((0..glyph.bbx.0).map(|x| bits.get(x / 8).map(|byte| byte & (0x80 >> (x % 8)) != 0).unwrap_or(false)).collect());
+            continue;
+        }
+        match keyword {
+            "FONTBOUNDINGBOX" => bounding_height = Some(number(1)? as usize),
+            "FONT_ASCENT" => ascent = Some(number(0)? as usize),
+            "FONT_DESCENT" => descent = Some(number(0)? as usize),
+            "STARTCHAR" => current = Some(BdfGlyph { encoding: -1, advance: 0, bbx: (0, 0, 0, 0), rows: Vec::new() }),
+            "ENCODING" => if let Some(glyph) = &mut current { glyph.encoding = number(0)? },
+            "DWIDTH" => if let Some(glyph) = &mut current { glyph.advance = number(0)?.max(0) as usize },
+            "BBX" => if let Some(glyph) = &mut current {
+                glyph.bbx = (number(0)?.max(0) as usize, number(1)?.max(0) as usize, number(2)? as isize, number(3)? as isize);
+            },
+            "BITMAP" => in_bitmap = true,
+            "ENDCHAR" => {
+                in_bitmap = false;
+                if let Some(glyph) = current.take() {
+                    // glyphs without a unicode encoding are left out
+                    if let Some(c) = u32::try_from(glyph.encoding).ok().and_then(char::from_u32) {
+                        glyphs.push((c, glyph.glyph_def()));
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    let line_height = match (ascent, descent, bounding_height) {
+        (Some(ascent), Some(descent), _) => ascent + descent,
+        (_, _, Some(height)) => height,
+        _ => return Err(String::from("no FONTBOUNDINGBOX")),
+    };
+    Ok((line_height, glyphs))
+}
+
+// an image as a grid of ink, row by row. a pixel is ink when it's far from the color of the
+// image's top left pixel, so light on dark and dark on light images both work
+pub fn png_ink(bytes: &[u8]) -> std::result::Result<(usize, usize, Vec<bool>), String> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let samples = info.color_type.samples();
+    let (width, height) = (info.width as usize, info.height as usize);
+
+    // luma and alpha
+    let pixel = |x: usize, y: usize| -> (i32, i32) {
+        let p = &buffer[y * info.line_size + x * samples..];
+        match info.color_type {
+            png::ColorType::Grayscale => (p[0] as i32, 255),
+            png::ColorType::GrayscaleAlpha => (p[0] as i32, p[1] as i32),
+            png::ColorType::Rgb => ((p[0] as i32 * 3 + p[1] as i32 * 6 + p[2] as i32) / 10, 255),
+            _ => ((p[0] as i32 * 3 + p[1] as i32 * 6 + p[2] as i32) / 10, p[3] as i32),
+        }
+    };
+    let background = pixel(0, 0);
+    let ink = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let (luma, alpha) = pixel(x, y);
+            (luma - background.0).abs() > 127 || (alpha - background.1).abs() > 127
+        })
+        .collect();
+    Ok((width, height, ink))
+}
+
+// the sheet is a grid of cells read left to right, top to bottom, starting at first_char, inked
+// as png_ink sees it. glyphs sit at the left of their cell and are as wide as their ink, blank
+// cells become spaces half a cell wide
+fn parse_png_sheet(bytes: &[u8], input: &PngFontInput) -> std::result::Result<Vec<(char, GlyphDef)>, String> {
+    let (image_width, image_height, sheet) = png_ink(bytes)?;
+    let ink = |x: usize, y: usize| sheet[y * image_width + x];
+
+    let columns = image_width / input.cell_width;
+    let rows = image_height / input.cell_height;
+    let mut glyphs = Vec::new();
+    for cell in 0..columns * rows {
+        let c = match char::from_u32(input.first_char as u32 + cell as u32) {
+            Some(c) => c,
+            None => continue,
+        };
+        let origin = ((cell % columns) * input.cell_width, (cell / columns) * input.cell_height);
+        let inked: Vec<(usize, usize)> = (0..input.cell_height)
+            .flat_map(|y| (0..input.cell_width).map(move |x| (x, y)))
+            .filter(|(x, y)| ink(origin.0 + x, origin.1 + y))
+            .collect();
+        if inked.is_empty() {
+            glyphs.push((c, GlyphDef::from_pixels(&[], 0, 0, 0, input.cell_width / 2)));
+            continue;
+        }
+        let width = inked.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+        let top = inked.iter().map(|(_, y)| *y).min().unwrap_or(0);
+        let bottom = inked.iter().map(|(_, y)| *y).max().unwrap_or(0);
+        let height = bottom - top + 1;
+        let pixels: Vec<bool> = (top..=bottom)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| ink(origin.0 + x, origin.1 + y))
+            .collect();
+        glyphs.push((c, GlyphDef::from_pixels(&pixels, width, height, input.baseline as isize - top as isize, width + 1)));
+    }
+    // empty cells filling out the last row of the sheet aren't characters
+    while glyphs.last().map(|(_, glyph)| glyph.width == 0).unwrap_or(false) && glyphs.len() > 1 {
+        glyphs.pop();
+    }
+    Ok(glyphs)
+}
+
+// the glyphs, GlyphRanges of consecutive characters, and the BitmapFont itself
+fn font_tokens(name: &Ident, path: &Path, line_height: usize, mut glyphs: Vec<(char, GlyphDef)>) -> TokenStream {
+    glyphs.sort_by_key(|(c, _)| *c);
+    glyphs.dedup_by_key(|(c, _)| *c);
+
+    let mut tokens = TokenStream::new();
+    let mut ranges: Vec<(char, Vec<Ident>)> = Vec::new();
+    for (c, glyph) in glyphs.iter() {
+        let glyph_name = format_ident!("{}_U{:04X}", name, *c as u32);
+        tokens.extend(glyph_tokens(&glyph_name, glyph));
+        match ranges.last_mut() {
+            Some((start, names)) if *start as u32 + names.len() as u32 == *c as u32 => names.push(glyph_name),
+            _ => ranges.push((*c, vec![glyph_name])),
+        }
+    }
+    let range_names: Vec<Ident> = (0..ranges.len()).map(|i| format_ident!("{}_RANGE_{}", name, i)).collect();
+    for ((start, names), range_name) in ranges.iter().zip(range_names.iter()) {
+        tokens.extend(quote! {
+            const #range_name: GlyphRange = GlyphRange {
+                start_char: #start,
+                glyphs: &[#(&#names),*],
+            };
+        });
+    }
+    // missing characters are drawn as an empty box as tall as the tallest glyph is above the
+    // baseline
+    let box_height = glyphs.iter().map(|(_, glyph)| glyph.baseline + 1).max().unwrap_or(1).max(3) as usize;
+    let box_width = (box_height / 2 + 1).max(3);
+    let box_pixels: Vec<bool> = (0..box_height)
+        .flat_map(|y| (0..box_width).map(move |x| x == 0 || y == 0 || x == box_width - 1 || y == box_height - 1))
+        .collect();
+    let fallback_name = format_ident!("{}_FALLBACK", name);
+    tokens.extend(glyph_tokens(&fallback_name, &GlyphDef::from_pixels(&box_pixels, box_width, box_height, box_height as isize - 1, box_width + 1)));
+
+    // rebuilds when the font file changes
+    let path = path.to_string_lossy().into_owned();
+    tokens.extend(quote! {
+        const _: &[u8] = include_bytes!(#path);
+
+        pub const #name: BitmapFont = BitmapFont::new(#line_height, &[#(&#range_names),*], Some(&#fallback_name));
+    });
+    tokens
+}
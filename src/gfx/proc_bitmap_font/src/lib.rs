@@ -2,6 +2,8 @@ use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
 mod bitmap_font;
+mod font_file;
+mod bitmap_image;
 
 #[proc_macro]
 pub fn bitmap_glyph(input: TokenStream) -> TokenStream {
@@ -10,3 +12,50 @@ pub fn bitmap_glyph(input: TokenStream) -> TokenStream {
         .unwrap_or_else(|e| e.to_compile_error())
         .into()
 }
+
+// defines a BitmapFont from a BDF font file, keeping the characters in the range, printable ascii
+// if there isn't one. paths are relative to the crate's Cargo.toml
+//     bdf_font!(FONT, "src/gfx/fonts/name.bdf", ' '..='~');
+#[proc_macro]
+pub fn bdf_font(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as font_file::BdfFontInput);
+    font_file::bdf_font_impl(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+// defines a BitmapFont from a png sheet of equal sized glyph cells, given the cell size, the row
+// of the cell the baseline is on and the character in the first cell
+//     png_font!(FONT, "src/gfx/fonts/name.png", 8, 10, 6, ' ');
+#[proc_macro]
+pub fn png_font(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as font_file::PngFontInput);
+    font_file::png_font_impl(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+// defines a static Bitmap from a picture drawn with * for set pixels, for icons
+//     bitmap!(ICON, r#"
+//      ***
+//     *   *
+//      ***
+//     "#);
+#[proc_macro]
+pub fn bitmap(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as bitmap_image::BitmapInput);
+    bitmap_image::bitmap_impl(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+// defines a static Bitmap from a png, for logos. ink is whatever differs from the top left
+// pixel, as with png_font!
+//     png_bitmap!(LOGO, "src/gfx/images/logo.png");
+#[proc_macro]
+pub fn png_bitmap(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as bitmap_image::BitmapInput);
+    bitmap_image::png_bitmap_impl(input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -0,0 +1,300 @@
+use qcw_com::{ControllerMessage, ParameterValue, PowerEnvelope, RemoteMessage};
+
+use crate::envelope::{message_mean_power, scale_message};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum LimitMode {
+    // out of range parameters are pulled back to the nearest allowed value
+    Clamp,
+    // out of range parameters are not sent at all
+    Refuse,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LimiterConfig {
+    pub mode: LimitMode,
+    // on time / burst period
+    pub max_duty: f32,
+    // duty cycle * power, as a fraction of what the bridge delivers firing continuously at full power
+    pub max_average_power: f32,
+    // heat the coil may build up, in seconds of firing at full power and 100% duty
+    pub thermal_capacity: f32,
+    // average power the coil sheds continuously
+    pub cooling_rate: f32,
+}
+
+impl Default for LimiterConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LimiterConfig {
+    pub const fn new() -> Self {
+        Self {
+            mode: LimitMode::Clamp,
+            max_duty: 0.05,
+            max_average_power: 0.03,
+            thermal_capacity: 0.5,
+            cooling_rate: 0.01,
+        }
+    }
+}
+
+// firing has to wait for this much of the thermal budget to come back once it is used up
+pub const RESUME_BUDGET: f32 = 0.2;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FiringParameters {
+    pub on_time_us: u32,
+    pub off_time_ms: u32,
+    pub power: f32,
+}
+
+impl FiringParameters {
+    pub fn duty_cycle(&self) -> f32 {
+        duty_cycle(self.on_time_us, self.off_time_ms)
+    }
+
+    pub fn average_power(&self) -> f32 {
+        self.duty_cycle() * self.power
+    }
+}
+
+pub fn duty_cycle(on_time_us: u32, off_time_ms: u32) -> f32 {
+    let period_us = on_time_us as f32 + off_time_ms as f32 * 1000.0;
+    if period_us <= 0.0 {
+        0.0
+    } else {
+        on_time_us as f32 / period_us
+    }
+}
+
+// the effective duty limit for a given power, combining the duty and average power limits
+fn duty_limit(config: &LimiterConfig, power: f32) -> f32 {
+    if power > 0.0 {
+        config.max_duty.min(config.max_average_power / power)
+    } else {
+        config.max_duty
+    }
+}
+
+pub fn max_on_time_us(config: &LimiterConfig, off_time_ms: u32, power: f32) -> u32 {
+    let duty = duty_limit(config, power);
+    if duty >= 1.0 {
+        return u32::MAX;
+    }
+    (duty * off_time_ms as f32 * 1000.0 / (1.0 - duty)) as u32
+}
+
+pub fn min_off_time_ms(config: &LimiterConfig, on_time_us: u32, power: f32) -> u32 {
+    let duty = duty_limit(config, power);
+    if duty <= 0.0 {
+        return u32::MAX;
+    }
+    libm::ceilf(on_time_us as f32 * (1.0 - duty) / (duty * 1000.0)) as u32
+}
+
+pub fn max_power(config: &LimiterConfig, on_time_us: u32, off_time_ms: u32) -> f32 {
+    let duty = duty_cycle(on_time_us, off_time_ms);
+    if duty > config.max_duty {
+        0.0
+    } else if duty > 0.0 {
+        (config.max_average_power / duty).min(1.0)
+    } else {
+        1.0
+    }
+}
+
+pub enum Verdict {
+    Allow(ControllerMessage),
+    Clamped(ControllerMessage),
+    // the message may go once the correction has been sent ahead of it
+    Corrected {
+        correction: ControllerMessage,
+        message: ControllerMessage,
+    },
+    Refused(&'static str),
+}
+
+// keeps track of the burst parameters the controller is using and stops any outgoing
+// message from pushing them past the configured limits. parameters are only checked as a
+// set, when firing starts or while it goes on, so the order a view sends them in doesn't matter
+pub struct Limiter {
+    pub config: LimiterConfig,
+    // None until sent or reported by the controller
+    on_time_us: Option<u32>,
+    off_time_ms: Option<u32>,
+    // the flat power or the envelope's mean power, whichever was sent last
+    power: Option<f32>,
+    running: bool,
+    heat: f32,
+    overheated: bool,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Limiter {
+    pub const fn new() -> Self {
+        Self {
+            config: LimiterConfig::new(),
+            on_time_us: None,
+            off_time_ms: None,
+            power: None,
+            running: false,
+            heat: 0.0,
+            overheated: false,
+        }
+    }
+
+    // what is known of the parameters, with anything not known yet as 0
+    pub fn parameters(&self) -> FiringParameters {
+        FiringParameters {
+            on_time_us: self.on_time_us.unwrap_or(0),
+            off_time_ms: self.off_time_ms.unwrap_or(0),
+            power: self.power.unwrap_or(0.0),
+        }
+    }
+
+    // the parameters the controller would fire with, once all of them are known
+    pub fn complete_parameters(&self) -> Option<FiringParameters> {
+        Some(FiringParameters {
+            on_time_us: self.on_time_us?,
+            off_time_ms: self.off_time_ms?,
+            power: self.power?,
+        })
+    }
+
+    pub fn running(&self) -> bool {
+        self.running
+    }
+
+    // fraction of the thermal budget left, from 0 to 1
+    pub fn remaining_budget(&self) -> f32 {
+        (1.0 - self.heat / self.config.thermal_capacity).clamp(0.0, 1.0)
+    }
+
+    pub fn overheated(&self) -> bool {
+        self.overheated
+    }
+
+    // advances the thermal model. returns true when the coil has to be stopped
+    pub fn update(&mut self, dt_micros: u64) -> bool {
+        let dt = dt_micros as f32 / 1_000_000.0;
+        let heating = if self.running { self.parameters().average_power() } else { 0.0 };
+        self.heat = (self.heat + (heating - self.config.cooling_rate) * dt).max(0.0);
+        if self.remaining_budget() <= 0.0 {
+            self.overheated = true;
+        } else if self.remaining_budget() >= RESUME_BUDGET {
+            self.overheated = false;
+        }
+        self.overheated && self.running
+    }
+
+    // learns the parameters the controller reports back
+    pub fn observe_received(&mut self, message: &RemoteMessage) {
+        if let RemoteMessage::GetParamResult(value) = message {
+            self.apply(value);
+        }
+    }
+
+    pub fn check(&mut self, message: ControllerMessage) -> Verdict {
+        let config = self.config;
+        // changing a parameter is only limited while it changes what is being fired
+        let live = if self.running { self.complete_parameters() } else { None };
+        let verdict = match (&message, live) {
+            (ControllerMessage::Run, _) => self.check_run(message),
+            (ControllerMessage::SetParam(ParameterValue::OnTimeUs(on_time)), Some(parameters)) => {
+                let limit = max_on_time_us(&config, parameters.off_time_ms, parameters.power);
+                self.limit_value(*on_time as u32 > limit, "on time over limit", || ControllerMessage::SetParam(ParameterValue::OnTimeUs(limit.min(u16::MAX as u32) as u16)), message)
+            },
+            (ControllerMessage::SetParam(ParameterValue::OffTimeMs(off_time)), Some(parameters)) => {
+                let limit = min_off_time_ms(&config, parameters.on_time_us, parameters.power);
+                self.limit_value((*off_time as u32) < limit, "off time under limit", || ControllerMessage::SetParam(ParameterValue::OffTimeMs(limit.min(u16::MAX as u32) as u16)), message)
+            },
+            (ControllerMessage::SetParam(ParameterValue::FlatPower(power)), Some(parameters)) => {
+                let limit = max_power(&config, parameters.on_time_us, parameters.off_time_ms);
+                self.limit_value(*power > limit, "power over limit", || ControllerMessage::SetParam(ParameterValue::FlatPower(limit)), message)
+            },
+            (ControllerMessage::SetEnvelope(envelope), Some(parameters)) => {
+                let limit = max_power(&config, parameters.on_time_us, parameters.off_time_ms);
+                let power = envelope_power(envelope);
+                let scaled = scale_message(envelope, limit / power);
+                self.limit_value(power > limit, "envelope power over limit", || ControllerMessage::SetEnvelope(scaled), message)
+            },
+            _ => Verdict::Allow(message),
+        };
+        match &verdict {
+            Verdict::Allow(message) | Verdict::Clamped(message) => self.observe_sent(message),
+            Verdict::Corrected { correction, message } => {
+                self.observe_sent(correction);
+                self.observe_sent(message);
+            },
+            Verdict::Refused(_) => {},
+        }
+        verdict
+    }
+
+    // checks everything that has been set so far. clamping shortens the on time, which brings
+    // both the duty cycle and the average power down
+    fn check_run(&self, message: ControllerMessage) -> Verdict {
+        let config = self.config;
+        let Some(parameters) = self.complete_parameters() else {
+            return Verdict::Refused("burst parameters not set");
+        };
+        let reason = if self.overheated {
+            return Verdict::Refused("thermal budget used up");
+        } else if parameters.duty_cycle() > config.max_duty {
+            "duty cycle over limit"
+        } else if parameters.average_power() > config.max_average_power {
+            "average power over limit"
+        } else {
+            return Verdict::Allow(message);
+        };
+        match config.mode {
+            LimitMode::Clamp => {
+                let limit = max_on_time_us(&config, parameters.off_time_ms, parameters.power);
+                Verdict::Corrected {
+                    correction: ControllerMessage::SetParam(ParameterValue::OnTimeUs(limit.min(u16::MAX as u32) as u16)),
+                    message,
+                }
+            },
+            LimitMode::Refuse => Verdict::Refused(reason),
+        }
+    }
+
+    fn limit_value(&self, over_limit: bool, reason: &'static str, clamped: impl FnOnce() -> ControllerMessage, message: ControllerMessage) -> Verdict {
+        match (over_limit, self.config.mode) {
+            (false, _) => Verdict::Allow(message),
+            (true, LimitMode::Clamp) => Verdict::Clamped(clamped()),
+            (true, LimitMode::Refuse) => Verdict::Refused(reason),
+        }
+    }
+
+    fn observe_sent(&mut self, message: &ControllerMessage) {
+        match message {
+            ControllerMessage::Run => self.running = true,
+            ControllerMessage::Stop => self.running = false,
+            ControllerMessage::SetParam(value) => self.apply(value),
+            ControllerMessage::SetEnvelope(envelope) => self.power = Some(envelope_power(envelope)),
+            _ => {}
+        }
+    }
+
+    fn apply(&mut self, value: &ParameterValue) {
+        match value {
+            ParameterValue::OnTimeUs(on_time) => self.on_time_us = Some(*on_time as u32),
+            ParameterValue::OffTimeMs(off_time) => self.off_time_ms = Some(*off_time as u32),
+            ParameterValue::FlatPower(power) => self.power = Some(*power),
+            _ => {}
+        }
+    }
+}
+
+fn envelope_power(envelope: &PowerEnvelope) -> f32 {
+    message_mean_power(envelope) / 100.0
+}
@@ -14,6 +14,7 @@ use embedded_hal::digital::{InputPin, OutputPin};
 use fugit::{Duration, ExtU32, RateExtU32};
 use mn12864k::SwapChain;
 use panic_halt as _;
+use defmt_rtt as _;
 
 use qcw_com::RemoteMessage;
 use rp235x_hal::clocks::ClockSource;
@@ -38,6 +39,17 @@ mod mn12864k;
 mod application;
 mod app_views;
 mod ui;
+mod screenshot;
+mod event_log;
+mod sequence;
+mod sweep;
+mod envelope;
+mod music;
+mod limiter;
+mod run_time;
+mod flash_store;
+mod parameters;
+mod animation;
 
 use qcw_com::*;
 
@@ -231,9 +243,12 @@ fn main() -> ! {
 
     let mut previous_button_states: [bool; 4] = [false; 4];
     let mut previous_encoder_count: i32 = 0;
+    let mut screenshot_chord_held = false;
 
-    let shared_state = AppSharedState {
-    };
+    let mut shared_state = AppSharedState::new();
+    if let Some(payload) = flash_store::read() {
+        shared_state.restore(&payload);
+    }
 
     let mut application = application::Application::new(shared_state);
 
@@ -257,7 +272,7 @@ fn main() -> ! {
         };
         previous_encoder_count = encoder_count;
 
-        let buttons_state_pressed_released = [0, 1, 2, 3].map(|i| {
+        let mut buttons_state_pressed_released = [0, 1, 2, 3].map(|i| {
             let current_state = BUTTON_STATES[i].load(Ordering::SeqCst);
             let previous_state = previous_button_states[i];
             let pressed = current_state && !previous_state;
@@ -265,6 +280,22 @@ fn main() -> ! {
             previous_button_states[i] = current_state;
             (current_state, pressed, released)
         });
+
+        // holding the two outer frame buttons together requests a screenshot. the chord is hidden
+        // from the application until both are let go, so it doesn't also trigger "Back" and friends
+        if buttons_state_pressed_released[BUTTON_0].0 && buttons_state_pressed_released[BUTTON_2].0 {
+            if !screenshot_chord_held {
+                screenshot::request();
+            }
+            screenshot_chord_held = true;
+        }
+        if screenshot_chord_held {
+            if !buttons_state_pressed_released[BUTTON_0].0 && !buttons_state_pressed_released[BUTTON_2].0 {
+                screenshot_chord_held = false;
+            }
+            buttons_state_pressed_released[BUTTON_0] = (false, false, false);
+            buttons_state_pressed_released[BUTTON_2] = (false, false, false);
+        }
         
         let input_state = application::InputState {
             encoder: EncoderState {
@@ -309,9 +340,20 @@ fn main() -> ! {
             }
         }
 
-        while let Some(message) = RemoteMessage::try_receive(&mut rx_buffer).unwrap() {
-            incoming_messages.push_back(message);
-        };
+        loop {
+            match RemoteMessage::try_receive(&mut rx_buffer) {
+                Ok(Some(message)) => incoming_messages.push_back(message),
+                Ok(None) => break,
+                Err(_) => {
+                    // drop whatever is buffered and resynchronize on the next message
+                    while rx_buffer.peek().is_some() {
+                        rx_buffer.pop();
+                    }
+                    application.log(event_log::Severity::Error, format_args!("Message decode error"));
+                    break;
+                }
+            }
+        }
 
         let com_state = application::ComState {
             inbox: &mut incoming_messages,
@@ -320,6 +362,12 @@ fn main() -> ! {
 
         application.update(delta_t.to_micros(), input_state, com_state);
 
+        if let Some(settings) = application.take_settings_save() {
+            let mut payload = [0u8; flash_store::PAYLOAD_SIZE];
+            payload[..settings.len()].copy_from_slice(&settings);
+            flash_store::write(&payload);
+        }
+
         while let Some(message) = outgoing_messages.front() {
             if !message.try_send(&mut tx_buffer) {
                 break;
@@ -343,11 +391,17 @@ fn main() -> ! {
             }
         }
         
-        if let Some(mut target) = swapchain.acquire_next_target() {
-            let framebuffer = target.framebuffer();
-            framebuffer.clear(false);
-            application.render(framebuffer);
-            target.present();
+        // only frames with something new on them are drawn, leaving the time for the uart
+        if application.needs_render() || screenshot::pending() {
+            if let Some(mut target) = swapchain.acquire_next_target() {
+                if !target.copy_last_frame() {
+                    application.invalidate_all();
+                }
+                let framebuffer = target.framebuffer();
+                application.render(framebuffer);
+                screenshot::capture_if_requested(framebuffer);
+                target.present();
+            }
         }
     }
 }
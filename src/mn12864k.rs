@@ -7,82 +7,11 @@ use hal::{spi, gpio, clocks::ClockSource};
 use critical_section::Mutex;
 use fugit::{ExtU32, ExtU32Ceil, RateExtU32};
 
-use crate::gfx::draw_target::DrawTarget;
+use crate::flash_store;
 
-pub struct Framebuffer {
-    pub buffer: [u8; 6*8*22],
-}
-
-impl Framebuffer {
-    pub const fn new() -> Self {
-        Self {
-            buffer: [0u8; 6*8*22]
-        }
-    }
-}
+mod framebuffer;
 
-impl Framebuffer {
-    const PIXEL_OFFSETS: [usize; 6] = [0, 2, 4, 5, 3, 1];
-
-    fn coord_to_bit_byte(position: (usize, usize)) -> (usize, usize) {
-        let column = position.0 / 6;
-        let pixel = (position.1 * 6) + Self::PIXEL_OFFSETS[position.0 % 6];
-        let byte = (pixel / 8) + column * 8 * 6;
-        let bit = pixel % 8;
-        (bit, byte)
-    }
-
-    fn position_in_buffer(position: (isize, isize)) -> bool {
-        position.0 >= 0 && position.0 < 128 &&
-        position.1 >= 0 && position.1 < 64
-    }
-
-    pub fn set(&mut self, position: (isize, isize), color: bool) {
-        if Self::position_in_buffer(position) {
-            self.set_raw((position.0 as usize, position.1 as usize), color);
-        }
-    }
-
-    pub fn get(&self, position: (isize, isize)) -> bool {
-        if Self::position_in_buffer(position) {
-            self.get_raw((position.0 as usize, position.1 as usize))
-        } else {
-            false
-        }
-    }
-
-    pub fn set_raw(&mut self, position: (usize, usize), color: bool) {
-        let (bit, byte) = Self::coord_to_bit_byte(position);
-        if color {
-            self.buffer[byte] |= 1 << bit;
-        } else {
-            self.buffer[byte] &= !(1 << bit);
-        }
-    }
-
-    pub fn get_raw(&self, position: (usize, usize)) -> bool {
-        let (bit, byte) = Self::coord_to_bit_byte(position);
-        (self.buffer[byte] & (1 << bit)) != 0
-    }
-
-    pub fn clear(&mut self, color: bool) {
-        if color {
-            self.buffer.fill(0xFF);
-        } else {
-            self.buffer.fill(0x00);
-        }
-    }
-}
-
-impl DrawTarget for Framebuffer {
-    fn set_pixel(&mut self, position: (isize, isize), color: bool) {
-        self.set(position, color);
-    }
-
-    fn get_pixel(&self, position: (isize, isize)) -> bool {
-        self.get(position)
-    }
-}
+pub use framebuffer::{Framebuffer, HEIGHT, LINEAR_BITMAP_SIZE, WIDTH};
 
 struct SwapChainState {
     pending_read: Option<usize>,
@@ -131,6 +60,8 @@ impl SwapChainState {
 pub struct SwapChain {
     framebuffers: [Framebuffer; 3],
     state: Mutex<RefCell<SwapChainState>>,
+    // only touched by whoever renders, the display just reads the buffer
+    last_presented: Option<usize>,
 }
 
 unsafe impl Sync for SwapChain {}
@@ -147,6 +78,19 @@ impl SwapChainTarget<'_> {
         &mut self.swapchain.framebuffers[self.index]
     }
 
+    // starts this frame off as a copy of the last one presented, so only what changed needs
+    // drawing. false if nothing has been presented yet and the whole frame has to be drawn
+    pub fn copy_last_frame(&mut self) -> bool {
+        match self.swapchain.last_presented {
+            None => false,
+            Some(last) if last == self.index => true,
+            Some(last) => {
+                self.swapchain.framebuffers[self.index].buffer = self.swapchain.framebuffers[last].buffer;
+                true
+            },
+        }
+    }
+
     pub fn present(&mut self) {
         if self.presented {
             return;
@@ -155,6 +99,7 @@ impl SwapChainTarget<'_> {
             let mut state = self.swapchain.state.borrow_ref_mut(cs);
             state.present(self.index);
         });
+        self.swapchain.last_presented = Some(self.index);
         self.presented = true;
     }
 }
@@ -175,7 +120,8 @@ impl SwapChain {
     pub const fn new() -> Self {
         Self {
             framebuffers: [Framebuffer::new(), Framebuffer::new(), Framebuffer::new()],
-            state: Mutex::new(RefCell::new(SwapChainState::new()))
+            state: Mutex::new(RefCell::new(SwapChainState::new())),
+            last_presented: None,
         }
     }
 
@@ -281,6 +227,12 @@ impl <GSpi: spi::SpiDevice, GSpiPinout: spi::ValidSpiPinout<GSpi>, GLatPinId: Pi
         blk_count_down.start(5u32.micros_at_least());
 
         loop {
+            if flash_store::park_requested() {
+                // blank rather than leave one grid lit while flash is being written
+                _ = self.gblk.set_state(gpio::PinState::Low);
+                _ = self.pblk.set_state(gpio::PinState::Low);
+                flash_store::park();
+            }
             framebuffer_index = critical_section::with(|cs| {
                 let mut swapchain_state = self.swapchain.state.borrow_ref_mut(cs);
                 match (swapchain_state.pop_presented(), framebuffer_index) {
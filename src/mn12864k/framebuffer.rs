@@ -0,0 +1,307 @@
+use crate::gfx::draw_target::DrawTarget;
+
+pub struct Framebuffer {
+    pub buffer: [u8; 6*8*22],
+    // drawing only touches pixels inside these corners, inclusive
+    clip: ((isize, isize), (isize, isize)),
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Framebuffer {
+    const SCREEN: ((isize, isize), (isize, isize)) = ((0, 0), (WIDTH as isize - 1, HEIGHT as isize - 1));
+
+    pub const fn new() -> Self {
+        Self {
+            buffer: [0u8; 6*8*22],
+            clip: Self::SCREEN,
+        }
+    }
+
+    // limits drawing to a rect of the screen, so part of a frame can be redrawn over the last one
+    pub fn set_clip(&mut self, upper_left: (isize, isize), lower_right: (isize, isize)) {
+        self.clip = (
+            (upper_left.0.max(0), upper_left.1.max(0)),
+            (lower_right.0.min(WIDTH as isize - 1), lower_right.1.min(HEIGHT as isize - 1)),
+        );
+    }
+
+    pub fn reset_clip(&mut self) {
+        self.clip = Self::SCREEN;
+    }
+}
+
+impl Framebuffer {
+    const PIXEL_OFFSETS: [usize; 6] = [0, 2, 4, 5, 3, 1];
+
+    // where each x lands: the first byte of its six pixel wide column and its offset within a
+    // row of the column, so finding a pixel doesn't need a divide by 6
+    const COLUMNS: [(u16, u8); WIDTH] = Self::column_table();
+
+    const fn column_table() -> [(u16, u8); WIDTH] {
+        let mut table = [(0, 0); WIDTH];
+        let mut x = 0;
+        while x < WIDTH {
+            table[x] = (((x / 6) * 8 * 6) as u16, Self::PIXEL_OFFSETS[x % 6] as u8);
+            x += 1;
+        }
+        table
+    }
+
+    // six pixels of a row, leftmost in the lowest bit, moved to where they go in a column
+    const SCATTER: [u8; 64] = Self::scatter_table();
+
+    const fn scatter_table() -> [u8; 64] {
+        let mut table = [0; 64];
+        let mut pixels = 0;
+        while pixels < 64 {
+            let mut x = 0;
+            while x < 6 {
+                if pixels & (1 << x) != 0 {
+                    table[pixels] |= 1 << Self::PIXEL_OFFSETS[x];
+                }
+                x += 1;
+            }
+            pixels += 1;
+        }
+        table
+    }
+
+    // the other way, from a row of a column back to six pixels with the leftmost in the lowest bit
+    const GATHER: [u8; 64] = Self::gather_table();
+
+    const fn gather_table() -> [u8; 64] {
+        let scatter = Self::scatter_table();
+        let mut table = [0; 64];
+        let mut pixels = 0;
+        while pixels < 64 {
+            table[scatter[pixels] as usize] = pixels as u8;
+            pixels += 1;
+        }
+        table
+    }
+
+    fn coord_to_bit_byte(position: (usize, usize)) -> (usize, usize) {
+        let (column_byte, offset) = Self::COLUMNS[position.0];
+        let pixel = (position.1 * 6) + offset as usize;
+        (pixel % 8, column_byte as usize + pixel / 8)
+    }
+
+    fn position_in_buffer(position: (isize, isize)) -> bool {
+        position.0 >= 0 && position.0 < 128 &&
+        position.1 >= 0 && position.1 < 64
+    }
+
+    pub fn set(&mut self, position: (isize, isize), color: bool) {
+        let (upper_left, lower_right) = self.clip;
+        if position.0 >= upper_left.0 && position.0 <= lower_right.0 &&
+           position.1 >= upper_left.1 && position.1 <= lower_right.1 {
+            self.set_raw((position.0 as usize, position.1 as usize), color);
+        }
+    }
+
+    pub fn get(&self, position: (isize, isize)) -> bool {
+        if Self::position_in_buffer(position) {
+            self.get_raw((position.0 as usize, position.1 as usize))
+        } else {
+            false
+        }
+    }
+
+    pub fn set_raw(&mut self, position: (usize, usize), color: bool) {
+        let (bit, byte) = Self::coord_to_bit_byte(position);
+        if color {
+            self.buffer[byte] |= 1 << bit;
+        } else {
+            self.buffer[byte] &= !(1 << bit);
+        }
+    }
+
+    pub fn get_raw(&self, position: (usize, usize)) -> bool {
+        let (bit, byte) = Self::coord_to_bit_byte(position);
+        (self.buffer[byte] & (1 << bit)) != 0
+    }
+
+    // the whole buffer, whatever the clip
+    pub fn clear(&mut self, color: bool) {
+        if color {
+            self.buffer.fill(0xFF);
+        } else {
+            self.buffer.fill(0x00);
+        }
+    }
+
+    // row-major, 16 bytes per row, leftmost pixel in the most significant bit
+    pub fn to_linear_bitmap(&self) -> [u8; LINEAR_BITMAP_SIZE] {
+        let mut bitmap = [0u8; LINEAR_BITMAP_SIZE];
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                if self.get_raw((x, y)) {
+                    bitmap[y * (WIDTH / 8) + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        bitmap
+    }
+}
+
+pub const WIDTH: usize = 128;
+pub const HEIGHT: usize = 64;
+pub const LINEAR_BITMAP_SIZE: usize = WIDTH * HEIGHT / 8;
+
+// rows put together into one run of bits before they're written to a column, which has to fit
+// in a u128 with up to 7 bits of shift
+const MAX_RUN_ROWS: usize = 20;
+
+impl Framebuffer {
+    // `rows` rows of one six pixel column from `top` down, six bits a row in PIXEL_OFFSETS order.
+    // down a column the rows follow one another, so they're written a byte at a time
+    fn write_column_run(&mut self, column: usize, top: usize, rows: usize, run: u128, color: bool) {
+        let start_bit = top * 6;
+        let run = run << (start_bit % 8);
+        let first_byte = column * 8 * 6 + start_bit / 8;
+        let byte_count = (start_bit % 8 + rows * 6).div_ceil(8);
+        for (byte, bits) in self.buffer[first_byte..first_byte + byte_count].iter_mut().zip(run.to_le_bytes()) {
+            if color {
+                *byte |= bits;
+            } else {
+                *byte &= !bits;
+            }
+        }
+    }
+
+    // up to six bits from a packed bitmap, starting at any bit
+    fn bitmap_bits(bitmap: &[u8], bit: usize, count: usize) -> u8 {
+        let low = bitmap.get(bit / 8).copied().unwrap_or(0) as u16;
+        let high = bitmap.get(bit / 8 + 1).copied().unwrap_or(0) as u16;
+        (((low | (high << 8)) >> (bit % 8)) & ((1 << count) - 1)) as u8
+    }
+
+    // one row of a six pixel column, leftmost in the lowest bit. columns off the screen are blank
+    fn column_pixels(&self, column: isize, y: usize) -> u8 {
+        if column < 0 || column as usize >= WIDTH.div_ceil(6) {
+            return 0;
+        }
+        let column = column as usize;
+        // the last column runs past the right edge of the screen
+        let on_screen = 0x3F >> (6 - (WIDTH - column * 6).min(6));
+        Self::GATHER[Self::bitmap_bits(&self.buffer[column * 8 * 6..(column + 1) * 8 * 6], y * 6, 6) as usize] & on_screen
+    }
+
+    // six pixels of a row starting at any x, which may be off the screen
+    fn row_pixels(&self, x: isize, y: usize) -> u8 {
+        let column = x.div_euclid(6);
+        let low = self.column_pixels(column, y) as u16;
+        let high = self.column_pixels(column + 1, y) as u16;
+        (((low | (high << 6)) >> x.rem_euclid(6)) & 0x3F) as u8
+    }
+
+    // the part of the rect inside the clip, as the usual inclusive corners made exclusive
+    fn clipped(&self, upper_left: (isize, isize), lower_right: (isize, isize)) -> Option<((usize, usize), (usize, usize))> {
+        let (clip_upper_left, clip_lower_right) = self.clip;
+        let left = upper_left.0.max(clip_upper_left.0);
+        let top = upper_left.1.max(clip_upper_left.1);
+        let right = lower_right.0.min(clip_lower_right.0) + 1;
+        let bottom = lower_right.1.min(clip_lower_right.1) + 1;
+        if left < right && top < bottom {
+            Some(((left as usize, top as usize), (right as usize, bottom as usize)))
+        } else {
+            None
+        }
+    }
+}
+
+impl DrawTarget for Framebuffer {
+    fn set_pixel(&mut self, position: (isize, isize), color: bool) {
+        self.set(position, color);
+    }
+
+    fn get_pixel(&self, position: (isize, isize)) -> bool {
+        self.get(position)
+    }
+
+    fn fill_span(&mut self, x_start: isize, x_end: isize, y: isize, color: bool) {
+        self.fill_rect((x_start, y), (x_end, y), color);
+    }
+
+    // each column the rect covers gets the same six bits on every row
+    fn fill_rect(&mut self, upper_left: (isize, isize), lower_right: (isize, isize), color: bool) {
+        let Some(((left, top), (right, bottom))) = self.clipped(upper_left, lower_right) else {
+            return;
+        };
+        for column in left / 6..=(right - 1) / 6 {
+            let first = left.max(column * 6) - column * 6;
+            let last = right.min(column * 6 + 6) - column * 6;
+            let pixels = Self::SCATTER[((0x3F >> (6 - (last - first))) << first) as usize] as u128;
+            let mut y = top;
+            while y < bottom {
+                let rows = (bottom - y).min(MAX_RUN_ROWS);
+                let run = (0..rows).fold(0u128, |run, row| run | (pixels << (row * 6)));
+                self.write_column_run(column, y, rows, run, color);
+                y += rows;
+            }
+        }
+    }
+
+    // the bitmap is taken a column at a time, with the part of each row in the column moved
+    // into place with SCATTER, so glyphs and images only touch each byte they cover once
+    fn blit_bitmap(&mut self, position: (isize, isize), bitmap: &[u8], width: usize, height: usize, color: bool) {
+        let lower_right = (position.0 + width as isize - 1, position.1 + height as isize - 1);
+        let Some(((left, top), (right, bottom))) = self.clipped(position, lower_right) else {
+            return;
+        };
+        for column in left / 6..=(right - 1) / 6 {
+            let first = left.max(column * 6);
+            let count = right.min(column * 6 + 6) - first;
+            let shift = first - column * 6;
+            let bitmap_x = (first as isize - position.0) as usize;
+            let mut y = top;
+            while y < bottom {
+                let rows = (bottom - y).min(MAX_RUN_ROWS);
+                let mut run = 0u128;
+                for row in 0..rows {
+                    let bitmap_y = (y + row) as isize - position.1;
+                    let bits = Self::bitmap_bits(bitmap, bitmap_y as usize * width + bitmap_x, count);
+                    run |= (Self::SCATTER[(bits << shift) as usize] as u128) << (row * 6);
+                }
+                if run != 0 {
+                    self.write_column_run(column, y, rows, run, color);
+                }
+                y += rows;
+            }
+        }
+    }
+}
+
+impl Framebuffer {
+    // sets the pixels that are set in source, moved right by x_offset. six pixels of a row are
+    // moved at a time, the same way blit_bitmap moves them
+    pub fn blit_framebuffer(&mut self, source: &Framebuffer, x_offset: isize) {
+        let Some(((left, top), (right, bottom))) = self.clipped((x_offset, 0), (x_offset + WIDTH as isize - 1, HEIGHT as isize - 1)) else {
+            return;
+        };
+        for column in left / 6..=(right - 1) / 6 {
+            let first = left.max(column * 6) - column * 6;
+            let last = right.min(column * 6 + 6) - column * 6;
+            let mask = (0x3F >> (6 - (last - first))) << first;
+            let source_x = (column * 6) as isize - x_offset;
+            let mut y = top;
+            while y < bottom {
+                let rows = (bottom - y).min(MAX_RUN_ROWS);
+                let mut run = 0u128;
+                for row in 0..rows {
+                    let bits = source.row_pixels(source_x, y + row) & mask;
+                    run |= (Self::SCATTER[bits as usize] as u128) << (row * 6);
+                }
+                if run != 0 {
+                    self.write_column_run(column, y, rows, run, true);
+                }
+                y += rows;
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use libm::{powf, roundf};
+use qcw_com::{ControllerMessage, ParameterValue, RunMode};
+
+use crate::application::ComState;
+
+const KEEPALIVE_INTERVAL_US: u64 = 10_000;
+
+// the controller takes its off time in whole milliseconds, so pitch gets coarser the higher a note is.
+// notes with a shorter period than this are dropped by octaves until they fit
+pub const MIN_PERIOD_MS: u32 = 4;
+pub const MAX_PERIOD_MS: u32 = 100;
+// upper bound on on time / burst period, whatever on time the user asks for
+pub const MAX_DUTY: f32 = 0.02;
+
+pub const REST: u8 = 0;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Note {
+    // midi note number, or REST
+    pub pitch: u8,
+    // in sixteenth notes
+    pub length: u8,
+}
+
+const fn n(pitch: u8, length: u8) -> Note {
+    Note { pitch, length }
+}
+
+pub struct Song {
+    pub name: &'static str,
+    pub tempo_bpm: u16,
+    pub notes: &'static [Note],
+}
+
+impl Song {
+    pub fn sixteenth_us(&self) -> u64 {
+        60_000_000 / (self.tempo_bpm.max(1) as u64 * 4)
+    }
+
+    pub fn length_sixteenths(&self) -> u32 {
+        self.notes.iter().map(|note| note.length as u32).sum()
+    }
+
+    pub fn pitch_range(&self) -> Option<(u8, u8)> {
+        self.notes.iter()
+            .filter(|note| note.pitch != REST)
+            .fold(None, |range, note| match range {
+                None => Some((note.pitch, note.pitch)),
+                Some((low, high)) => Some((low.min(note.pitch), high.max(note.pitch))),
+            })
+    }
+}
+
+// songs are built in only. uploading them needs a usb stack the remote doesn't have yet, the
+// uart is the controller link
+pub const SONG_COUNT: usize = 3;
+
+pub static SONGS: [Song; SONG_COUNT] = [
+    Song {
+        name: "Scale",
+        tempo_bpm: 120,
+        notes: &[
+            n(45, 2), n(47, 2), n(48, 2), n(50, 2), n(52, 2), n(53, 2), n(55, 2), n(57, 4),
+            n(REST, 2),
+            n(57, 2), n(55, 2), n(53, 2), n(52, 2), n(50, 2), n(48, 2), n(47, 2), n(45, 4),
+        ],
+    },
+    Song {
+        name: "Ode to Joy",
+        tempo_bpm: 100,
+        notes: &[
+            n(52, 4), n(52, 4), n(53, 4), n(55, 4), n(55, 4), n(53, 4), n(52, 4), n(50, 4),
+            n(48, 4), n(48, 4), n(50, 4), n(52, 4), n(52, 6), n(50, 2), n(50, 8),
+            n(52, 4), n(52, 4), n(53, 4), n(55, 4), n(55, 4), n(53, 4), n(52, 4), n(50, 4),
+            n(48, 4), n(48, 4), n(50, 4), n(52, 4), n(50, 6), n(48, 2), n(48, 8),
+        ],
+    },
+    Song {
+        name: "Twinkle Twinkle",
+        tempo_bpm: 110,
+        notes: &[
+            n(48, 4), n(48, 4), n(55, 4), n(55, 4), n(57, 4), n(57, 4), n(55, 8),
+            n(53, 4), n(53, 4), n(52, 4), n(52, 4), n(50, 4), n(50, 4), n(48, 8),
+            n(55, 4), n(55, 4), n(53, 4), n(53, 4), n(52, 4), n(52, 4), n(50, 8),
+            n(55, 4), n(55, 4), n(53, 4), n(53, 4), n(52, 4), n(52, 4), n(50, 8),
+            n(48, 4), n(48, 4), n(55, 4), n(55, 4), n(57, 4), n(57, 4), n(55, 8),
+            n(53, 4), n(53, 4), n(52, 4), n(52, 4), n(50, 4), n(50, 4), n(48, 8),
+        ],
+    },
+];
+
+pub fn pitch_frequency_hz(pitch: u8) -> f32 {
+    440.0 * powf(2.0, (pitch as f32 - 69.0) / 12.0)
+}
+
+// the burst period, in the controller's milliseconds, closest to a note's pitch
+pub fn pitch_period_ms(pitch: u8) -> u32 {
+    let mut period = 1000.0 / pitch_frequency_hz(pitch);
+    while period < MIN_PERIOD_MS as f32 {
+        period *= 2.0;
+    }
+    (roundf(period) as u32).clamp(MIN_PERIOD_MS, MAX_PERIOD_MS)
+}
+
+pub fn pitch_name(pitch: u8) -> (&'static str, i32) {
+    const NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+    (NAMES[pitch as usize % 12], pitch as i32 / 12 - 1)
+}
+
+// the longest on time a burst of this period may have without going over MAX_DUTY
+pub fn duty_limited_on_time_us(on_time_us: u16, period_ms: u32) -> u16 {
+    let limit = (period_ms as f32 * 1000.0 * MAX_DUTY) as u32;
+    (on_time_us as u32).min(limit) as u16
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PlayerState {
+    Stopped,
+    Playing,
+    Finished,
+}
+
+// plays a song as a train of bursts, one burst per period of the note being played.
+// the coil is stopped during rests, at the end of the song and whenever playback is stopped
+pub struct MusicPlayer {
+    song: Option<&'static Song>,
+    state: PlayerState,
+    note: usize,
+    note_entered: bool,
+    t_elapsed: u64,
+    t_note_start: u64,
+    t_last_keepalive: u64,
+    firing: bool,
+    on_time_us: u16,
+    power: f32,
+    sent_on_time_us: Option<u16>,
+}
+
+impl Default for MusicPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MusicPlayer {
+    pub fn new() -> Self {
+        Self {
+            song: None,
+            state: PlayerState::Stopped,
+            note: 0,
+            note_entered: false,
+            t_elapsed: 0,
+            t_note_start: 0,
+            t_last_keepalive: 0,
+            firing: false,
+            on_time_us: 100,
+            power: 0.2,
+            sent_on_time_us: None,
+        }
+    }
+
+    pub fn play(&mut self, song: &'static Song, on_time_us: u16, power: f32, com: &mut ComState<'_>) {
+        self.stop(com);
+        self.song = Some(song);
+        self.state = PlayerState::Playing;
+        self.note = 0;
+        self.note_entered = false;
+        self.on_time_us = on_time_us;
+        self.power = power;
+        self.sent_on_time_us = None;
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::RunMode(RunMode::OpenLoop)));
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::FlatPower(power)));
+    }
+
+    pub fn stop(&mut self, com: &mut ComState<'_>) {
+        self.stop_firing(com);
+        if self.state == PlayerState::Playing {
+            self.state = PlayerState::Stopped;
+        }
+    }
+
+    pub fn set_power(&mut self, power: f32, com: &mut ComState<'_>) {
+        self.power = power;
+        if self.state == PlayerState::Playing {
+            com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::FlatPower(power)));
+        }
+    }
+
+    pub fn state(&self) -> PlayerState {
+        self.state
+    }
+
+    pub fn song(&self) -> Option<&'static Song> {
+        self.song
+    }
+
+    pub fn current_note(&self) -> Option<(usize, Note)> {
+        if self.state != PlayerState::Playing {
+            return None;
+        }
+        self.song.and_then(|song| song.notes.get(self.note)).map(|note| (self.note, *note))
+    }
+
+    // playback position in sixteenths, including the fraction of the current note
+    pub fn position_sixteenths(&self) -> f32 {
+        let Some(song) = self.song else {
+            return 0.0;
+        };
+        let played: u32 = song.notes.iter().take(self.note).map(|note| note.length as u32).sum();
+        let into_note = if self.state == PlayerState::Playing {
+            (self.t_elapsed - self.t_note_start) as f32 / song.sixteenth_us() as f32
+        } else {
+            0.0
+        };
+        played as f32 + into_note
+    }
+
+    pub fn update(&mut self, dt_micros: u64, com: &mut ComState<'_>) {
+        self.t_elapsed += dt_micros;
+        if self.state != PlayerState::Playing {
+            return;
+        }
+        let Some(song) = self.song else {
+            return;
+        };
+
+        if self.note_entered && self.t_elapsed - self.t_note_start >= song.notes[self.note].length as u64 * song.sixteenth_us() {
+            self.note += 1;
+            self.note_entered = false;
+        }
+        if self.note >= song.notes.len() {
+            self.stop_firing(com);
+            self.state = PlayerState::Finished;
+            return;
+        }
+        if !self.note_entered {
+            self.enter_note(song.notes[self.note], com);
+            self.note_entered = true;
+            self.t_note_start = self.t_elapsed;
+        }
+
+        if self.firing && (self.t_elapsed - self.t_last_keepalive) >= KEEPALIVE_INTERVAL_US {
+            com.outbox.push_back(ControllerMessage::KeepAlive);
+            self.t_last_keepalive = self.t_elapsed;
+        }
+    }
+
+    fn enter_note(&mut self, note: Note, com: &mut ComState<'_>) {
+        if note.pitch == REST {
+            self.stop_firing(com);
+            return;
+        }
+        let period_ms = pitch_period_ms(note.pitch);
+        let on_time_us = duty_limited_on_time_us(self.on_time_us, period_ms);
+        if self.sent_on_time_us != Some(on_time_us) {
+            com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::OnTimeUs(on_time_us)));
+            self.sent_on_time_us = Some(on_time_us);
+        }
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::OffTimeMs(period_ms.saturating_sub(on_time_us as u32 / 1000) as u16)));
+        if !self.firing {
+            com.outbox.push_back(ControllerMessage::Run);
+            self.firing = true;
+            self.t_last_keepalive = self.t_elapsed;
+        }
+    }
+
+    fn stop_firing(&mut self, com: &mut ComState<'_>) {
+        if self.firing {
+            com.outbox.push_back(ControllerMessage::Stop);
+            self.firing = false;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use libm::roundf;
+use qcw_com::{Parameter, ParameterValue};
+
+use crate::ui::{DigitEditor, NumericField};
+
+// how a numeric controller parameter is entered on the remote. values are fixed point with
+// `decimals` implied decimal places, the same as NumericField and DigitEditor use
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ParameterRange {
+    pub min: i32,
+    pub max: i32,
+    pub decimals: u8,
+    pub unit: &'static str,
+}
+
+impl ParameterRange {
+    const fn new(min: i32, max: i32, decimals: u8, unit: &'static str) -> Self {
+        Self { min, max, decimals, unit }
+    }
+}
+
+// the run mode isn't a number, so it has no range
+pub fn range(parameter: Parameter) -> Option<ParameterRange> {
+    match parameter {
+        Parameter::OnTime => Some(ParameterRange::new(0, 1000, 0, "µs")),
+        Parameter::OffTime => Some(ParameterRange::new(10, 5000, 0, "ms")),
+        Parameter::StartupFrequency => Some(ParameterRange::new(300, 700, 0, "kHz")),
+        Parameter::FlatPower => Some(ParameterRange::new(0, 100, 0, "%")),
+        Parameter::DelayCompensation => Some(ParameterRange::new(-400, 400, 0, "ns")),
+        _ => None,
+    }
+}
+
+// the parameter a value is for and the value in that parameter's fixed point units
+pub fn to_fixed(value: &ParameterValue) -> Option<(Parameter, i32)> {
+    match value {
+        ParameterValue::OnTimeUs(on_time) => Some((Parameter::OnTime, *on_time as i32)),
+        ParameterValue::OffTimeMs(off_time) => Some((Parameter::OffTime, *off_time as i32)),
+        ParameterValue::StartupFrequencykHz(frequency) => Some((Parameter::StartupFrequency, roundf(*frequency) as i32)),
+        ParameterValue::FlatPower(power) => Some((Parameter::FlatPower, roundf(*power * 100.0) as i32)),
+        ParameterValue::DelayCompensationNS(delay) => Some((Parameter::DelayCompensation, *delay as i32)),
+        _ => None,
+    }
+}
+
+pub fn from_fixed(parameter: Parameter, value: i32) -> Option<ParameterValue> {
+    match parameter {
+        Parameter::OnTime => Some(ParameterValue::OnTimeUs(value as u16)),
+        Parameter::OffTime => Some(ParameterValue::OffTimeMs(value as u16)),
+        Parameter::StartupFrequency => Some(ParameterValue::StartupFrequencykHz(value as f32)),
+        Parameter::FlatPower => Some(ParameterValue::FlatPower(value as f32 / 100.0)),
+        Parameter::DelayCompensation => Some(ParameterValue::DelayCompensationNS(value as i16)),
+        _ => None,
+    }
+}
+
+pub fn numeric_field(parameter: Parameter, value: i32, step: i32) -> Option<NumericField> {
+    range(parameter).map(|range| NumericField::new(value, range.min, range.max, step, range.decimals, range.unit))
+}
+
+// a digit editor starting from the value, ready to type in a new one for the same parameter
+pub fn digit_editor(value: &ParameterValue) -> Option<DigitEditor> {
+    let (parameter, value) = to_fixed(value)?;
+    let range = range(parameter)?;
+    Some(DigitEditor::new(value, range.min, range.max, range.decimals, range.unit))
+}
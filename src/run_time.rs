@@ -0,0 +1,225 @@
+use core::fmt;
+
+use qcw_com::ControllerMessage;
+
+use crate::limiter::FiringParameters;
+
+// the controller stops on its own when keepalives stop arriving for this long
+const KEEPALIVE_TIMEOUT_US: u64 = 100_000;
+// totals are written back to flash no more often than this while they are changing
+const SAVE_INTERVAL_US: u64 = 60_000_000;
+
+pub const TRIP_COUNT: usize = 2;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RunTotals {
+    pub bursts: u64,
+    // summed burst on times
+    pub on_time_us: u64,
+    // wall clock time spent running
+    pub fired_us: u64,
+}
+
+impl Default for RunTotals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunTotals {
+    pub const fn new() -> Self {
+        Self {
+            bursts: 0,
+            on_time_us: 0,
+            fired_us: 0,
+        }
+    }
+
+    fn add(&mut self, bursts: u64, on_time_us: u64, fired_us: u64) {
+        self.bursts += bursts;
+        self.on_time_us += on_time_us;
+        self.fired_us += fired_us;
+    }
+
+    const SERIALIZED_SIZE: usize = 24;
+
+    fn write_to(&self, bytes: &mut [u8]) {
+        bytes[0..8].copy_from_slice(&self.bursts.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.on_time_us.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.fired_us.to_le_bytes());
+    }
+
+    fn read_from(bytes: &[u8]) -> Self {
+        let read_u64 = |offset: usize| {
+            let mut value = [0u8; 8];
+            value.copy_from_slice(&bytes[offset..offset + 8]);
+            u64::from_le_bytes(value)
+        };
+        Self {
+            bursts: read_u64(0),
+            on_time_us: read_u64(8),
+            fired_us: read_u64(16),
+        }
+    }
+}
+
+// compact duration for the maintenance table, picking units so it stays short
+pub struct DisplayDuration(pub u64);
+
+impl fmt::Display for DisplayDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let us = self.0;
+        if us < 1_000 {
+            write!(f, "{}µs", us)
+        } else if us < 1_000_000 {
+            write!(f, "{}.{}ms", us / 1_000, (us % 1_000) / 100)
+        } else if us < 60_000_000 {
+            write!(f, "{}.{}s", us / 1_000_000, (us % 1_000_000) / 100_000)
+        } else if us < 3_600_000_000 {
+            write!(f, "{}m{:02}s", us / 60_000_000, (us % 60_000_000) / 1_000_000)
+        } else {
+            write!(f, "{}h{:02}m", us / 3_600_000_000, (us % 3_600_000_000) / 60_000_000)
+        }
+    }
+}
+
+// follows Run, Stop and KeepAlive on their way out and counts bursts and on time from the
+// parameters in use. the session totals start from zero at power up, the lifetime and trip
+// totals are persisted
+pub struct RunTimeAccountant {
+    session: RunTotals,
+    lifetime: RunTotals,
+    trips: [RunTotals; TRIP_COUNT],
+    firing: bool,
+    t_last_keepalive: u64,
+    // time into the current burst period, carried across updates
+    period_phase_us: u64,
+    unsaved: bool,
+    t_last_save: u64,
+    save_requested: bool,
+}
+
+impl Default for RunTimeAccountant {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunTimeAccountant {
+    pub const fn new() -> Self {
+        Self {
+            session: RunTotals::new(),
+            lifetime: RunTotals::new(),
+            trips: [RunTotals::new(); TRIP_COUNT],
+            firing: false,
+            t_last_keepalive: 0,
+            period_phase_us: 0,
+            unsaved: false,
+            t_last_save: 0,
+            save_requested: false,
+        }
+    }
+
+    pub fn session(&self) -> &RunTotals {
+        &self.session
+    }
+
+    pub fn lifetime(&self) -> &RunTotals {
+        &self.lifetime
+    }
+
+    pub fn trip(&self, index: usize) -> &RunTotals {
+        &self.trips[index]
+    }
+
+    pub fn firing(&self) -> bool {
+        self.firing
+    }
+
+    // for when something else stored alongside the totals changes. it is written on the same
+    // schedule as the totals
+    pub fn mark_unsaved(&mut self) {
+        self.unsaved = true;
+    }
+
+    pub fn reset_trip(&mut self, index: usize) {
+        self.trips[index] = RunTotals::new();
+        if self.firing {
+            self.unsaved = true;
+        } else {
+            self.save_requested = true;
+        }
+    }
+
+    pub fn observe_sent(&mut self, message: &ControllerMessage, now_us: u64) {
+        match message {
+            ControllerMessage::Run => {
+                if !self.firing {
+                    self.period_phase_us = 0;
+                }
+                self.firing = true;
+                self.t_last_keepalive = now_us;
+            },
+            ControllerMessage::KeepAlive => self.t_last_keepalive = now_us,
+            ControllerMessage::Stop => self.firing = false,
+            _ => {}
+        }
+    }
+
+    pub fn update(&mut self, dt_micros: u64, now_us: u64, parameters: &FiringParameters) {
+        if !self.firing {
+            // saving parks the display for a moment, so it waits until the coil is idle
+            if self.unsaved && now_us - self.t_last_save >= SAVE_INTERVAL_US {
+                self.save_requested = true;
+            }
+            return;
+        }
+        if now_us - self.t_last_keepalive > KEEPALIVE_TIMEOUT_US {
+            // the controller will have stopped by itself
+            self.firing = false;
+            return;
+        }
+        let period_us = parameters.on_time_us as u64 + parameters.off_time_ms as u64 * 1000;
+        if period_us == 0 {
+            return;
+        }
+        // a burst is counted at the start of its period
+        let phase = self.period_phase_us + dt_micros;
+        let bursts = phase.div_ceil(period_us) - self.period_phase_us.div_ceil(period_us);
+        self.period_phase_us = phase % period_us;
+        let on_time_us = bursts * parameters.on_time_us as u64;
+
+        self.session.add(bursts, on_time_us, dt_micros);
+        self.lifetime.add(bursts, on_time_us, dt_micros);
+        self.trips.iter_mut().for_each(|trip| trip.add(bursts, on_time_us, dt_micros));
+        self.unsaved = true;
+    }
+
+    // the persisted totals, when they are due to be written back
+    pub fn take_save(&mut self, now_us: u64) -> Option<[u8; PERSISTED_SIZE]> {
+        if !self.save_requested {
+            return None;
+        }
+        self.save_requested = false;
+        self.unsaved = false;
+        self.t_last_save = now_us;
+        let mut bytes = [0u8; PERSISTED_SIZE];
+        self.lifetime.write_to(&mut bytes[0..]);
+        for (i, trip) in self.trips.iter().enumerate() {
+            trip.write_to(&mut bytes[(i + 1) * RunTotals::SERIALIZED_SIZE..]);
+        }
+        Some(bytes)
+    }
+
+    pub fn restore(&mut self, bytes: &[u8]) {
+        if bytes.len() < PERSISTED_SIZE {
+            return;
+        }
+        self.lifetime = RunTotals::read_from(&bytes[0..]);
+        for (i, trip) in self.trips.iter_mut().enumerate() {
+            *trip = RunTotals::read_from(&bytes[(i + 1) * RunTotals::SERIALIZED_SIZE..]);
+        }
+    }
+}
+
+pub const PERSISTED_SIZE: usize = (TRIP_COUNT + 1) * RunTotals::SERIALIZED_SIZE;
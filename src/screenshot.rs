@@ -0,0 +1,29 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::mn12864k::{Framebuffer, HEIGHT, WIDTH};
+
+static SCREENSHOT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+pub fn request() {
+    SCREENSHOT_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+// a frame has to be presented for the screenshot to be taken, even if nothing on it changed
+pub fn pending() -> bool {
+    SCREENSHOT_REQUESTED.load(Ordering::SeqCst)
+}
+
+// dumps the framebuffer over RTT if a screenshot was requested since the last call.
+// the tools/vfd_screenshot host tool turns the log output into a PNG. the uart is the 10 kbaud
+// controller link, a 1 KiB dump there would hold up the keepalives for about a second
+pub fn capture_if_requested(framebuffer: &Framebuffer) {
+    if !SCREENSHOT_REQUESTED.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let bitmap = framebuffer.to_linear_bitmap();
+    defmt::println!("vfd-screenshot begin {=usize}x{=usize}", WIDTH, HEIGHT);
+    for (row, bytes) in bitmap.chunks(WIDTH / 8).enumerate() {
+        defmt::println!("vfd-row {=usize} {=[u8]:x}", row, bytes);
+    }
+    defmt::println!("vfd-screenshot end");
+}
@@ -0,0 +1,332 @@
+use core::fmt;
+
+use alloc::vec::Vec;
+use qcw_com::{ControllerMessage, ParameterValue, RemoteMessage, RunMode, Statistic, StatisticValue};
+
+use crate::application::ComState;
+use crate::event_log::DisplayParameterValue;
+
+const KEEPALIVE_INTERVAL_US: u64 = 10_000;
+const STAT_REPLY_TIMEOUT_US: u64 = 500_000;
+const MAX_RECORDS: usize = 32;
+
+// on/off times the controller is assumed to use until a sequence sets its own
+const DEFAULT_ON_TIME_US: u32 = 100;
+const DEFAULT_OFF_TIME_MS: u32 = 100;
+
+pub enum SequenceStep {
+    SetParam(ParameterValue),
+    ResetStats,
+    // fire for this many bursts, timed from the most recently set on and off times
+    RunBursts(u32),
+    RunMs(u32),
+    WaitMs(u32),
+    RecordMaxCurrent,
+}
+
+impl fmt::Display for SequenceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceStep::SetParam(value) => write!(f, "Set {}", DisplayParameterValue(value)),
+            SequenceStep::ResetStats => write!(f, "Reset Stats"),
+            SequenceStep::RunBursts(bursts) => write!(f, "Run {} bursts", bursts),
+            SequenceStep::RunMs(duration) => write!(f, "Run {}ms", duration),
+            SequenceStep::WaitMs(duration) => write!(f, "Wait {}ms", duration),
+            SequenceStep::RecordMaxCurrent => write!(f, "Record Max Current"),
+        }
+    }
+}
+
+pub struct Sequence {
+    pub name: &'static str,
+    pub steps: &'static [SequenceStep],
+}
+
+pub const SEQUENCE_COUNT: usize = 3;
+
+pub static SEQUENCES: [Sequence; SEQUENCE_COUNT] = [
+    Sequence {
+        name: "Power Ramp",
+        steps: &[
+            SequenceStep::SetParam(ParameterValue::RunMode(RunMode::OpenLoop)),
+            SequenceStep::SetParam(ParameterValue::OnTimeUs(200)),
+            SequenceStep::SetParam(ParameterValue::OffTimeMs(100)),
+            SequenceStep::SetParam(ParameterValue::FlatPower(0.1)),
+            SequenceStep::ResetStats,
+            SequenceStep::RunBursts(5),
+            SequenceStep::RecordMaxCurrent,
+            SequenceStep::WaitMs(500),
+            SequenceStep::SetParam(ParameterValue::FlatPower(0.2)),
+            SequenceStep::ResetStats,
+            SequenceStep::RunBursts(5),
+            SequenceStep::RecordMaxCurrent,
+            SequenceStep::WaitMs(500),
+            SequenceStep::SetParam(ParameterValue::FlatPower(0.3)),
+            SequenceStep::ResetStats,
+            SequenceStep::RunBursts(5),
+            SequenceStep::RecordMaxCurrent,
+            SequenceStep::WaitMs(500),
+            SequenceStep::SetParam(ParameterValue::FlatPower(0.4)),
+            SequenceStep::ResetStats,
+            SequenceStep::RunBursts(5),
+            SequenceStep::RecordMaxCurrent,
+        ],
+    },
+    Sequence {
+        name: "On Time Ramp",
+        steps: &[
+            SequenceStep::SetParam(ParameterValue::RunMode(RunMode::OpenLoop)),
+            SequenceStep::SetParam(ParameterValue::FlatPower(0.2)),
+            SequenceStep::SetParam(ParameterValue::OffTimeMs(200)),
+            SequenceStep::SetParam(ParameterValue::OnTimeUs(100)),
+            SequenceStep::ResetStats,
+            SequenceStep::RunBursts(5),
+            SequenceStep::RecordMaxCurrent,
+            SequenceStep::WaitMs(500),
+            SequenceStep::SetParam(ParameterValue::OnTimeUs(200)),
+            SequenceStep::ResetStats,
+            SequenceStep::RunBursts(5),
+            SequenceStep::RecordMaxCurrent,
+            SequenceStep::WaitMs(500),
+            SequenceStep::SetParam(ParameterValue::OnTimeUs(400)),
+            SequenceStep::ResetStats,
+            SequenceStep::RunBursts(5),
+            SequenceStep::RecordMaxCurrent,
+        ],
+    },
+    Sequence {
+        name: "Soak 10s",
+        steps: &[
+            SequenceStep::SetParam(ParameterValue::RunMode(RunMode::OpenLoop)),
+            SequenceStep::SetParam(ParameterValue::FlatPower(0.1)),
+            SequenceStep::SetParam(ParameterValue::OnTimeUs(100)),
+            SequenceStep::SetParam(ParameterValue::OffTimeMs(500)),
+            SequenceStep::ResetStats,
+            SequenceStep::RunMs(10_000),
+            SequenceStep::RecordMaxCurrent,
+        ],
+    },
+];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RunnerState {
+    Idle,
+    Running,
+    Paused,
+    Finished,
+    Aborted(&'static str),
+}
+
+#[derive(Copy, Clone)]
+pub struct StatRecord {
+    pub step: usize,
+    pub max_current: f32,
+}
+
+// executes a sequence against the controller. whenever the runner stops for any reason
+// while the coil is firing, it sends a Stop
+pub struct SequenceRunner {
+    sequence: Option<&'static Sequence>,
+    state: RunnerState,
+    step: usize,
+    step_entered: bool,
+    step_elapsed_us: u64,
+    step_duration_us: u64,
+    t_elapsed: u64,
+    t_last_keepalive: u64,
+    firing: bool,
+    on_time_us: u32,
+    off_time_ms: u32,
+    records: Vec<StatRecord>,
+}
+
+impl Default for SequenceRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SequenceRunner {
+    pub fn new() -> Self {
+        Self {
+            sequence: None,
+            state: RunnerState::Idle,
+            step: 0,
+            step_entered: false,
+            step_elapsed_us: 0,
+            step_duration_us: 0,
+            t_elapsed: 0,
+            t_last_keepalive: 0,
+            firing: false,
+            on_time_us: DEFAULT_ON_TIME_US,
+            off_time_ms: DEFAULT_OFF_TIME_MS,
+            records: Vec::new(),
+        }
+    }
+
+    pub fn start(&mut self, sequence: &'static Sequence) {
+        *self = Self::new();
+        self.sequence = Some(sequence);
+        self.state = RunnerState::Running;
+    }
+
+    pub fn pause(&mut self, com: &mut ComState<'_>) {
+        if self.state == RunnerState::Running {
+            self.stop_firing(com);
+            self.state = RunnerState::Paused;
+        }
+    }
+
+    // the interrupted step is re-entered, a run step continues for its remaining time
+    pub fn resume(&mut self) {
+        if self.state == RunnerState::Paused {
+            self.step_entered = false;
+            self.state = RunnerState::Running;
+        }
+    }
+
+    pub fn abort(&mut self, com: &mut ComState<'_>, reason: &'static str) {
+        if self.is_active() {
+            self.stop_firing(com);
+            self.state = RunnerState::Aborted(reason);
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, RunnerState::Running | RunnerState::Paused)
+    }
+
+    pub fn state(&self) -> RunnerState {
+        self.state
+    }
+
+    pub fn sequence(&self) -> Option<&'static Sequence> {
+        self.sequence
+    }
+
+    // (current step, total steps)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.step, self.sequence.map(|sequence| sequence.steps.len()).unwrap_or(0))
+    }
+
+    pub fn current_step(&self) -> Option<&'static SequenceStep> {
+        self.sequence.and_then(|sequence| sequence.steps.get(self.step))
+    }
+
+    // fraction of the current timed step completed
+    pub fn step_progress(&self) -> f32 {
+        if self.step_duration_us == 0 {
+            0.0
+        } else {
+            (self.step_elapsed_us as f32 / self.step_duration_us as f32).min(1.0)
+        }
+    }
+
+    pub fn records(&self) -> &[StatRecord] {
+        &self.records
+    }
+
+    pub fn handle_message(&mut self, message: &RemoteMessage) {
+        if self.state != RunnerState::Running || !self.step_entered {
+            return;
+        }
+        if let (Some(SequenceStep::RecordMaxCurrent), RemoteMessage::GetStatResult(StatisticValue::MaxPrimaryCurrentA(current))) = (self.current_step(), message) {
+            if self.records.len() == MAX_RECORDS {
+                self.records.remove(0);
+            }
+            self.records.push(StatRecord { step: self.step, max_current: *current });
+            self.advance();
+        }
+    }
+
+    pub fn update(&mut self, dt_micros: u64, com: &mut ComState<'_>) {
+        self.t_elapsed += dt_micros;
+        if self.state != RunnerState::Running {
+            return;
+        }
+        self.step_elapsed_us += dt_micros;
+
+        // instantaneous steps are all issued in the same update
+        while self.state == RunnerState::Running {
+            let Some(step) = self.current_step() else {
+                self.stop_firing(com);
+                self.state = RunnerState::Finished;
+                break;
+            };
+            if !self.step_entered {
+                self.enter_step(step, com);
+                self.step_entered = true;
+            }
+            let complete = match step {
+                SequenceStep::SetParam(_) | SequenceStep::ResetStats => true,
+                SequenceStep::RunBursts(_) | SequenceStep::RunMs(_) | SequenceStep::WaitMs(_) => self.step_elapsed_us >= self.step_duration_us,
+                SequenceStep::RecordMaxCurrent => {
+                    if self.step_elapsed_us >= STAT_REPLY_TIMEOUT_US {
+                        self.abort(com, "No reply from controller");
+                    }
+                    false
+                },
+            };
+            if !complete {
+                break;
+            }
+            if matches!(step, SequenceStep::RunBursts(_) | SequenceStep::RunMs(_)) {
+                self.stop_firing(com);
+            }
+            self.advance();
+        }
+
+        if self.firing && (self.t_elapsed - self.t_last_keepalive) >= KEEPALIVE_INTERVAL_US {
+            com.outbox.push_back(ControllerMessage::KeepAlive);
+            self.t_last_keepalive = self.t_elapsed;
+        }
+    }
+
+    fn enter_step(&mut self, step: &SequenceStep, com: &mut ComState<'_>) {
+        match step {
+            SequenceStep::SetParam(value) => {
+                match value {
+                    ParameterValue::OnTimeUs(on_time) => self.on_time_us = *on_time as u32,
+                    ParameterValue::OffTimeMs(off_time) => self.off_time_ms = *off_time as u32,
+                    _ => {}
+                }
+                com.outbox.push_back(ControllerMessage::SetParam(value.clone()));
+            },
+            SequenceStep::ResetStats => com.outbox.push_back(ControllerMessage::ResetStats),
+            SequenceStep::RunBursts(bursts) => {
+                let burst_period_us = self.on_time_us as u64 + self.off_time_ms as u64 * 1000;
+                self.step_duration_us = *bursts as u64 * burst_period_us;
+                self.start_firing(com);
+            },
+            SequenceStep::RunMs(duration) => {
+                self.step_duration_us = *duration as u64 * 1000;
+                self.start_firing(com);
+            },
+            SequenceStep::WaitMs(duration) => self.step_duration_us = *duration as u64 * 1000,
+            SequenceStep::RecordMaxCurrent => {
+                self.step_elapsed_us = 0;
+                com.outbox.push_back(ControllerMessage::GetStat(Statistic::MaxPrimaryCurrent));
+            },
+        }
+    }
+
+    fn advance(&mut self) {
+        self.step += 1;
+        self.step_entered = false;
+        self.step_elapsed_us = 0;
+        self.step_duration_us = 0;
+    }
+
+    fn start_firing(&mut self, com: &mut ComState<'_>) {
+        com.outbox.push_back(ControllerMessage::Run);
+        self.firing = true;
+        self.t_last_keepalive = self.t_elapsed;
+    }
+
+    fn stop_firing(&mut self, com: &mut ComState<'_>) {
+        if self.firing {
+            com.outbox.push_back(ControllerMessage::Stop);
+            self.firing = false;
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use alloc::vec::Vec;
+use qcw_com::{ControllerMessage, ParameterValue, RemoteMessage, RunMode, Statistic, StatisticValue};
+
+use crate::application::ComState;
+
+const KEEPALIVE_INTERVAL_US: u64 = 10_000;
+const STAT_REPLY_TIMEOUT_US: u64 = 500_000;
+pub const MAX_SWEEP_POINTS: usize = 128;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SweepParameter {
+    PhaseDelay,
+    StartupFrequency,
+    OnTime,
+    Power,
+}
+
+impl SweepParameter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SweepParameter::PhaseDelay => "Phase Delay",
+            SweepParameter::StartupFrequency => "Frequency",
+            SweepParameter::OnTime => "On Time",
+            SweepParameter::Power => "Power",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SweepParameter::PhaseDelay => "ns",
+            SweepParameter::StartupFrequency => "kHz",
+            SweepParameter::OnTime => "µs",
+            SweepParameter::Power => "%",
+        }
+    }
+
+    // (min, max) the parameter may be swept across
+    pub fn limits(&self) -> (i32, i32) {
+        match self {
+            SweepParameter::PhaseDelay => (-400, 400),
+            SweepParameter::StartupFrequency => (300, 700),
+            SweepParameter::OnTime => (0, 1000),
+            SweepParameter::Power => (0, 100),
+        }
+    }
+
+    // (start, end, step) a freshly selected parameter starts out with
+    pub fn default_range(&self) -> (i32, i32, i32) {
+        match self {
+            SweepParameter::PhaseDelay => (-400, 400, 25),
+            SweepParameter::StartupFrequency => (350, 500, 5),
+            SweepParameter::OnTime => (100, 500, 50),
+            SweepParameter::Power => (10, 50, 10),
+        }
+    }
+
+    // how far one encoder detent moves the start, end or step of a sweep
+    pub fn increment(&self) -> i32 {
+        match self {
+            SweepParameter::PhaseDelay => 5,
+            SweepParameter::StartupFrequency => 1,
+            SweepParameter::OnTime => 10,
+            SweepParameter::Power => 1,
+        }
+    }
+
+    pub fn parameter_value(&self, value: i32) -> ParameterValue {
+        match self {
+            SweepParameter::PhaseDelay => ParameterValue::DelayCompensationNS(value as i16),
+            SweepParameter::StartupFrequency => ParameterValue::StartupFrequencykHz(value as f32),
+            SweepParameter::OnTime => ParameterValue::OnTimeUs(value as u16),
+            SweepParameter::Power => ParameterValue::FlatPower(value as f32 / 100.0),
+        }
+    }
+
+    // the phase delay only matters when the controller follows the feedback signal
+    pub fn run_mode(&self) -> RunMode {
+        match self {
+            SweepParameter::PhaseDelay => RunMode::TestClosedLoop,
+            _ => RunMode::OpenLoop,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            SweepParameter::PhaseDelay => SweepParameter::StartupFrequency,
+            SweepParameter::StartupFrequency => SweepParameter::OnTime,
+            SweepParameter::OnTime => SweepParameter::Power,
+            SweepParameter::Power => SweepParameter::PhaseDelay,
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        match self {
+            SweepParameter::PhaseDelay => SweepParameter::Power,
+            SweepParameter::StartupFrequency => SweepParameter::PhaseDelay,
+            SweepParameter::OnTime => SweepParameter::StartupFrequency,
+            SweepParameter::Power => SweepParameter::OnTime,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SweepMetric {
+    HighestCurrent,
+    LowestCurrent,
+    HighestFeedbackFrequency,
+    LowestFeedbackFrequency,
+}
+
+impl SweepMetric {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SweepMetric::HighestCurrent => "Max I",
+            SweepMetric::LowestCurrent => "Min I",
+            SweepMetric::HighestFeedbackFrequency => "Max Fb",
+            SweepMetric::LowestFeedbackFrequency => "Min Fb",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SweepMetric::HighestCurrent | SweepMetric::LowestCurrent => "A",
+            SweepMetric::HighestFeedbackFrequency | SweepMetric::LowestFeedbackFrequency => "kHz",
+        }
+    }
+
+    // the statistic the metric looks at
+    pub fn measurement(&self, point: &SweepPoint) -> f32 {
+        match self {
+            SweepMetric::HighestCurrent | SweepMetric::LowestCurrent => point.max_current,
+            SweepMetric::HighestFeedbackFrequency | SweepMetric::LowestFeedbackFrequency => point.feedback_frequency,
+        }
+    }
+
+    // higher is better
+    pub fn score(&self, point: &SweepPoint) -> f32 {
+        match self {
+            SweepMetric::HighestCurrent | SweepMetric::HighestFeedbackFrequency => self.measurement(point),
+            SweepMetric::LowestCurrent | SweepMetric::LowestFeedbackFrequency => -self.measurement(point),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            SweepMetric::HighestCurrent => SweepMetric::LowestCurrent,
+            SweepMetric::LowestCurrent => SweepMetric::HighestFeedbackFrequency,
+            SweepMetric::HighestFeedbackFrequency => SweepMetric::LowestFeedbackFrequency,
+            SweepMetric::LowestFeedbackFrequency => SweepMetric::HighestCurrent,
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        match self {
+            SweepMetric::HighestCurrent => SweepMetric::LowestFeedbackFrequency,
+            SweepMetric::LowestCurrent => SweepMetric::HighestCurrent,
+            SweepMetric::HighestFeedbackFrequency => SweepMetric::LowestCurrent,
+            SweepMetric::LowestFeedbackFrequency => SweepMetric::HighestFeedbackFrequency,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SweepConfig {
+    pub parameter: SweepParameter,
+    pub start: i32,
+    pub end: i32,
+    pub step: i32,
+    pub dwell_ms: u32,
+    // burst settings sent before the first point, the swept parameter then overrides its own
+    pub on_time_us: u16,
+    pub off_time_ms: u16,
+    pub power: f32,
+}
+
+impl SweepConfig {
+    pub fn new(parameter: SweepParameter) -> Self {
+        let (start, end, step) = parameter.default_range();
+        Self {
+            parameter,
+            start,
+            end,
+            step,
+            dwell_ms: 500,
+            on_time_us: 100,
+            off_time_ms: 200,
+            power: 0.2,
+        }
+    }
+
+    // the step actually walked. a configured step that would take more than MAX_SWEEP_POINTS to
+    // get from start to end is widened, to a whole number of encoder increments
+    pub fn step(&self) -> i32 {
+        let span = (self.end - self.start).abs();
+        let increment = self.parameter.increment();
+        let min_step = (span + MAX_SWEEP_POINTS as i32 - 2) / (MAX_SWEEP_POINTS as i32 - 1);
+        let min_step = (min_step + increment - 1) / increment * increment;
+        self.step.max(min_step).max(1)
+    }
+
+    pub fn point_count(&self) -> usize {
+        ((self.end - self.start).abs() / self.step()) as usize + 1
+    }
+
+    // walks from start towards end, always including both ends
+    pub fn point_value(&self, index: usize) -> i32 {
+        let direction = if self.end >= self.start { 1 } else { -1 };
+        if index > 0 && index + 1 == self.point_count() {
+            self.end
+        } else {
+            self.start + direction * self.step() * index as i32
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SweepPoint {
+    pub value: i32,
+    pub max_current: f32,
+    pub feedback_frequency: f32,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SweepState {
+    Idle,
+    Running,
+    Finished,
+    Aborted(&'static str),
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum PointPhase {
+    Setup,
+    Firing,
+    Reading,
+}
+
+// fires the coil once per point for the dwell time, then collects the controller statistics for that point.
+// the coil is always stopped before the runner finishes or aborts
+pub struct SweepRunner {
+    config: SweepConfig,
+    state: SweepState,
+    phase: PointPhase,
+    point: usize,
+    t_elapsed: u64,
+    t_phase_start: u64,
+    t_last_keepalive: u64,
+    firing: bool,
+    pending_current: Option<f32>,
+    pending_frequency: Option<f32>,
+    points: Vec<SweepPoint>,
+}
+
+impl SweepRunner {
+    pub fn new(config: SweepConfig) -> Self {
+        Self {
+            config,
+            state: SweepState::Idle,
+            phase: PointPhase::Setup,
+            point: 0,
+            t_elapsed: 0,
+            t_phase_start: 0,
+            t_last_keepalive: 0,
+            firing: false,
+            pending_current: None,
+            pending_frequency: None,
+            points: Vec::new(),
+        }
+    }
+
+    pub fn start(&mut self, config: SweepConfig, com: &mut ComState<'_>) {
+        *self = Self::new(config);
+        self.points.reserve_exact(config.point_count());
+        self.state = SweepState::Running;
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::RunMode(config.parameter.run_mode())));
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::FlatPower(config.power)));
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::OnTimeUs(config.on_time_us)));
+        com.outbox.push_back(ControllerMessage::SetParam(ParameterValue::OffTimeMs(config.off_time_ms)));
+    }
+
+    pub fn abort(&mut self, com: &mut ComState<'_>, reason: &'static str) {
+        if self.state == SweepState::Running {
+            self.stop_firing(com);
+            self.state = SweepState::Aborted(reason);
+        }
+    }
+
+    pub fn state(&self) -> SweepState {
+        self.state
+    }
+
+    pub fn config(&self) -> &SweepConfig {
+        &self.config
+    }
+
+    pub fn points(&self) -> &[SweepPoint] {
+        &self.points
+    }
+
+    // (points measured, total points)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.points.len(), self.config.point_count())
+    }
+
+    pub fn optimum(&self, metric: SweepMetric) -> Option<SweepPoint> {
+        self.points.iter()
+            .copied()
+            .fold(None, |best: Option<SweepPoint>, point| match best {
+                Some(best) if metric.score(&best) >= metric.score(&point) => Some(best),
+                _ => Some(point),
+            })
+    }
+
+    pub fn handle_message(&mut self, message: &RemoteMessage) {
+        if self.state != SweepState::Running || self.phase != PointPhase::Reading {
+            return;
+        }
+        match message {
+            RemoteMessage::GetStatResult(StatisticValue::MaxPrimaryCurrentA(current)) => self.pending_current = Some(*current),
+            RemoteMessage::GetStatResult(StatisticValue::FeedbackFrequencykHz(frequency)) => self.pending_frequency = Some(*frequency),
+            _ => {}
+        }
+    }
+
+    pub fn update(&mut self, dt_micros: u64, com: &mut ComState<'_>) {
+        self.t_elapsed += dt_micros;
+        if self.state != SweepState::Running {
+            return;
+        }
+        let phase_elapsed = self.t_elapsed - self.t_phase_start;
+        match self.phase {
+            PointPhase::Setup => {
+                let value = self.config.point_value(self.point);
+                com.outbox.push_back(ControllerMessage::SetParam(self.config.parameter.parameter_value(value)));
+                com.outbox.push_back(ControllerMessage::ResetStats);
+                com.outbox.push_back(ControllerMessage::Run);
+                self.firing = true;
+                self.t_last_keepalive = self.t_elapsed;
+                self.enter_phase(PointPhase::Firing);
+            },
+            PointPhase::Firing => {
+                if phase_elapsed >= self.config.dwell_ms as u64 * 1000 {
+                    self.stop_firing(com);
+                    self.pending_current = None;
+                    self.pending_frequency = None;
+                    com.outbox.push_back(ControllerMessage::GetStat(Statistic::MaxPrimaryCurrent));
+                    com.outbox.push_back(ControllerMessage::GetStat(Statistic::FeedbackFrequency));
+                    self.enter_phase(PointPhase::Reading);
+                }
+            },
+            PointPhase::Reading => {
+                if let (Some(max_current), Some(feedback_frequency)) = (self.pending_current, self.pending_frequency) {
+                    self.points.push(SweepPoint {
+                        value: self.config.point_value(self.point),
+                        max_current,
+                        feedback_frequency,
+                    });
+                    self.point += 1;
+                    if self.point >= self.config.point_count() {
+                        self.state = SweepState::Finished;
+                    } else {
+                        self.enter_phase(PointPhase::Setup);
+                    }
+                } else if phase_elapsed >= STAT_REPLY_TIMEOUT_US {
+                    self.abort(com, "No reply from controller");
+                }
+            },
+        }
+
+        if self.firing && (self.t_elapsed - self.t_last_keepalive) >= KEEPALIVE_INTERVAL_US {
+            com.outbox.push_back(ControllerMessage::KeepAlive);
+            self.t_last_keepalive = self.t_elapsed;
+        }
+    }
+
+    fn enter_phase(&mut self, phase: PointPhase) {
+        self.phase = phase;
+        self.t_phase_start = self.t_elapsed;
+    }
+
+    fn stop_firing(&mut self, com: &mut ComState<'_>) {
+        if self.firing {
+            com.outbox.push_back(ControllerMessage::Stop);
+            self.firing = false;
+        }
+    }
+}
+
+
+// the interior point where the measurement changes least between its neighbours,
+// for settings where a steady reading matters more than an extreme one
+pub fn most_stable_point(points: &[SweepPoint], measurement: impl Fn(&SweepPoint) -> f32) -> Option<SweepPoint> {
+    if points.len() < 3 {
+        return points.first().copied();
+    }
+    points.windows(3)
+        .map(|window| (window[1], libm::fabsf(measurement(&window[2]) - measurement(&window[0]))))
+        .fold(None, |best: Option<(SweepPoint, f32)>, (point, variation)| match best {
+            Some((_, best_variation)) if best_variation <= variation => best,
+            _ => Some((point, variation)),
+        })
+        .map(|(point, _)| point)
+}
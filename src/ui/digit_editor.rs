@@ -0,0 +1,157 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::application::InputState;
+use crate::gfx::{draw_target::DrawTarget, fonts::BASIC_5PX, primitives::*};
+
+use super::numeric_field::format_fixed;
+use super::{LINE_HEIGHT, TEXT_BASELINE};
+
+// room for each digit, wide enough for the widest numeral plus a pixel either side
+const DIGIT_PITCH: isize = 6;
+
+pub enum DigitEditResult {
+    Editing,
+    Done(i32),
+    Cancelled,
+}
+
+// edits a fixed point value one digit at a time
+pub struct DigitEditor {
+    // most significant first, without the sign
+    digits: Vec<u8>,
+    negative: bool,
+    // the sign takes up the first position when the range goes below zero
+    signed: bool,
+    cursor: usize,
+    min: i32,
+    max: i32,
+    decimals: u8,
+    unit: &'static str,
+    error: Option<String>,
+}
+
+impl DigitEditor {
+    pub fn new(value: i32, min: i32, max: i32, decimals: u8, unit: &'static str) -> Self {
+        let largest = min.unsigned_abs().max(max.unsigned_abs());
+        let mut count = 1;
+        while 10u32.pow(count) <= largest && count < 9 {
+            count += 1;
+        }
+        let count = (count as usize).max(decimals as usize + 1);
+
+        let mut editor = Self {
+            digits: alloc::vec![0; count],
+            negative: false,
+            signed: min < 0,
+            cursor: 0,
+            min,
+            max,
+            decimals,
+            unit,
+            error: None,
+        };
+        editor.set_value(value.clamp(min, max));
+        editor
+    }
+
+    fn set_value(&mut self, value: i32) {
+        self.negative = value < 0;
+        let mut magnitude = value.unsigned_abs();
+        for digit in self.digits.iter_mut().rev() {
+            *digit = (magnitude % 10) as u8;
+            magnitude /= 10;
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        let magnitude = self.digits.iter().fold(0i32, |value, digit| value * 10 + *digit as i32);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    fn positions(&self) -> usize {
+        self.digits.len() + self.signed as usize
+    }
+
+    pub fn button_labels(&self) -> [&'static str; 3] {
+        [if self.cursor == 0 { "Cancel" } else { "<" }, ">", "OK"]
+    }
+
+    pub fn update(&mut self, input_state: &InputState) -> DigitEditResult {
+        let encoder = &input_state.encoder;
+        if encoder.delta != 0 {
+            self.error = None;
+            if self.signed && self.cursor == 0 {
+                if encoder.delta % 2 != 0 {
+                    self.negative = !self.negative;
+                }
+            } else {
+                let digit = &mut self.digits[self.cursor - self.signed as usize];
+                *digit = (*digit as i32 + encoder.delta).rem_euclid(10) as u8;
+            }
+        }
+        if encoder.button.released || input_state.buttons[1].released {
+            self.cursor = (self.cursor + 1) % self.positions();
+        }
+        if input_state.buttons[0].released {
+            if self.cursor == 0 {
+                return DigitEditResult::Cancelled;
+            }
+            self.cursor -= 1;
+        }
+        if input_state.buttons[2].released {
+            let value = self.value();
+            if value < self.min {
+                self.error = Some(format!("min {}", format_fixed(self.min, self.decimals, self.unit)));
+            } else if value > self.max {
+                self.error = Some(format!("max {}", format_fixed(self.max, self.decimals, self.unit)));
+            } else {
+                return DigitEditResult::Done(value);
+            }
+        }
+        DigitEditResult::Editing
+    }
+
+    // digits and unit, with the range or error below
+    pub fn size(&self) -> (isize, isize) {
+        let point_width = if self.decimals > 0 { 2 } else { 0 };
+        let unit_width = if self.unit.is_empty() { 0 } else { BASIC_5PX.get_text_width(self.unit) + 2 };
+        (self.positions() as isize * DIGIT_PITCH + point_width + unit_width, LINE_HEIGHT * 2 + 2)
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize)) {
+        let baseline = position.1 + TEXT_BASELINE + 1;
+        let mut x = position.0;
+        for index in 0..self.positions() {
+            let text = if self.signed && index == 0 {
+                String::from(if self.negative { "-" } else { "+" })
+            } else {
+                format!("{}", self.digits[index - self.signed as usize])
+            };
+            let selected = index == self.cursor;
+            if selected {
+                draw_filled_rect(target, (x, position.1), (x + DIGIT_PITCH - 2, position.1 + LINE_HEIGHT), true);
+            }
+            let glyph_x = x + (DIGIT_PITCH - 1 - BASIC_5PX.get_text_width(&text)) / 2;
+            BASIC_5PX.draw_text_line(target, (glyph_x, baseline), &text, !selected);
+            x += DIGIT_PITCH;
+
+            // the decimal point sits in the gap after the last whole digit
+            let whole_digits = self.digits.len() - self.decimals as usize;
+            if self.decimals > 0 && index + 1 == whole_digits + self.signed as usize {
+                draw_filled_rect(target, (x, baseline), (x, baseline), true);
+                x += 2;
+            }
+        }
+        if !self.unit.is_empty() {
+            BASIC_5PX.draw_text_line(target, (x + 2, baseline), self.unit, true);
+        }
+
+        let note = match &self.error {
+            Some(error) => error.clone(),
+            None => format!("{} to {}", format_fixed(self.min, self.decimals, ""), format_fixed(self.max, self.decimals, self.unit)),
+        };
+        BASIC_5PX.draw_text_line(target, (position.0, position.1 + LINE_HEIGHT + 2 + TEXT_BASELINE + 1), &note, true);
+    }
+}
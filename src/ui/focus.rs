@@ -0,0 +1,126 @@
+use crate::application::EncoderState;
+
+use super::Focus;
+
+// holding the encoder button this long while editing throws the edit away
+const CANCEL_HOLD_US: u64 = 600_000;
+
+// what the focus manager needs from an editable widget
+pub trait Focusable {
+    fn set_focus(&mut self, focus: Focus);
+    fn begin_edit(&mut self);
+    fn cancel_edit(&mut self);
+    // returns true when the value changed
+    fn edit(&mut self, encoder: &EncoderState) -> bool;
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FocusEvent {
+    None,
+    Moved(usize),
+    BeginEdit(usize),
+    // the value of the widget being edited changed
+    Changed(usize),
+    Commit(usize),
+    // the widget has already gone back to its value from before the edit
+    Cancel(usize),
+}
+
+// click to edit, click again to commit, hold to cancel
+pub struct FocusManager {
+    index: usize,
+    editing: bool,
+    hold_us: u64,
+    // the hold that cancelled an edit is still going, its release mustn't start a new one
+    cancel_held: bool,
+}
+
+impl Default for FocusManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FocusManager {
+    pub const fn new() -> Self {
+        Self {
+            index: 0,
+            editing: false,
+            hold_us: 0,
+            cancel_held: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn is_editing(&self, index: usize) -> bool {
+        self.editing && self.index == index
+    }
+
+    pub fn focus(&self, index: usize) -> Focus {
+        match (self.index == index, self.editing) {
+            (false, _) => Focus::None,
+            (true, false) => Focus::Focused,
+            (true, true) => Focus::Editing,
+        }
+    }
+
+    pub fn update(&mut self, dt_micros: u64, encoder: &EncoderState, widgets: &mut [&mut dyn Focusable]) -> FocusEvent {
+        if widgets.is_empty() {
+            return FocusEvent::None;
+        }
+        self.index = self.index.min(widgets.len() - 1);
+        let mut event = FocusEvent::None;
+
+        if encoder.button.pressed {
+            self.hold_us = 0;
+        } else if encoder.button.down {
+            self.hold_us += dt_micros;
+        }
+        if self.cancel_held {
+            if encoder.button.released {
+                self.cancel_held = false;
+                self.hold_us = 0;
+            }
+        } else if self.editing {
+            if encoder.button.down && self.hold_us >= CANCEL_HOLD_US {
+                widgets[self.index].cancel_edit();
+                self.editing = false;
+                self.cancel_held = true;
+                event = FocusEvent::Cancel(self.index);
+            } else if encoder.button.released {
+                self.editing = false;
+                self.hold_us = 0;
+                event = FocusEvent::Commit(self.index);
+            } else if widgets[self.index].edit(encoder) {
+                event = FocusEvent::Changed(self.index);
+            }
+        } else if encoder.button.released {
+            self.hold_us = 0;
+            self.editing = true;
+            widgets[self.index].begin_edit();
+            event = FocusEvent::BeginEdit(self.index);
+        } else if encoder.delta != 0 {
+            let index = (self.index as i32 + encoder.delta).clamp(0, widgets.len() as i32 - 1) as usize;
+            if index != self.index {
+                self.index = index;
+                event = FocusEvent::Moved(index);
+            }
+        }
+
+        for (i, widget) in widgets.iter_mut().enumerate() {
+            widget.set_focus(self.focus(i));
+        }
+        event
+    }
+}
@@ -0,0 +1,44 @@
+use core::fmt::{self, Write};
+
+use alloc::string::String;
+
+use crate::gfx::{draw_target::DrawTarget, fonts::BASIC_5PX};
+
+use super::{LINE_HEIGHT, TEXT_BASELINE};
+
+pub struct Label {
+    text: String,
+    pub inverted: bool,
+}
+
+impl Label {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: String::from(text),
+            inverted: false,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        self.text.push_str(text);
+    }
+
+    // reuses the label's buffer, so a label updated every frame doesn't churn the heap
+    pub fn set_fmt(&mut self, args: fmt::Arguments<'_>) {
+        self.text.clear();
+        _ = self.text.write_fmt(args);
+    }
+
+    pub fn size(&self) -> (isize, isize) {
+        (BASIC_5PX.get_text_width(&self.text), LINE_HEIGHT)
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize)) {
+        BASIC_5PX.draw_text_line(target, (position.0, position.1 + TEXT_BASELINE), &self.text, !self.inverted);
+    }
+}
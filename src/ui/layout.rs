@@ -0,0 +1,171 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+pub use crate::gfx::bitmap_font::Align;
+use crate::gfx::bitmap_font::BitmapFont;
+use crate::gfx::fonts::{largest_fitting, UI_7PX};
+use crate::gfx::draw_target::{DrawTarget, MaskedDrawTarget, RectMask, TranslatedDrawTarget, _DTRef, _Maskable, _Translatable};
+
+const ELLIPSIS: &str = "..";
+
+// how much of a row or column a cell takes up
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Length {
+    Fixed(isize),
+    // a share of whatever the fixed cells leave over, by weight
+    Fill(u8),
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Rect {
+    pub position: (isize, isize),
+    pub size: (isize, isize),
+}
+
+impl Rect {
+    pub const fn new(position: (isize, isize), size: (isize, isize)) -> Self {
+        Self { position, size }
+    }
+
+    // both corners inclusive, the way the primitives take them
+    pub const fn from_corners(upper_left: (isize, isize), lower_right: (isize, isize)) -> Self {
+        Self {
+            position: upper_left,
+            size: (lower_right.0 - upper_left.0 + 1, lower_right.1 - upper_left.1 + 1),
+        }
+    }
+
+    pub const fn right(&self) -> isize {
+        self.position.0 + self.size.0 - 1
+    }
+
+    pub const fn bottom(&self) -> isize {
+        self.position.1 + self.size.1 - 1
+    }
+
+    pub const fn lower_right(&self) -> (isize, isize) {
+        (self.right(), self.bottom())
+    }
+
+    pub fn inset(&self, padding: isize) -> Self {
+        Self {
+            position: (self.position.0 + padding, self.position.1 + padding),
+            size: ((self.size.0 - padding * 2).max(0), (self.size.1 - padding * 2).max(0)),
+        }
+    }
+
+    // where something of `size` goes to sit in this rect with the given alignment
+    pub fn align(&self, size: (isize, isize), horizontal: Align, vertical: Align) -> (isize, isize) {
+        (
+            self.position.0 + horizontal.offset(self.size.0, size.0),
+            self.position.1 + vertical.offset(self.size.1, size.1),
+        )
+    }
+
+    // splits the rect top to bottom
+    pub fn rows<const N: usize>(&self, lengths: [Length; N], spacing: isize) -> [Rect; N] {
+        let extents = split(self.size.1, &lengths, spacing);
+        core::array::from_fn(|i| Rect {
+            position: (self.position.0, self.position.1 + extents[i].0),
+            size: (self.size.0, extents[i].1),
+        })
+    }
+
+    // splits the rect left to right
+    pub fn columns<const N: usize>(&self, lengths: [Length; N], spacing: isize) -> [Rect; N] {
+        let extents = split(self.size.0, &lengths, spacing);
+        core::array::from_fn(|i| Rect {
+            position: (self.position.0 + extents[i].0, self.position.1),
+            size: (extents[i].1, self.size.1),
+        })
+    }
+
+    // a draw target with its origin at the rect's upper left corner that can't draw outside it
+    pub fn clip<Target: DrawTarget>(&self, target: Target) -> TranslatedDrawTarget<MaskedDrawTarget<Target, RectMask>> {
+        target
+            .mask(RectMask {
+                upper_left: self.position,
+                lower_right: self.lower_right(),
+            })
+            .translate(self.position)
+    }
+}
+
+// offset and length of each cell along one axis
+fn split<const N: usize>(available: isize, lengths: &[Length; N], spacing: isize) -> [(isize, isize); N] {
+    let fixed: isize = lengths.iter().map(|length| match length {
+        Length::Fixed(size) => *size,
+        Length::Fill(_) => 0,
+    }).sum();
+    let weights: isize = lengths.iter().map(|length| match length {
+        Length::Fixed(_) => 0,
+        Length::Fill(weight) => *weight as isize,
+    }).sum();
+    let spare = (available - fixed - spacing * (N as isize - 1).max(0)).max(0);
+
+    let mut extents = [(0, 0); N];
+    let mut offset = 0;
+    let mut weight_so_far = 0;
+    for (i, length) in lengths.iter().enumerate() {
+        let size = match length {
+            Length::Fixed(size) => *size,
+            // sized from running totals so rounding never leaves a gap at the end
+            Length::Fill(weight) => {
+                let start = if weights > 0 { spare * weight_so_far / weights } else { 0 };
+                weight_so_far += *weight as isize;
+                let end = if weights > 0 { spare * weight_so_far / weights } else { 0 };
+                end - start
+            },
+        };
+        extents[i] = (offset, size);
+        offset += size + spacing;
+    }
+    extents
+}
+
+// the text as is if it fits, otherwise as much of it as fits followed by an ellipsis
+pub fn ellipsize<'a>(font: &BitmapFont, text: &'a str, max_width: isize) -> Cow<'a, str> {
+    if font.get_text_width(text) <= max_width {
+        return Cow::Borrowed(text);
+    }
+    let ellipsis_width = font.get_text_width(ELLIPSIS) + 1;
+    let mut width = 0;
+    let mut end = 0;
+    for (index, c) in text.char_indices() {
+        let advance = font.glyph(c).map(|glyph| glyph.advance() as isize).unwrap_or(0);
+        if width + advance + ellipsis_width > max_width + 1 {
+            break;
+        }
+        width += advance;
+        end = index + c.len_utf8();
+    }
+    let mut truncated = String::from(text[..end].trim_end());
+    truncated.push_str(ELLIPSIS);
+    Cow::Owned(truncated)
+}
+
+// one line of text aligned in a rect, cut short with an ellipsis
+pub fn draw_text_in<Target: DrawTarget>(target: &mut Target, font: &BitmapFont, rect: Rect, text: &str, align: Align, color: bool) {
+    let text = ellipsize(font, text, rect.size.0);
+    let width = font.get_text_width(&text);
+    let x = align.offset(rect.size.0, width);
+    let y = Align::Center.offset(rect.size.1, font.line_height as isize) + font.baseline();
+    let mut clipped = rect.clip(target.dt_ref());
+    font.draw_text_line(&mut clipped, (x, y), &text, color);
+}
+
+// value as big as fits, with its unit small after it
+pub fn draw_readout<Target: DrawTarget>(target: &mut Target, rect: Rect, value: &str, unit: &str, align: Align, color: bool) {
+    let unit_font = &UI_7PX;
+    let unit_width = if unit.is_empty() { 0 } else { unit_font.get_text_width(unit) + 3 };
+    let font = largest_fitting(value, (rect.size.0 - unit_width, rect.size.1));
+    let value_width = font.get_text_width(value);
+    let x = align.offset(rect.size.0, value_width + unit_width);
+    let baseline = Align::Center.offset(rect.size.1, font.line_height as isize) + font.baseline();
+    let mut clipped = rect.clip(target.dt_ref());
+    font.draw_text_line(&mut clipped, (x, baseline), value, color);
+    if !unit.is_empty() {
+        unit_font.draw_text_line(&mut clipped, (x + value_width + 3, baseline), unit, color);
+    }
+}
+
@@ -1,6 +1,6 @@
-use alloc::vec::Vec;
+use libm::roundf;
 
-use crate::{application::EncoderState, gfx::{draw_target::{DrawTarget, MaskedDrawTarget, RectMask, TranslatedDrawTarget, _DTRef, _Maskable, _Translatable}, fonts::BASIC_5PX, primitives::*}, mn12864k::Framebuffer};
+use crate::{animation::{Easing, Tween}, application::EncoderState, gfx::{dirty::DirtyRegion, draw_target::{RectMask, _DTRef, _Maskable, _Translatable}, fonts::BASIC_5PX, primitives::*}, mn12864k::Framebuffer};
 
 pub struct ListPicker<T: Clone, const N: usize> {
     items: [(T, &'static str); N],
@@ -10,8 +10,12 @@ pub struct ListPicker<T: Clone, const N: usize> {
     width: usize,
     height: usize,
     scroll_offset: isize,
+    // where the list is drawn, catching up with scroll_offset
+    scroll: Tween,
 }
 
+const SCROLL_TIME_US: u64 = 120_000;
+
 impl<T: Clone, const N: usize> ListPicker<T, N> {
     pub fn new(items: [(T, &'static str); N], position: (isize, isize), width: usize, height: usize) -> Self {
         Self {
@@ -22,12 +26,37 @@ impl<T: Clone, const N: usize> ListPicker<T, N> {
             height,
             selected: false,
             scroll_offset: 0,
+            scroll: Tween::new(0.0, SCROLL_TIME_US, Easing::EaseOut),
         }
     }
 
     pub fn reset(&mut self) {
         self.index = 0;
         self.selected = false;
+        self.scroll_to_index();
+        self.scroll.set(self.scroll_offset as f32);
+    }
+
+    // scrolls just far enough to bring the selected item into view
+    fn scroll_to_index(&mut self) {
+        let min_selected_y = self.scroll_offset + self.index as isize * 10;
+        let max_selected_y = self.scroll_offset + self.index as isize * 10 + 10;
+
+        if min_selected_y < 0 {
+            self.scroll_offset += -min_selected_y;
+        } else if max_selected_y > self.height as isize {
+            self.scroll_offset -= max_selected_y - self.height as isize;
+        }
+        self.scroll.retarget(self.scroll_offset as f32);
+    }
+
+    // scrolls towards the selection, call every update
+    pub fn animate(&mut self, dt_micros: u64, dirty: &mut DirtyRegion) {
+        if !self.scroll.finished() {
+            let lower_right = (self.position.0 + self.width as isize, self.position.1 + self.height as isize);
+            dirty.mark(self.position, lower_right);
+        }
+        self.scroll.update(dt_micros);
     }
 
     pub fn update(&mut self, encoder: &EncoderState) -> Option<T> {
@@ -52,22 +81,14 @@ impl<T: Clone, const N: usize> ListPicker<T, N> {
                 }
             }
         }
+        self.scroll_to_index();
         selected
     }
 
     pub fn render(&mut self, framebuffer: &mut Framebuffer) {
-        let total_height = N * 11;
-
-        let min_selected_y = self.scroll_offset + self.index as isize * 10;
-        let max_selected_y = self.scroll_offset + self.index as isize * 10 + 10;
-
-        if min_selected_y < 0 {
-            self.scroll_offset += -min_selected_y;
-        } else if max_selected_y > self.height as isize {
-            self.scroll_offset -= max_selected_y - self.height as isize;
-        }
+        let scroll_offset = roundf(self.scroll.value()) as isize;
 
-        let mut mask = RectMask {
+        let mask = RectMask {
             upper_left: (0, 0),
             lower_right: (self.width as isize, self.height as isize)
         };
@@ -81,7 +102,7 @@ impl<T: Clone, const N: usize> ListPicker<T, N> {
 
         let mut target = translated_framebuffer
             .mask(mask)
-            .translate((0, self.scroll_offset));
+            .translate((0, scroll_offset));
 
         for i in 1..self.items.len() {
             draw_hline(&mut target, 0, (self.width - 1) as isize, i as isize * 10, true);
@@ -1,3 +1,62 @@
+use crate::gfx::draw_target::DrawTarget;
+use crate::gfx::primitives::*;
+
 mod list_picker;
+mod label;
+mod numeric_field;
+mod toggle;
+mod slider;
+mod progress_bar;
+mod value_table;
+mod layout;
+mod focus;
+mod text_entry;
+mod digit_editor;
 
 pub use list_picker::ListPicker;
+pub use label::Label;
+pub use numeric_field::{format_fixed, NumericField};
+pub use toggle::Toggle;
+pub use slider::{Gauge, Slider};
+pub use progress_bar::ProgressBar;
+pub use value_table::ValueTable;
+pub use focus::{FocusEvent, FocusManager, Focusable};
+pub use text_entry::{TextEntry, TextEntryResult, TEXT_ENTRY_HEIGHT};
+pub use digit_editor::{DigitEditResult, DigitEditor};
+pub use layout::{draw_readout, draw_text_in, ellipsize, Align, Length, Rect};
+
+// widgets are placed by their upper left corner
+pub const LINE_HEIGHT: isize = 8;
+pub const TEXT_BASELINE: isize = 5;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Focus {
+    None,
+    // the encoder would act on this widget if its button was pressed
+    Focused,
+    // the encoder changes the widget's value
+    Editing,
+}
+
+impl Focus {
+    pub fn editing(&self) -> bool {
+        *self == Focus::Editing
+    }
+}
+
+// outline while focused, inverted while editing. returns the content color
+pub fn draw_focus<Target: DrawTarget>(target: &mut Target, focus: Focus, position: (isize, isize), size: (isize, isize)) -> bool {
+    let upper_left = (position.0 - 2, position.1 - 1);
+    let lower_right = (position.0 + size.0 + 1, position.1 + size.1);
+    match focus {
+        Focus::None => true,
+        Focus::Focused => {
+            draw_rect(target, upper_left, lower_right, true);
+            true
+        },
+        Focus::Editing => {
+            draw_filled_rect(target, upper_left, lower_right, true);
+            false
+        },
+    }
+}
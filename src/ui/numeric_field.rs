@@ -0,0 +1,132 @@
+use alloc::format;
+use alloc::string::String;
+
+use crate::application::EncoderState;
+use crate::gfx::{draw_target::DrawTarget, fonts::BASIC_5PX};
+
+use super::{draw_focus, Focus, Focusable, LINE_HEIGHT, TEXT_BASELINE};
+
+// integer value with implied decimal places, so 1234 with 2 decimals reads 12.34
+pub struct NumericField {
+    value: i32,
+    min: i32,
+    max: i32,
+    step: i32,
+    decimals: u8,
+    unit: &'static str,
+    pub focus: Focus,
+    edit_start: i32,
+}
+
+impl NumericField {
+    pub fn new(value: i32, min: i32, max: i32, step: i32, decimals: u8, unit: &'static str) -> Self {
+        Self {
+            value: value.clamp(min, max),
+            min,
+            max,
+            step,
+            decimals,
+            unit,
+            focus: Focus::None,
+            edit_start: 0,
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: i32) {
+        self.value = value.clamp(self.min, self.max);
+    }
+
+    pub fn value_f32(&self) -> f32 {
+        self.value as f32 / libm::powf(10.0, self.decimals as f32)
+    }
+
+    pub fn set_range(&mut self, min: i32, max: i32) {
+        self.min = min;
+        self.max = max;
+        self.value = self.value.clamp(min, max);
+    }
+
+    // returns true when the value changed
+    pub fn update(&mut self, encoder: &EncoderState) -> bool {
+        if !self.focus.editing() || encoder.delta == 0 {
+            return false;
+        }
+        let previous = self.value;
+        self.value = (self.value + encoder.delta * self.step).clamp(self.min, self.max);
+        self.value != previous
+    }
+
+    fn format_value(&self, value: i32) -> String {
+        format_fixed(value, self.decimals, self.unit)
+    }
+
+    pub fn range(&self) -> (i32, i32) {
+        (self.min, self.max)
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    pub fn unit(&self) -> &'static str {
+        self.unit
+    }
+
+    pub fn text(&self) -> String {
+        self.format_value(self.value)
+    }
+
+    // wide enough for any value in range, so the field doesn't change size while it's edited
+    pub fn size(&self) -> (isize, isize) {
+        let width = BASIC_5PX.get_text_width(&self.format_value(self.min))
+            .max(BASIC_5PX.get_text_width(&self.format_value(self.max)));
+        (width, LINE_HEIGHT)
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize)) {
+        let size = self.size();
+        let color = draw_focus(target, self.focus, position, size);
+        let text = self.text();
+        let x = position.0 + size.0 - BASIC_5PX.get_text_width(&text);
+        BASIC_5PX.draw_text_line(target, (x, position.1 + TEXT_BASELINE), &text, color);
+    }
+}
+
+// a fixed point value as text, 1234 with 2 decimals and a unit of "V" reads "12.34 V"
+pub fn format_fixed(value: i32, decimals: u8, unit: &'static str) -> String {
+    let scale = 10u32.pow(decimals as u32);
+    let sign = if value < 0 { "-" } else { "" };
+    let magnitude = value.unsigned_abs();
+    let text = if decimals == 0 {
+        format!("{}{}", sign, magnitude)
+    } else {
+        format!("{}{}.{:0width$}", sign, magnitude / scale, magnitude % scale, width = decimals as usize)
+    };
+    if unit.is_empty() {
+        text
+    } else {
+        format!("{} {}", text, unit)
+    }
+}
+
+impl Focusable for NumericField {
+    fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+    }
+
+    fn begin_edit(&mut self) {
+        self.edit_start = self.value;
+    }
+
+    fn cancel_edit(&mut self) {
+        self.value = self.edit_start;
+    }
+
+    fn edit(&mut self, encoder: &EncoderState) -> bool {
+        self.update(encoder)
+    }
+}
@@ -0,0 +1,33 @@
+use crate::gfx::{draw_target::DrawTarget, primitives::*};
+
+pub struct ProgressBar {
+    // 0 to 1
+    pub fraction: f32,
+    pub size: (isize, isize),
+}
+
+impl ProgressBar {
+    pub fn new(size: (isize, isize)) -> Self {
+        Self {
+            fraction: 0.0,
+            size,
+        }
+    }
+
+    pub fn set_progress(&mut self, done: f32, total: f32) {
+        self.fraction = if total > 0.0 { (done / total).clamp(0.0, 1.0) } else { 0.0 };
+    }
+
+    pub fn size(&self) -> (isize, isize) {
+        self.size
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize)) {
+        let lower_right = (position.0 + self.size.0 - 1, position.1 + self.size.1 - 1);
+        draw_rect(target, position, lower_right, true);
+        let filled = (self.fraction.clamp(0.0, 1.0) * (self.size.0 - 1) as f32) as isize;
+        if filled > 0 {
+            draw_filled_rect(target, position, (position.0 + filled, lower_right.1), true);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::application::EncoderState;
+use crate::gfx::{draw_target::DrawTarget, primitives::*};
+
+use super::{draw_focus, Focus, Focusable};
+
+// horizontal scale with end ticks, and a centre tick when the range spans zero
+fn draw_scale<Target: DrawTarget>(target: &mut Target, left: isize, right: isize, y: isize, zero_x: Option<isize>, color: bool) {
+    draw_hline(target, left, right, y, color);
+    draw_vline(target, left, y - 1, y + 1, color);
+    draw_vline(target, right, y - 1, y + 1, color);
+    if let Some(zero_x) = zero_x {
+        draw_vline(target, zero_x, y - 1, y + 1, color);
+    }
+}
+
+fn value_x(value: i32, min: i32, max: i32, left: isize, width: isize) -> isize {
+    if max <= min {
+        return left;
+    }
+    left + ((value.clamp(min, max) - min) as i64 * (width - 1) as i64 / (max - min) as i64) as isize
+}
+
+pub const SLIDER_HEIGHT: isize = 8;
+
+// a value picked along a scale, with an arrow cursor above it
+pub struct Slider {
+    value: i32,
+    min: i32,
+    max: i32,
+    step: i32,
+    width: isize,
+    pub focus: Focus,
+    edit_start: i32,
+}
+
+impl Slider {
+    pub fn new(value: i32, min: i32, max: i32, step: i32, width: isize) -> Self {
+        Self {
+            value: value.clamp(min, max),
+            min,
+            max,
+            step,
+            width,
+            focus: Focus::None,
+            edit_start: 0,
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: i32) {
+        self.value = value.clamp(self.min, self.max);
+    }
+
+    // returns true when the value changed
+    pub fn update(&mut self, encoder: &EncoderState) -> bool {
+        if !self.focus.editing() || encoder.delta == 0 {
+            return false;
+        }
+        let previous = self.value;
+        self.value = (self.value + encoder.delta * self.step).clamp(self.min, self.max);
+        self.value != previous
+    }
+
+    pub fn size(&self) -> (isize, isize) {
+        (self.width, SLIDER_HEIGHT)
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize)) {
+        let color = draw_focus(target, self.focus, position, self.size());
+        let left = position.0;
+        let right = position.0 + self.width - 1;
+        let scale_y = position.1 + SLIDER_HEIGHT - 2;
+        let zero_x = (self.min < 0 && self.max > 0).then(|| value_x(0, self.min, self.max, left, self.width));
+        draw_scale(target, left, right, scale_y, zero_x, color);
+
+        let cursor_x = value_x(self.value, self.min, self.max, left, self.width);
+        let cursor_top = position.1;
+        draw_line(target, (cursor_x - 2, cursor_top + 2), (cursor_x, cursor_top), color);
+        draw_line(target, (cursor_x + 2, cursor_top + 2), (cursor_x, cursor_top), color);
+        draw_vline(target, cursor_x, cursor_top, scale_y, color);
+    }
+}
+
+// read only bar meter. fills from zero, or from the low end of the range if it doesn't span zero
+pub struct Gauge {
+    value: f32,
+    min: f32,
+    max: f32,
+    size: (isize, isize),
+}
+
+impl Gauge {
+    pub fn new(min: f32, max: f32, size: (isize, isize)) -> Self {
+        Self {
+            value: min,
+            min,
+            max,
+            size,
+        }
+    }
+
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value;
+    }
+
+    pub fn size(&self) -> (isize, isize) {
+        self.size
+    }
+
+    fn x_of(&self, value: f32, left: isize) -> isize {
+        if self.max <= self.min {
+            return left;
+        }
+        let fraction = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+        left + (fraction * (self.size.0 - 1) as f32) as isize
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize)) {
+        let left = position.0;
+        let lower_right = (position.0 + self.size.0 - 1, position.1 + self.size.1 - 1);
+        draw_rect(target, position, lower_right, true);
+        let origin = if self.min < 0.0 && self.max > 0.0 { 0.0 } else { self.min };
+        let origin_x = self.x_of(origin, left);
+        let value_x = self.x_of(self.value, left);
+        if value_x != origin_x {
+            draw_filled_rect(target, (origin_x.min(value_x), position.1), (origin_x.max(value_x), lower_right.1), true);
+        }
+        if origin_x != left {
+            draw_vline(target, origin_x, position.1 - 1, lower_right.1 + 1, true);
+        }
+    }
+}
+
+impl Focusable for Slider {
+    fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+    }
+
+    fn begin_edit(&mut self) {
+        self.edit_start = self.value;
+    }
+
+    fn cancel_edit(&mut self) {
+        self.value = self.edit_start;
+    }
+
+    fn edit(&mut self, encoder: &EncoderState) -> bool {
+        self.update(encoder)
+    }
+}
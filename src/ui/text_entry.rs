@@ -0,0 +1,127 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::application::InputState;
+use crate::gfx::{bitmap_font::BitmapFont, draw_target::DrawTarget, primitives::*};
+
+use super::{LINE_HEIGHT, TEXT_BASELINE};
+
+// characters either side of the selected one on the wheel
+const WHEEL_SIDE: isize = 6;
+const WHEEL_PITCH: isize = 8;
+pub const TEXT_ENTRY_HEIGHT: isize = LINE_HEIGHT * 2 + 4;
+
+pub enum TextEntryResult {
+    Editing,
+    Done(String),
+    Cancelled,
+}
+
+// text entry, one character at a time off the encoder
+pub struct TextEntry {
+    font: &'static BitmapFont,
+    wheel: Vec<char>,
+    selected: usize,
+    shift: bool,
+    text: String,
+    max_len: usize,
+}
+
+impl TextEntry {
+    pub fn new(font: &'static BitmapFont, initial: &str, max_len: usize) -> Self {
+        let mut entry = Self {
+            font,
+            wheel: Vec::new(),
+            selected: 0,
+            shift: true,
+            text: String::from(initial),
+            max_len,
+        };
+        entry.build_wheel('A');
+        entry
+    }
+
+    // every character the font can draw, less the letters of the case shift isn't on
+    fn build_wheel(&mut self, keep: char) {
+        let shift = self.shift;
+        self.wheel = self.font.ranges.iter()
+            .flat_map(|range| (0..range.glyphs.len() as u32).filter_map(move |i| char::from_u32(range.start_char as u32 + i)))
+            .filter(|c| if shift { !c.is_ascii_lowercase() } else { !c.is_ascii_uppercase() })
+            .collect();
+        self.selected = self.wheel.iter().position(|c| *c == keep).unwrap_or(0);
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn button_labels(&self) -> [&'static str; 3] {
+        [if self.text.is_empty() { "Cancel" } else { "Del" }, if self.shift { "abc" } else { "ABC" }, "OK"]
+    }
+
+    fn selected_char(&self) -> char {
+        self.wheel.get(self.selected).copied().unwrap_or(' ')
+    }
+
+    pub fn update(&mut self, input_state: &InputState) -> TextEntryResult {
+        let encoder = &input_state.encoder;
+        if encoder.delta != 0 && !self.wheel.is_empty() {
+            self.selected = (self.selected as i32 + encoder.delta).rem_euclid(self.wheel.len() as i32) as usize;
+        }
+        if encoder.button.released && self.text.chars().count() < self.max_len {
+            self.text.push(self.selected_char());
+        }
+        if input_state.buttons[0].released && self.text.pop().is_none() {
+            return TextEntryResult::Cancelled;
+        }
+        if input_state.buttons[1].released {
+            self.shift = !self.shift;
+            let current = self.selected_char();
+            let keep = if self.shift { current.to_ascii_uppercase() } else { current.to_ascii_lowercase() };
+            self.build_wheel(keep);
+        }
+        if input_state.buttons[2].released {
+            return TextEntryResult::Done(core::mem::take(&mut self.text));
+        }
+        TextEntryResult::Editing
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize), width: isize) {
+        // the text so far, with the end kept in view and a cursor after it
+        let cursor_space = 5;
+        let mut shown: &str = &self.text;
+        while self.font.get_text_width(shown) > width - cursor_space - 2 {
+            let mut chars = shown.chars();
+            chars.next();
+            shown = chars.as_str();
+        }
+        let baseline = position.1 + TEXT_BASELINE;
+        self.font.draw_text_line(target, (position.0, baseline), shown, true);
+        let cursor_x = position.0 + self.font.get_text_width(shown) + 2;
+        draw_hline(target, cursor_x, cursor_x + cursor_space - 2, baseline + 1, true);
+        draw_hline(target, position.0, position.0 + width - 1, position.1 + LINE_HEIGHT + 1, true);
+
+        // the wheel, selected character boxed in the middle
+        let wheel_top = position.1 + LINE_HEIGHT + 4;
+        let center_x = position.0 + width / 2;
+        let wheel_baseline = wheel_top + TEXT_BASELINE;
+        let len = self.wheel.len() as isize;
+        if len == 0 {
+            return;
+        }
+        for offset in -WHEEL_SIDE..=WHEEL_SIDE {
+            let c = self.wheel[(self.selected as isize + offset).rem_euclid(len) as usize];
+            let mut buffer = [0u8; 4];
+            let glyph_text: &str = c.encode_utf8(&mut buffer);
+            let x = center_x + offset * WHEEL_PITCH - self.font.get_text_width(glyph_text) / 2;
+            if offset == 0 {
+                draw_filled_rect(target, (center_x - 4, wheel_top - 1), (center_x + 4, wheel_top + LINE_HEIGHT - 1), true);
+            }
+            self.font.draw_text_line(target, (x, wheel_baseline), glyph_text, offset != 0);
+        }
+        // a space draws nothing, so it gets a marker
+        if self.selected_char() == ' ' {
+            draw_hline(target, center_x - 2, center_x + 2, wheel_baseline, false);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::application::EncoderState;
+use crate::gfx::{draw_target::DrawTarget, fonts::BASIC_5PX, primitives::*};
+
+use super::{draw_focus, Focus, Focusable, LINE_HEIGHT, TEXT_BASELINE};
+
+const BOX_SIZE: isize = 5;
+
+// a checkbox with a label. turning the encoder while editing flips it
+pub struct Toggle {
+    pub value: bool,
+    pub label: &'static str,
+    pub focus: Focus,
+    edit_start: bool,
+}
+
+impl Toggle {
+    pub fn new(label: &'static str, value: bool) -> Self {
+        Self {
+            value,
+            label,
+            focus: Focus::None,
+            edit_start: false,
+        }
+    }
+
+    // returns true when the value changed
+    pub fn update(&mut self, encoder: &EncoderState) -> bool {
+        if self.focus.editing() && encoder.delta % 2 != 0 {
+            self.value = !self.value;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn size(&self) -> (isize, isize) {
+        let label_width = if self.label.is_empty() { 0 } else { BASIC_5PX.get_text_width(self.label) + 3 };
+        (BOX_SIZE + label_width, LINE_HEIGHT)
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize)) {
+        let color = draw_focus(target, self.focus, position, self.size());
+        let box_top = position.1 + TEXT_BASELINE - BOX_SIZE + 1;
+        draw_rect(target, (position.0, box_top), (position.0 + BOX_SIZE - 1, box_top + BOX_SIZE - 1), color);
+        if self.value {
+            draw_filled_rect(target, (position.0 + 1, box_top + 1), (position.0 + BOX_SIZE - 2, box_top + BOX_SIZE - 2), color);
+        }
+        if !self.label.is_empty() {
+            BASIC_5PX.draw_text_line(target, (position.0 + BOX_SIZE + 3, position.1 + TEXT_BASELINE), self.label, color);
+        }
+    }
+}
+
+impl Focusable for Toggle {
+    fn set_focus(&mut self, focus: Focus) {
+        self.focus = focus;
+    }
+
+    fn begin_edit(&mut self) {
+        self.edit_start = self.value;
+    }
+
+    fn cancel_edit(&mut self) {
+        self.value = self.edit_start;
+    }
+
+    fn edit(&mut self, encoder: &EncoderState) -> bool {
+        self.update(encoder)
+    }
+}
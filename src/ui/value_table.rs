@@ -0,0 +1,45 @@
+use core::fmt::{self, Write};
+
+use alloc::string::String;
+
+use crate::gfx::{draw_target::DrawTarget, fonts::BASIC_5PX};
+
+use super::{ellipsize, LINE_HEIGHT, TEXT_BASELINE};
+
+// labels down the left, values right aligned against the table's right edge
+pub struct ValueTable<const N: usize> {
+    labels: [&'static str; N],
+    values: [String; N],
+    width: isize,
+}
+
+impl<const N: usize> ValueTable<N> {
+    pub fn new(labels: [&'static str; N], width: isize) -> Self {
+        Self {
+            labels,
+            values: core::array::from_fn(|_| String::new()),
+            width,
+        }
+    }
+
+    pub fn set(&mut self, row: usize, args: fmt::Arguments<'_>) {
+        let value = &mut self.values[row];
+        value.clear();
+        _ = value.write_fmt(args);
+    }
+
+    pub fn size(&self) -> (isize, isize) {
+        (self.width, N as isize * LINE_HEIGHT)
+    }
+
+    pub fn render<Target: DrawTarget>(&self, target: &mut Target, position: (isize, isize)) {
+        for (row, (label, value)) in self.labels.iter().zip(self.values.iter()).enumerate() {
+            let baseline = position.1 + row as isize * LINE_HEIGHT + TEXT_BASELINE;
+            let value_x = position.0 + self.width - 1 - BASIC_5PX.get_text_width(value);
+            // the value wins when the two don't both fit
+            let label = ellipsize(&BASIC_5PX, label, value_x - position.0 - 4);
+            BASIC_5PX.draw_text_line(target, (position.0, baseline), &label, true);
+            BASIC_5PX.draw_text_line(target, (value_x, baseline), value, true);
+        }
+    }
+}
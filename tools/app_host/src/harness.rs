@@ -0,0 +1,159 @@
+use alloc::collections::vec_deque::VecDeque;
+use alloc::vec::Vec;
+
+use qcw_com::{ControllerMessage, RemoteMessage};
+
+use crate::app_views::{AppView, View};
+use crate::application::{AppSharedState, ButtonState, ComState, EncoderState, InputState};
+use crate::mn12864k::Framebuffer;
+
+// scripted input, built up from a frame with nothing touched
+pub trait ScriptedButton {
+    fn idle() -> Self;
+    fn pressed() -> Self;
+    fn held() -> Self;
+    fn released() -> Self;
+}
+
+impl ScriptedButton for ButtonState {
+    fn idle() -> Self {
+        Self {
+            down: false,
+            pressed: false,
+            released: false,
+        }
+    }
+
+    fn pressed() -> Self {
+        Self {
+            down: true,
+            pressed: true,
+            released: false,
+        }
+    }
+
+    fn held() -> Self {
+        Self {
+            down: true,
+            pressed: false,
+            released: false,
+        }
+    }
+
+    fn released() -> Self {
+        Self {
+            down: false,
+            pressed: false,
+            released: true,
+        }
+    }
+}
+
+pub trait ScriptedInput {
+    fn idle() -> Self;
+    fn with_encoder_delta(self, delta: i32) -> Self;
+    fn with_encoder_button(self, button: ButtonState) -> Self;
+    fn with_button(self, index: usize, button: ButtonState) -> Self;
+}
+
+impl ScriptedInput for InputState {
+    fn idle() -> Self {
+        Self {
+            encoder: EncoderState {
+                count: 0,
+                delta: 0,
+                button: ButtonState::idle(),
+            },
+            buttons: [ButtonState::idle(), ButtonState::idle(), ButtonState::idle()],
+        }
+    }
+
+    fn with_encoder_delta(mut self, delta: i32) -> Self {
+        self.encoder.count += delta;
+        self.encoder.delta = delta;
+        self
+    }
+
+    fn with_encoder_button(mut self, button: ButtonState) -> Self {
+        self.encoder.button = button;
+        self
+    }
+
+    fn with_button(mut self, index: usize, button: ButtonState) -> Self {
+        self.buttons[index] = button;
+        self
+    }
+}
+
+// drives a single view the way Application does, without the uart or the display, so a view's
+// message traffic can be checked against scripted input and controller replies
+pub struct ViewHarness<V: AppView> {
+    pub view: V,
+    pub shared_state: AppSharedState,
+    inbox: VecDeque<RemoteMessage>,
+    outbox: VecDeque<ControllerMessage>,
+    framebuffer: Framebuffer,
+}
+
+impl<V: AppView> ViewHarness<V> {
+    pub fn new(mut view: V) -> Self {
+        view.start();
+        Self {
+            view,
+            shared_state: AppSharedState::new(),
+            inbox: VecDeque::new(),
+            outbox: VecDeque::new(),
+            framebuffer: Framebuffer::new(),
+        }
+    }
+
+    // queues a reply for the view to see on its next step
+    pub fn inject(&mut self, message: RemoteMessage) {
+        self.inbox.push_back(message);
+    }
+
+    pub fn step(&mut self, dt_micros: u64, input_state: InputState) -> Option<View> {
+        self.shared_state.time_us += dt_micros;
+        let mut com = ComState {
+            inbox: &mut self.inbox,
+            outbox: &mut self.outbox,
+        };
+        let next_view = self.view.update(dt_micros, input_state, &mut com, &mut self.shared_state);
+        // rendering is part of every frame on the device, and some views advance state in it
+        self.framebuffer.clear(false);
+        self.view.render(&mut self.framebuffer, &mut self.shared_state);
+        next_view
+    }
+
+    pub fn step_idle(&mut self, dt_micros: u64, steps: usize) -> Option<View> {
+        let mut next_view = None;
+        for _ in 0..steps {
+            next_view = self.step(dt_micros, InputState::idle()).or(next_view);
+        }
+        next_view
+    }
+
+    // a full press and release of one of the frame buttons, over two steps
+    pub fn click_button(&mut self, dt_micros: u64, index: usize) -> Option<View> {
+        self.step(dt_micros, InputState::idle().with_button(index, ButtonState::pressed()));
+        self.step(dt_micros, InputState::idle().with_button(index, ButtonState::released()))
+    }
+
+    pub fn click_encoder(&mut self, dt_micros: u64) -> Option<View> {
+        self.step(dt_micros, InputState::idle().with_encoder_button(ButtonState::pressed()));
+        self.step(dt_micros, InputState::idle().with_encoder_button(ButtonState::released()))
+    }
+
+    pub fn turn_encoder(&mut self, dt_micros: u64, delta: i32) -> Option<View> {
+        self.step(dt_micros, InputState::idle().with_encoder_delta(delta))
+    }
+
+    // everything the view has queued since the last call, oldest first
+    pub fn take_sent(&mut self) -> Vec<ControllerMessage> {
+        self.outbox.drain(..).collect()
+    }
+
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+}
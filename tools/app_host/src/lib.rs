@@ -0,0 +1,55 @@
+//! The firmware's application logic built for the host, so it can be tested there:
+//!
+//! ```text
+//! cargo test --target <host triple>
+//! ```
+
+extern crate alloc;
+
+#[path = "../../../src/gfx/mod.rs"]
+pub mod gfx;
+
+#[path = "../../../src/ui/mod.rs"]
+pub mod ui;
+
+#[path = "../../../src/animation.rs"]
+pub mod animation;
+
+// only the framebuffer, the rest of the display driver needs the hardware
+#[path = "../../../src/mn12864k"]
+pub mod mn12864k {
+    mod framebuffer;
+    pub use framebuffer::*;
+}
+
+#[path = "../../../src/application.rs"]
+pub mod application;
+
+#[path = "../../../src/app_views/mod.rs"]
+pub mod app_views;
+
+#[path = "../../../src/event_log.rs"]
+pub mod event_log;
+
+#[path = "../../../src/envelope.rs"]
+pub mod envelope;
+
+#[path = "../../../src/limiter.rs"]
+pub mod limiter;
+
+#[path = "../../../src/run_time.rs"]
+pub mod run_time;
+
+#[path = "../../../src/sequence.rs"]
+pub mod sequence;
+
+#[path = "../../../src/sweep.rs"]
+pub mod sweep;
+
+#[path = "../../../src/music.rs"]
+pub mod music;
+
+#[path = "../../../src/parameters.rs"]
+pub mod parameters;
+
+pub mod harness;
@@ -0,0 +1,47 @@
+use app_host::envelope::*;
+
+#[test]
+fn envelopes_survive_a_round_trip_through_bytes() {
+    let mut envelopes = default_envelopes();
+    envelopes[1].set_name("Spark µ");
+    envelopes[2].move_power(1, -30);
+    let index = envelopes[3].insert_after(0).unwrap();
+    envelopes[3].move_time(index, 100);
+
+    let mut bytes = [0u8; PERSISTED_SIZE];
+    write_envelopes(&envelopes, &mut bytes);
+    let restored = read_envelopes(&bytes).unwrap();
+    for (restored, envelope) in restored.iter().zip(envelopes.iter()) {
+        assert_eq!(restored.name(), envelope.name());
+        assert_eq!(restored.breakpoints(), envelope.breakpoints());
+    }
+}
+
+#[test]
+fn records_without_envelopes_are_ignored() {
+    assert!(read_envelopes(&[0u8; PERSISTED_SIZE]).is_none());
+    assert!(read_envelopes(&[0xffu8; PERSISTED_SIZE]).is_none());
+    assert!(read_envelopes(&[]).is_none());
+}
+
+#[test]
+fn damaged_envelopes_are_ignored() {
+    let mut bytes = [0u8; PERSISTED_SIZE];
+    write_envelopes(&default_envelopes(), &mut bytes);
+    // the first envelope's first breakpoint power, pushed past 100%
+    let power_offset = 1 + 1 + NAME_CAPACITY + 1 + 2;
+    bytes[power_offset] = 101;
+    assert!(read_envelopes(&bytes).is_none());
+}
+
+#[test]
+fn names_are_cut_short_between_characters() {
+    let mut envelope = Envelope::new("", &[]);
+    envelope.set_name("Twelve chars");
+    assert_eq!(envelope.name(), "Twelve chars");
+    envelope.set_name("Thirteen char");
+    assert_eq!(envelope.name(), "Thirteen cha");
+    // µ takes two bytes and wouldn't fit in the last one
+    envelope.set_name("Eleven charµ");
+    assert_eq!(envelope.name(), "Eleven char");
+}
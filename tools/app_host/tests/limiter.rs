@@ -0,0 +1,156 @@
+use app_host::envelope::{default_envelopes, message_mean_power, Breakpoint, Envelope, TIME_SCALE};
+use app_host::limiter::*;
+use qcw_com::{ControllerMessage, ParameterValue, RemoteMessage};
+
+fn set(value: ParameterValue) -> ControllerMessage {
+    ControllerMessage::SetParam(value)
+}
+
+fn allowed(verdict: &Verdict) -> bool {
+    matches!(verdict, Verdict::Allow(_))
+}
+
+#[test]
+fn duty_cycle_is_on_time_over_the_whole_period() {
+    assert_eq!(duty_cycle(100, 1), 100.0 / 1100.0);
+    assert_eq!(duty_cycle(0, 10), 0.0);
+    assert_eq!(duty_cycle(0, 0), 0.0);
+}
+
+#[test]
+fn average_power_scales_the_duty_cycle() {
+    let parameters = FiringParameters { on_time_us: 100, off_time_ms: 1, power: 0.5 };
+    assert_eq!(parameters.average_power(), 0.5 * 100.0 / 1100.0);
+    let parameters = FiringParameters { power: 0.0, ..parameters };
+    assert_eq!(parameters.average_power(), 0.0);
+}
+
+#[test]
+fn max_on_time_keeps_within_both_limits() {
+    let config = LimiterConfig::new();
+    // at full power the average power limit is the tighter one
+    assert_eq!(max_on_time_us(&config, 10, 1.0), 309);
+    assert!(duty_cycle(309, 10) <= config.max_average_power);
+    assert!(duty_cycle(310, 10) > config.max_average_power);
+    // with no power only the duty limit is left
+    assert_eq!(max_on_time_us(&config, 10, 0.0), 526);
+    assert!(duty_cycle(526, 10) <= config.max_duty);
+    assert!(duty_cycle(527, 10) > config.max_duty);
+}
+
+#[test]
+fn min_off_time_keeps_within_both_limits() {
+    let config = LimiterConfig::new();
+    assert_eq!(min_off_time_ms(&config, 600, 1.0), 20);
+    assert!(duty_cycle(600, 20) <= config.max_average_power);
+    assert!(duty_cycle(600, 19) > config.max_average_power);
+    assert_eq!(min_off_time_ms(&config, 600, 0.0), 12);
+    assert!(duty_cycle(600, 12) <= config.max_duty);
+    assert!(duty_cycle(600, 11) > config.max_duty);
+}
+
+#[test]
+fn max_power_follows_the_duty_cycle() {
+    let config = LimiterConfig::new();
+    assert_eq!(max_power(&config, 0, 10), 1.0);
+    assert_eq!(max_power(&config, 600, 300), 1.0);
+    let limit = max_power(&config, 500, 10);
+    assert!((limit * duty_cycle(500, 10) - config.max_average_power).abs() < 1e-6);
+    // over the duty limit nothing is allowed at all
+    assert_eq!(max_power(&config, 600, 10), 0.0);
+}
+
+#[test]
+fn parameters_can_be_sent_in_any_order_before_run() {
+    let mut limiter = Limiter::new();
+    let verdict = limiter.check(set(ParameterValue::FlatPower(1.0)));
+    assert!(matches!(verdict, Verdict::Allow(ControllerMessage::SetParam(ParameterValue::FlatPower(power))) if power == 1.0));
+    assert!(allowed(&limiter.check(set(ParameterValue::OnTimeUs(600)))));
+    assert!(allowed(&limiter.check(set(ParameterValue::OffTimeMs(300)))));
+    assert!(allowed(&limiter.check(ControllerMessage::Run)));
+    assert!(limiter.running());
+    assert_eq!(limiter.parameters(), FiringParameters { on_time_us: 600, off_time_ms: 300, power: 1.0 });
+}
+
+#[test]
+fn run_is_refused_until_every_parameter_is_known() {
+    let mut limiter = Limiter::new();
+    assert!(limiter.complete_parameters().is_none());
+    assert!(matches!(limiter.check(ControllerMessage::Run), Verdict::Refused(_)));
+    limiter.check(set(ParameterValue::OnTimeUs(100)));
+    limiter.check(set(ParameterValue::OffTimeMs(100)));
+    assert!(matches!(limiter.check(ControllerMessage::Run), Verdict::Refused(_)));
+    assert!(!limiter.running());
+    // values reported by the controller count as well
+    limiter.observe_received(&RemoteMessage::GetParamResult(ParameterValue::FlatPower(0.5)));
+    assert!(allowed(&limiter.check(ControllerMessage::Run)));
+}
+
+#[test]
+fn run_over_the_limits_shortens_the_on_time_first() {
+    let mut limiter = Limiter::new();
+    limiter.check(set(ParameterValue::OnTimeUs(1000)));
+    limiter.check(set(ParameterValue::OffTimeMs(10)));
+    limiter.check(set(ParameterValue::FlatPower(1.0)));
+    let verdict = limiter.check(ControllerMessage::Run);
+    assert!(matches!(verdict, Verdict::Corrected {
+        correction: ControllerMessage::SetParam(ParameterValue::OnTimeUs(309)),
+        message: ControllerMessage::Run,
+    }));
+    assert!(limiter.running());
+    assert_eq!(limiter.parameters().on_time_us, 309);
+}
+
+#[test]
+fn run_over_the_limits_is_refused_in_refuse_mode() {
+    let mut limiter = Limiter::new();
+    limiter.config.mode = LimitMode::Refuse;
+    limiter.check(set(ParameterValue::OnTimeUs(600)));
+    limiter.check(set(ParameterValue::OffTimeMs(10)));
+    limiter.check(set(ParameterValue::FlatPower(0.1)));
+    assert!(matches!(limiter.check(ControllerMessage::Run), Verdict::Refused("duty cycle over limit")));
+    limiter.check(set(ParameterValue::OffTimeMs(15)));
+    limiter.check(set(ParameterValue::FlatPower(1.0)));
+    assert!(matches!(limiter.check(ControllerMessage::Run), Verdict::Refused("average power over limit")));
+    assert!(!limiter.running());
+}
+
+#[test]
+fn changes_while_running_are_limited_against_the_rest() {
+    let mut limiter = Limiter::new();
+    limiter.check(set(ParameterValue::OnTimeUs(100)));
+    limiter.check(set(ParameterValue::OffTimeMs(10)));
+    limiter.check(set(ParameterValue::FlatPower(1.0)));
+    limiter.check(ControllerMessage::Run);
+    assert!(matches!(limiter.check(set(ParameterValue::OnTimeUs(1000))), Verdict::Clamped(ControllerMessage::SetParam(ParameterValue::OnTimeUs(309)))));
+    assert!(matches!(limiter.check(set(ParameterValue::OffTimeMs(1))), Verdict::Clamped(ControllerMessage::SetParam(ParameterValue::OffTimeMs(10)))));
+    assert!(allowed(&limiter.check(ControllerMessage::Stop)));
+    // once stopped, nothing is limited until the next run
+    assert!(allowed(&limiter.check(set(ParameterValue::OnTimeUs(1000)))));
+}
+
+#[test]
+fn an_envelope_counts_as_its_mean_power() {
+    let envelopes = default_envelopes();
+    let mut limiter = Limiter::new();
+    limiter.check(set(ParameterValue::OnTimeUs(500)));
+    limiter.check(set(ParameterValue::OffTimeMs(10)));
+    // the linear ramp averages 50%
+    assert!(allowed(&limiter.check(ControllerMessage::SetEnvelope(envelopes[0].to_message()))));
+    assert_eq!(limiter.parameters().power, 0.5);
+    assert!(allowed(&limiter.check(ControllerMessage::Run)));
+
+    // a full power envelope while firing is scaled down to the power limit
+    let full = Envelope::new("Full", &[Breakpoint::new(0, 100), Breakpoint::new(TIME_SCALE, 100)]);
+    let limit = max_power(&limiter.config, 500, 10);
+    match limiter.check(ControllerMessage::SetEnvelope(full.to_message())) {
+        Verdict::Clamped(ControllerMessage::SetEnvelope(envelope)) => {
+            assert!(message_mean_power(&envelope) / 100.0 <= limit);
+        },
+        _ => panic!("envelope not clamped"),
+    }
+    assert!(limiter.parameters().power <= limit);
+
+    limiter.config.mode = LimitMode::Refuse;
+    assert!(matches!(limiter.check(ControllerMessage::SetEnvelope(full.to_message())), Verdict::Refused(_)));
+}
@@ -0,0 +1,28 @@
+use app_host::limiter::FiringParameters;
+use app_host::run_time::RunTimeAccountant;
+use qcw_com::ControllerMessage;
+
+const PARAMETERS: FiringParameters = FiringParameters { on_time_us: 100, off_time_ms: 10, power: 0.5 };
+
+#[test]
+fn resetting_a_trip_while_idle_saves_straight_away() {
+    let mut run_time = RunTimeAccountant::new();
+    run_time.reset_trip(0);
+    assert!(run_time.take_save(1_000).is_some());
+}
+
+#[test]
+fn resetting_a_trip_while_firing_waits_for_the_coil_to_stop() {
+    let mut run_time = RunTimeAccountant::new();
+    run_time.observe_sent(&ControllerMessage::Run, 0);
+    run_time.update(1_000, 1_000, &PARAMETERS);
+    run_time.reset_trip(0);
+    assert!(run_time.take_save(1_000).is_none());
+    run_time.update(1_000, 2_000, &PARAMETERS);
+    assert!(run_time.take_save(2_000).is_none());
+
+    run_time.observe_sent(&ControllerMessage::Stop, 3_000);
+    run_time.update(1_000, 60_000_000, &PARAMETERS);
+    assert_eq!((run_time.trip(0).bursts, run_time.trip(1).bursts), (0, 1));
+    assert!(run_time.take_save(60_000_000).is_some());
+}
@@ -0,0 +1,424 @@
+use app_host::app_views::{
+    DebugLedView, EnvelopeEditorView, EventLogView, LimitsView, MaintenanceView, MusicView, OpenLoopTestView, PhaseTuningView,
+    PingTestView, SequenceView, StatMonitorView, SweepView, View, ViewPickerView,
+};
+use app_host::application::{ButtonState, InputState};
+use app_host::harness::{ScriptedButton, ScriptedInput, ViewHarness};
+use app_host::event_log::Severity;
+use app_host::limiter::FiringParameters;
+use qcw_com::{ControllerMessage, Parameter, ParameterValue, RemoteMessage, RunMode, Statistic, StatisticValue};
+
+// short enough that no keepalive or periodic request falls due unless a test waits for one
+const DT: u64 = 1_000;
+
+const RUN: usize = 1;
+const RESET: usize = 2;
+
+#[test]
+fn idle_input_has_nothing_changed() {
+    let input = InputState::idle();
+    assert!(!input.changed());
+    assert_eq!(input.encoder.delta, 0);
+    assert!(!ButtonState::held().changed());
+    assert!(ButtonState::pressed().changed());
+    assert!(ButtonState::released().changed());
+}
+
+#[test]
+fn input_builders_set_what_they_name() {
+    let input = InputState::idle().with_encoder_delta(3);
+    assert!(input.changed());
+    assert_eq!((input.encoder.count, input.encoder.delta), (3, 3));
+
+    let input = InputState::idle().with_button(2, ButtonState::pressed());
+    assert!(input.changed());
+    assert!(input.buttons[2].pressed && input.buttons[2].down);
+    assert!(!input.buttons[0].down && !input.buttons[1].down);
+
+    let input = InputState::idle().with_encoder_button(ButtonState::held());
+    assert!(input.encoder.button.down);
+    assert!(!input.changed());
+}
+
+#[test]
+fn harness_steps_the_clock_and_collects_what_was_sent() {
+    let mut harness = ViewHarness::new(DebugLedView::new());
+    assert!(harness.step_idle(DT, 5).is_none());
+    assert_eq!(harness.shared_state.time_us, 5 * DT);
+    assert!(harness.take_sent().is_empty());
+    assert!(matches!(harness.click_button(DT, 0), Some(View::ViewPicker)));
+}
+
+#[test]
+fn debug_led_toggles() {
+    let mut harness = ViewHarness::new(DebugLedView::new());
+    harness.click_button(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::SetDebugLed(true)]));
+    harness.click_button(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::SetDebugLed(false)]));
+    harness.click_button(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::SetDebugLed(true)]));
+}
+
+#[test]
+fn open_loop_test_runs_and_stops() {
+    let mut harness = ViewHarness::new(OpenLoopTestView::new());
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::RunMode(RunMode::OpenLoop)),
+        ControllerMessage::Run,
+    ]));
+
+    // kept alive for as long as it runs
+    harness.step_idle(DT, 20);
+    let sent = harness.take_sent();
+    assert!(!sent.is_empty());
+    assert!(sent.iter().all(|message| matches!(message, ControllerMessage::KeepAlive)));
+
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+    harness.step_idle(DT, 20);
+    assert!(harness.take_sent().is_empty());
+}
+
+#[test]
+fn open_loop_test_stops_on_the_way_out() {
+    let mut harness = ViewHarness::new(OpenLoopTestView::new());
+    harness.click_button(DT, RUN);
+    harness.take_sent();
+    assert!(matches!(harness.click_button(DT, 0), Some(View::ViewPicker)));
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+}
+
+// reads the delay from the controller and leaves the view Disabled
+fn phase_tuning_ready() -> ViewHarness<PhaseTuningView> {
+    let mut harness = ViewHarness::new(PhaseTuningView::new());
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::GetParam(Parameter::DelayCompensation)]));
+    harness.inject(RemoteMessage::GetParamResult(ParameterValue::DelayCompensationNS(20)));
+    harness.step_idle(DT, 1);
+    assert!(harness.take_sent().is_empty());
+    harness
+}
+
+#[test]
+fn phase_tuning_runs_with_its_burst_settings() {
+    let mut harness = phase_tuning_ready();
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::FlatPower(power)),
+        ControllerMessage::SetParam(ParameterValue::OnTimeUs(600)),
+        ControllerMessage::SetParam(ParameterValue::OffTimeMs(300)),
+        ControllerMessage::SetParam(ParameterValue::RunMode(RunMode::TestClosedLoop)),
+        ControllerMessage::Run,
+    ] if *power == 1.0));
+}
+
+#[test]
+fn phase_tuning_stop_goes_through_disabling() {
+    let mut harness = phase_tuning_ready();
+    harness.click_button(DT, RUN);
+    harness.take_sent();
+
+    // the press only moves to Disabling, the Stop goes out on the step after
+    harness.click_button(DT, RUN);
+    assert!(harness.take_sent().is_empty());
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+
+    // back to Disabled, so Run starts it again
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().last(), Some(ControllerMessage::Run)));
+}
+
+#[test]
+fn phase_tuning_stops_when_the_limiter_stops_the_coil() {
+    let mut harness = phase_tuning_ready();
+    harness.click_button(DT, RUN);
+    harness.take_sent();
+
+    harness.shared_state.stopped = true;
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+    harness.shared_state.stopped = false;
+    harness.step_idle(20 * DT, 1);
+    assert!(harness.take_sent().is_empty());
+}
+
+// holds the encoder long enough to switch modes, then lets go
+fn hold_encoder(harness: &mut ViewHarness<PhaseTuningView>) {
+    harness.step(DT, InputState::idle().with_encoder_button(ButtonState::pressed()));
+    harness.step(700 * DT, InputState::idle().with_encoder_button(ButtonState::held()));
+    harness.step(DT, InputState::idle().with_encoder_button(ButtonState::released()));
+}
+
+#[test]
+fn phase_tuning_switches_to_auto_tune_and_back_with_a_hold() {
+    let mut harness = phase_tuning_ready();
+    hold_encoder(&mut harness);
+
+    // the release that ended the hold opened no editor, so the encoder picks a goal and Start sweeps
+    harness.turn_encoder(DT, 1);
+    assert!(harness.take_sent().is_empty());
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::RunMode(RunMode::TestClosedLoop)),
+        ControllerMessage::SetParam(ParameterValue::FlatPower(_)),
+        ControllerMessage::SetParam(ParameterValue::OnTimeUs(600)),
+        ControllerMessage::SetParam(ParameterValue::OffTimeMs(300)),
+    ]));
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(-400)),
+        ControllerMessage::ResetStats,
+        ControllerMessage::Run,
+    ]));
+
+    // stopping leaves a failed review, OK goes back to picking a goal and a hold back to tuning by hand
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(20)),
+        ControllerMessage::GetParam(Parameter::DelayCompensation),
+    ]));
+    hold_encoder(&mut harness);
+    harness.turn_encoder(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(21)),
+        ControllerMessage::GetParam(Parameter::DelayCompensation),
+    ]));
+}
+
+#[test]
+fn phase_tuning_reset_while_running_stops_and_rereads_the_delay() {
+    let mut harness = phase_tuning_ready();
+    harness.click_button(DT, RUN);
+    harness.take_sent();
+
+    harness.click_button(DT, RESET);
+    assert!(harness.take_sent().is_empty());
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::GetParam(Parameter::DelayCompensation)]));
+}
+
+#[test]
+fn phase_tuning_reset_while_disabled_rereads_the_delay() {
+    let mut harness = phase_tuning_ready();
+    harness.click_button(DT, RESET);
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::GetParam(Parameter::DelayCompensation)]));
+}
+
+#[test]
+fn phase_tuning_sends_encoder_changes_while_disabled() {
+    let mut harness = phase_tuning_ready();
+    harness.turn_encoder(DT, -5);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(15)),
+        ControllerMessage::GetParam(Parameter::DelayCompensation),
+    ]));
+}
+
+#[test]
+fn phase_tuning_types_in_a_delay() {
+    let mut harness = phase_tuning_ready();
+    harness.click_encoder(DT);
+    assert!(harness.take_sent().is_empty());
+
+    // the first position is the sign, and OK is the third frame button rather than Reset
+    harness.turn_encoder(DT, 1);
+    assert!(harness.take_sent().is_empty());
+    assert!(harness.click_button(DT, RESET).is_none());
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(-20)),
+        ControllerMessage::GetParam(Parameter::DelayCompensation),
+    ]));
+
+    // and the encoder tunes again once the editor has closed
+    harness.turn_encoder(DT, 5);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(-15)),
+        ControllerMessage::GetParam(Parameter::DelayCompensation),
+    ]));
+}
+
+#[test]
+fn phase_tuning_cancelling_the_digit_editor_stays_in_the_view() {
+    let mut harness = phase_tuning_ready();
+    harness.click_encoder(DT);
+    assert!(harness.click_button(DT, 0).is_none());
+    assert!(harness.take_sent().is_empty());
+    assert!(matches!(harness.click_button(DT, 0), Some(View::ViewPicker)));
+}
+
+#[test]
+fn limits_types_in_a_max_duty() {
+    let mut harness = ViewHarness::new(LimitsView::new());
+    harness.turn_encoder(DT, 1);
+    harness.click_button(DT, 2);
+
+    // 5.0 % with a digit for tenths, the first turn makes it 15.0 %
+    harness.turn_encoder(DT, 1);
+    harness.click_button(DT, 2);
+    assert_eq!(harness.shared_state.limiter.config.max_duty, 0.15);
+    assert!(harness.click_button(DT, 0).is_some());
+}
+
+#[test]
+fn envelope_moves_are_saved_once_the_move_is_finished() {
+    let mut harness = ViewHarness::new(EnvelopeEditorView::new());
+    harness.turn_encoder(DT, 1);
+    harness.click_encoder(DT);
+    harness.turn_encoder(DT, -3);
+    harness.turn_encoder(DT, -3);
+    assert!(!harness.shared_state.envelopes_unsaved);
+    harness.click_encoder(DT);
+    assert!(harness.shared_state.envelopes_unsaved);
+}
+
+#[test]
+fn envelope_apply_sends_the_selected_envelope() {
+    let mut harness = ViewHarness::new(EnvelopeEditorView::new());
+    for _ in 0..3 {
+        harness.click_encoder(DT);
+    }
+    harness.take_sent();
+    harness.click_button(DT, 1);
+    assert!(harness.take_sent().iter().any(|message| matches!(message, ControllerMessage::SetEnvelope(_))));
+}
+
+#[test]
+fn sequence_aborts_when_the_limiter_refuses_part_of_it() {
+    let mut harness = ViewHarness::new(SequenceView::new());
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().last(), Some(ControllerMessage::Run)));
+
+    harness.shared_state.refused = Some("power over limit");
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+    harness.shared_state.refused = None;
+    harness.step_idle(100 * DT, 10);
+    assert!(harness.take_sent().is_empty());
+}
+
+#[test]
+fn sweep_sends_its_burst_settings_before_the_first_point() {
+    let mut harness = ViewHarness::new(SweepView::new());
+    harness.click_button(DT, RUN);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::SetParam(ParameterValue::RunMode(RunMode::TestClosedLoop)),
+        ControllerMessage::SetParam(ParameterValue::FlatPower(_)),
+        ControllerMessage::SetParam(ParameterValue::OnTimeUs(_)),
+        ControllerMessage::SetParam(ParameterValue::OffTimeMs(_)),
+        ControllerMessage::SetParam(ParameterValue::DelayCompensationNS(-400)),
+        ControllerMessage::ResetStats,
+        ControllerMessage::Run,
+    ]));
+
+    // a refused setting ends the sweep
+    harness.shared_state.refused = Some("power over limit");
+    harness.step_idle(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+    harness.shared_state.refused = None;
+    harness.step_idle(100 * DT, 10);
+    assert!(harness.take_sent().is_empty());
+}
+
+#[test]
+fn view_picker_opens_the_tool_under_the_cursor() {
+    let mut harness = ViewHarness::new(ViewPickerView::new());
+    assert!(matches!(harness.click_encoder(DT), Some(View::DebugLed)));
+    harness.turn_encoder(DT, 2);
+    assert!(matches!(harness.click_encoder(DT), Some(View::PhaseTuning)));
+    assert!(harness.take_sent().is_empty());
+}
+
+#[test]
+fn ping_test_pings_with_a_new_sequence_number_each_time() {
+    let mut harness = ViewHarness::new(PingTestView::new());
+    harness.step_idle(101 * DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Ping(0)]));
+    harness.step_idle(50 * DT, 1);
+    assert!(harness.take_sent().is_empty());
+
+    // the reply is shown in place of the dashes
+    let waiting = harness.framebuffer().to_linear_bitmap();
+    harness.inject(RemoteMessage::Ping(0));
+    harness.step_idle(DT, 1);
+    assert!(harness.framebuffer().to_linear_bitmap() != waiting);
+
+    harness.step_idle(50 * DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Ping(0x0001_0101)]));
+    assert!(matches!(harness.click_button(DT, 0), Some(View::ViewPicker)));
+}
+
+#[test]
+fn stat_monitor_polls_the_statistics_and_resets_them() {
+    let mut harness = ViewHarness::new(StatMonitorView::new());
+    harness.step_idle(30 * DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [
+        ControllerMessage::GetStat(Statistic::MaxPrimaryCurrent),
+        ControllerMessage::GetStat(Statistic::FeedbackFrequency),
+    ]));
+
+    let before = harness.framebuffer().to_linear_bitmap();
+    harness.inject(RemoteMessage::GetStatResult(StatisticValue::MaxPrimaryCurrentA(12.5)));
+    harness.step_idle(DT, 1);
+    assert!(harness.framebuffer().to_linear_bitmap() != before);
+
+    harness.click_button(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::ResetStats]));
+}
+
+#[test]
+fn event_log_clears_and_takes_markers() {
+    let mut harness = ViewHarness::new(EventLogView::new());
+    harness.shared_state.log(Severity::Info, format_args!("Something"));
+    harness.click_button(DT, 1);
+    assert_eq!(harness.shared_state.event_log.len(), 0);
+
+    // Mark, one character from the wheel, OK
+    harness.click_button(DT, 2);
+    harness.click_encoder(DT);
+    harness.click_button(DT, 2);
+    let log = &harness.shared_state.event_log;
+    assert_eq!(log.len(), 1);
+    assert!(log.get(0).unwrap().message().starts_with("Marker: "));
+    assert!(matches!(harness.click_button(DT, 0), Some(View::ViewPicker)));
+}
+
+#[test]
+fn music_plays_until_stopped() {
+    let mut harness = ViewHarness::new(MusicView::new());
+    harness.click_button(DT, 1);
+    let sent = harness.take_sent();
+    assert!(matches!(&sent[..2], [
+        ControllerMessage::SetParam(ParameterValue::RunMode(RunMode::OpenLoop)),
+        ControllerMessage::SetParam(ParameterValue::FlatPower(_)),
+    ]));
+    assert!(sent.iter().any(|message| matches!(message, ControllerMessage::Run)));
+
+    harness.click_button(DT, 1);
+    assert!(matches!(harness.take_sent().as_slice(), [ControllerMessage::Stop]));
+    harness.step_idle(100 * DT, 10);
+    assert!(harness.take_sent().is_empty());
+}
+
+#[test]
+fn maintenance_resets_one_trip() {
+    let mut harness = ViewHarness::new(MaintenanceView::new());
+    let parameters = FiringParameters { on_time_us: 100, off_time_ms: 10, power: 0.5 };
+    let run_time = &mut harness.shared_state.run_time;
+    run_time.observe_sent(&ControllerMessage::Run, 0);
+    run_time.update(1_000, 1_000, &parameters);
+    run_time.observe_sent(&ControllerMessage::Stop, 1_000);
+
+    harness.click_button(DT, 2);
+    let run_time = &harness.shared_state.run_time;
+    assert_eq!(run_time.trip(1).fired_us, 0);
+    assert_eq!(run_time.trip(0).fired_us, 1_000);
+    assert_eq!(run_time.lifetime().fired_us, 1_000);
+}
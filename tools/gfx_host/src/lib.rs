@@ -0,0 +1,16 @@
+//! The firmware's graphics code built for the host, so it can be tested there:
+//!
+//! ```text
+//! cargo test --target <host triple>
+//! ```
+
+extern crate alloc;
+
+#[path = "../../../src/gfx/mod.rs"]
+pub mod gfx;
+
+#[path = "../../../src/mn12864k/framebuffer.rs"]
+pub mod framebuffer;
+
+#[path = "../../../src/animation.rs"]
+pub mod animation;
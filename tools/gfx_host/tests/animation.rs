@@ -0,0 +1,111 @@
+use gfx_host::animation::{Blink, Easing, Tween};
+use gfx_host::framebuffer::{Framebuffer, HEIGHT, WIDTH};
+use gfx_host::gfx::primitives::*;
+
+#[test]
+fn easings_start_and_end_on_the_values() {
+    for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+        assert_eq!(easing.apply(0.0), 0.0, "{:?}", easing);
+        assert_eq!(easing.apply(1.0), 1.0, "{:?}", easing);
+        assert_eq!(easing.apply(-1.0), 0.0, "{:?}", easing);
+        assert_eq!(easing.apply(2.0), 1.0, "{:?}", easing);
+        let mut previous = 0.0;
+        for step in 1..=20 {
+            let value = easing.apply(step as f32 / 20.0);
+            assert!(value >= previous, "{:?} goes backwards at {}", easing, step);
+            previous = value;
+        }
+    }
+    assert!(Easing::EaseOut.apply(0.25) > 0.25);
+    assert!(Easing::EaseIn.apply(0.25) < 0.25);
+    assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+}
+
+#[test]
+fn tween_runs_for_its_duration() {
+    let mut tween = Tween::new(3.0, 100_000, Easing::Linear);
+    assert!(tween.finished());
+    assert_eq!(tween.value(), 3.0);
+
+    tween.start(0.0, 10.0);
+    assert!(!tween.finished());
+    assert_eq!(tween.value(), 0.0);
+    tween.update(25_000);
+    assert_eq!(tween.value(), 2.5);
+    tween.update(25_000);
+    assert_eq!(tween.value(), 5.0);
+    tween.update(1_000_000);
+    assert!(tween.finished());
+    assert_eq!(tween.value(), 10.0);
+}
+
+#[test]
+fn retargeting_carries_on_from_where_the_tween_is() {
+    let mut tween = Tween::new(0.0, 100_000, Easing::EaseOut);
+    tween.retarget(10.0);
+    tween.update(50_000);
+    let midway = tween.value();
+    tween.retarget(-10.0);
+    assert_eq!(tween.value(), midway);
+    assert_eq!(tween.target(), -10.0);
+
+    // the same target again doesn't restart it
+    tween.update(100_000);
+    tween.retarget(-10.0);
+    assert!(tween.finished());
+    assert_eq!(tween.value(), -10.0);
+
+    tween.retarget(4.0);
+    tween.set(7.0);
+    assert!(tween.finished());
+    assert_eq!(tween.value(), 7.0);
+}
+
+#[test]
+fn zero_length_tween_finishes_at_once() {
+    let mut tween = Tween::new(0.0, 0, Easing::EaseInOut);
+    tween.start(1.0, 2.0);
+    assert!(tween.finished());
+    assert_eq!(tween.value(), 2.0);
+}
+
+#[test]
+fn blink_is_on_for_half_of_each_period() {
+    let mut blink = Blink::new(100_000);
+    let mut pattern = Vec::new();
+    for _ in 0..8 {
+        pattern.push(blink.on());
+        blink.update(25_000);
+    }
+    assert_eq!(pattern, [true, true, false, false, true, true, false, false]);
+
+    blink.update(60_000);
+    assert!(!blink.on());
+    blink.restart();
+    assert!(blink.on());
+}
+
+// how Application draws a slide between two views
+#[test]
+fn slide_transition_puts_both_frames_side_by_side() {
+    let mut outgoing_frame = Framebuffer::new();
+    outgoing_frame.clear(true);
+    let mut incoming_frame = Framebuffer::new();
+    draw_rect(&mut incoming_frame, (0, 0), (WIDTH as isize - 1, HEIGHT as isize - 1), true);
+
+    let shift = 40;
+    let mut screen = Framebuffer::new();
+    screen.blit_framebuffer(&outgoing_frame, -shift);
+    screen.blit_framebuffer(&incoming_frame, WIDTH as isize - shift);
+
+    for y in 0..HEIGHT as isize {
+        for x in 0..WIDTH as isize {
+            let expected = if x < WIDTH as isize - shift {
+                true
+            } else {
+                incoming_frame.get((x - (WIDTH as isize - shift), y))
+            };
+            assert_eq!(screen.get((x, y)), expected, "{:?}", (x, y));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use gfx_host::framebuffer::Framebuffer;
+use gfx_host::gfx::bitmap::{Bitmap, RasterOp};
+use gfx_host::gfx::draw_target::DrawTarget;
+use gfx_host::gfx::fonts::UI_7PX;
+use gfx_host::gfx::primitives::*;
+use proc_bitmap_font::{bitmap, png_bitmap};
+
+bitmap!(PATTERN, r#"
+.*.*
+"#);
+
+bitmap!(ICON, r#"
+ *** 
+*   *
+* * *
+*   *
+ *** 
+"#);
+
+png_bitmap!(SHEET, "src/gfx/fonts/ui_7px.png");
+
+fn row(bitmap: &Bitmap) -> String {
+    (0..bitmap.width() as isize).map(|x| if bitmap.get((x, 0)) { '#' } else { '.' }).collect()
+}
+
+// every combination of target and bitmap pixel, the target being ..## and the bitmap .#.#
+fn combined(op: RasterOp) -> String {
+    let mut target = Bitmap::new(4, 1);
+    target.set_pixel((2, 0), true);
+    target.set_pixel((3, 0), true);
+    PATTERN.blit(&mut target, (0, 0), op);
+    row(&target)
+}
+
+#[test]
+fn raster_ops() {
+    assert_eq!(combined(RasterOp::Copy), ".#.#");
+    assert_eq!(combined(RasterOp::Or), ".###");
+    assert_eq!(combined(RasterOp::And), "...#");
+    assert_eq!(combined(RasterOp::Xor), ".##.");
+    assert_eq!(combined(RasterOp::Invert), "#.#.");
+}
+
+#[test]
+fn picture_size_and_pixels() {
+    assert_eq!((ICON.width(), ICON.height()), (5, 5));
+    assert!(ICON.get((2, 2)) && ICON.get((1, 0)));
+    assert!(!ICON.get((0, 0)) && !ICON.get((2, 1)) && !ICON.get((5, 0)));
+}
+
+#[test]
+fn drawn_bitmap_blits_like_drawing_directly() {
+    let mut sprite = Bitmap::new(21, 13);
+    draw_circle(&mut sprite, (10, 6), 6, true);
+    draw_line(&mut sprite, (0, 12), (20, 0), true);
+    ICON.blit(&mut sprite, (8, 4), RasterOp::Xor);
+
+    let mut blitted = Framebuffer::new();
+    let mut direct = Framebuffer::new();
+    for position in [(3, 2), (-4, 50), (115, 7)] {
+        sprite.blit(&mut blitted, position, RasterOp::Or);
+        draw_circle(&mut direct, (position.0 + 10, position.1 + 6), 6, true);
+        draw_line(&mut direct, (position.0, position.1 + 12), (position.0 + 20, position.1), true);
+        ICON.blit(&mut direct, (position.0 + 8, position.1 + 4), RasterOp::Xor);
+    }
+    assert!(blitted.buffer == direct.buffer);
+}
+
+#[test]
+fn copy_clears_under_the_bitmap() {
+    let mut framebuffer = Framebuffer::new();
+    framebuffer.clear(true);
+    ICON.blit(&mut framebuffer, (10, 10), RasterOp::Copy);
+    for y in 0..5 {
+        for x in 0..5 {
+            assert_eq!(framebuffer.get((10 + x, 10 + y)), ICON.get((x, y)));
+        }
+    }
+    assert!(framebuffer.get((9, 10)) && framebuffer.get((15, 14)));
+}
+
+#[test]
+fn png_matches_the_font_made_from_it() {
+    assert_eq!((SHEET.width(), SHEET.height()), (128, 60));
+    // 'A' is cell 33 of the 16 wide grid of 8x10 cells, which have the baseline on row 6
+    let glyph = UI_7PX.find_glyph('A').unwrap();
+    let origin = (8, 20 + 6 - glyph.baseline);
+    for y in 0..glyph.height {
+        for x in 0..glyph.width {
+            let bit = y * glyph.width + x;
+            let inked = glyph.bitmap[bit / 8] & (1 << (bit % 8)) != 0;
+            assert_eq!(SHEET.get((origin.0 + x as isize, origin.1 + y as isize)), inked);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use gfx_host::framebuffer::{Framebuffer, HEIGHT, WIDTH};
+use gfx_host::gfx::dirty::DirtyRegion;
+use gfx_host::gfx::draw_target::DrawTarget;
+use gfx_host::gfx::fonts::BASIC_5PX;
+use gfx_host::gfx::primitives::*;
+
+fn region() -> DirtyRegion {
+    DirtyRegion::new(WIDTH, HEIGHT)
+}
+
+#[test]
+fn separate_rects_stay_separate() {
+    let mut dirty = region();
+    assert!(dirty.is_empty());
+    dirty.mark((0, 0), (9, 9));
+    dirty.mark((20, 20), (29, 29));
+    assert_eq!(dirty.rects(), [((0, 0), (9, 9)), ((20, 20), (29, 29))]);
+
+    let taken = dirty.take();
+    assert!(dirty.is_empty());
+    assert_eq!(taken.rects().len(), 2);
+}
+
+#[test]
+fn touching_rects_merge() {
+    let mut dirty = region();
+    dirty.mark((0, 0), (9, 9));
+    dirty.mark((10, 0), (19, 9));
+    assert_eq!(dirty.rects(), [((0, 0), (19, 9))]);
+
+    // bridging two rects merges all three
+    dirty.mark((40, 0), (49, 9));
+    dirty.mark((15, 5), (45, 6));
+    assert_eq!(dirty.rects(), [((0, 0), (49, 9))]);
+}
+
+#[test]
+fn rects_are_clipped_to_the_screen() {
+    let mut dirty = region();
+    dirty.mark((-10, -10), (5, 5));
+    dirty.mark((200, 0), (210, 10));
+    assert_eq!(dirty.rects(), [((0, 0), (5, 5))]);
+
+    dirty.mark_all();
+    assert_eq!(dirty.rects(), [((0, 0), (127, 63))]);
+}
+
+#[test]
+fn too_many_rects_redraw_everything() {
+    let mut dirty = region();
+    dirty.mark((0, 0), (2, 2));
+    dirty.mark((30, 0), (32, 2));
+    assert_eq!(dirty.rects().len(), 2);
+    dirty.mark((0, 60), (2, 63));
+    assert_eq!(dirty.rects(), [((0, 0), (127, 63))]);
+
+    // and anything marked after that is already covered
+    dirty.mark((60, 30), (70, 40));
+    assert_eq!(dirty.rects(), [((0, 0), (127, 63))]);
+}
+
+fn draw_scene(framebuffer: &mut Framebuffer, value: u32) {
+    draw_rect(framebuffer, (0, 0), (127, 63), true);
+    draw_hline(framebuffer, 1, 126, 10, true);
+    BASIC_5PX.draw_text_line(framebuffer, (3, 7), "Title", true);
+    BASIC_5PX.draw_text_line(framebuffer, (5, 25), &format!("Value {}", value), true);
+    draw_filled_rect(framebuffer, (70, 20), (70 + value as isize, 30), true);
+}
+
+// what Application does: start from the last frame and redraw only the dirty rects through the clip
+#[test]
+fn redrawing_dirty_rects_matches_a_full_redraw() {
+    let mut last = Framebuffer::new();
+    draw_scene(&mut last, 12);
+
+    let mut dirty = region();
+    dirty.mark((5, 18), (60, 27));
+    dirty.mark((70, 20), (126, 30));
+
+    let mut partial = Framebuffer::new();
+    partial.buffer = last.buffer;
+    for &(upper_left, lower_right) in dirty.rects() {
+        partial.set_clip(upper_left, lower_right);
+        partial.fill_rect(upper_left, lower_right, false);
+        draw_scene(&mut partial, 40);
+    }
+    partial.reset_clip();
+
+    let mut full = Framebuffer::new();
+    draw_scene(&mut full, 40);
+    assert!(partial.buffer == full.buffer);
+}
@@ -0,0 +1,56 @@
+use gfx_host::gfx::bitmap_font::{BitmapFont, Glyph, GlyphRange};
+use gfx_host::gfx::fonts::{icons, BASIC_5PX};
+use proc_bitmap_font::bdf_font;
+
+bdf_font!(TINY, "tools/gfx_host/tests/fixtures/tiny.bdf");
+bdf_font!(TINY_UPPER, "tools/gfx_host/tests/fixtures/tiny.bdf", 'A'..='Z');
+
+fn rows(glyph: &Glyph) -> String {
+    (0..glyph.height)
+        .map(|y| (0..glyph.width).map(|x| {
+            let bit = y * glyph.width + x;
+            if glyph.bitmap[bit / 8] & (1 << (bit % 8)) != 0 { '#' } else { '.' }
+        }).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn bdf_glyphs_sit_on_the_baseline() {
+    assert_eq!(TINY.line_height, 7);
+
+    let a = TINY.find_glyph('A').unwrap();
+    assert_eq!((a.width, a.height, a.baseline, a.advance), (3, 5, 4, 5));
+    assert_eq!(rows(a), ".#.\n#.#\n###\n#.#\n#.#");
+
+    // two rows below the baseline, and moved right by its x offset
+    let j = TINY.find_glyph('j').unwrap();
+    assert_eq!((j.width, j.height, j.baseline, j.advance), (3, 6, 3, 4));
+    assert_eq!(rows(j), "..#\n...\n..#\n..#\n..#\n.#.");
+
+    let space = TINY.find_glyph(' ').unwrap();
+    assert_eq!((space.width, space.height, space.advance), (0, 0, 3));
+    // the advances, less the gap after the last glyph
+    assert_eq!(TINY.get_text_width(" A"), 7);
+}
+
+#[test]
+fn bdf_font_keeps_only_encoded_characters_in_its_range() {
+    assert!(TINY.has_glyphs(" Aj"));
+    assert!(TINY.find_glyph('B').is_none());
+    assert!(TINY_UPPER.has_glyphs("A"));
+    assert!(TINY_UPPER.find_glyph('j').is_none() && TINY_UPPER.find_glyph(' ').is_none());
+    // what's missing is drawn as the fallback box
+    assert!(TINY.glyph('B').is_some());
+}
+
+#[test]
+fn icons_stay_within_the_letters() {
+    let letter = BASIC_5PX.find_glyph('A').unwrap();
+    let (top, bottom) = (letter.baseline, letter.baseline + 1 - letter.height as isize);
+    for icon in [icons::WARNING, icons::LINK, icons::NO_LINK, icons::LIGHTNING, icons::PLAY, icons::STOP, icons::PAUSE, icons::CHECK, icons::CROSS] {
+        let glyph = BASIC_5PX.find_glyph(icon.chars().next().unwrap()).unwrap();
+        assert!(glyph.baseline <= top, "{:?} goes above the letters", icon);
+        assert!(glyph.baseline + 1 - glyph.height as isize >= bottom, "{:?} goes below the letters", icon);
+    }
+}
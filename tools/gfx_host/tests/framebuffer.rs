@@ -0,0 +1,192 @@
+use gfx_host::framebuffer::Framebuffer;
+use gfx_host::gfx::draw_target::{DitherMask, DrawTarget, RectMask, _DTRef, _Maskable, _Translatable};
+use gfx_host::gfx::fonts::{BASIC_5PX, DIGITS_16PX, UI_7PX};
+
+// the framebuffer with only set_pixel, so everything goes through the pixel by pixel defaults
+struct Pixels<'a>(&'a mut Framebuffer);
+
+impl DrawTarget for Pixels<'_> {
+    fn set_pixel(&mut self, position: (isize, isize), color: bool) {
+        self.0.set(position, color);
+    }
+
+    fn get_pixel(&self, position: (isize, isize)) -> bool {
+        self.0.get(position)
+    }
+}
+
+// draws the same thing both ways, on a clear and on a lit screen, and checks they match
+fn matches_pixels(draw: impl Fn(&mut Framebuffer, bool, bool)) {
+    for background in [false, true] {
+        for color in [true, false] {
+            let mut fast = Framebuffer::new();
+            let mut slow = Framebuffer::new();
+            fast.clear(background);
+            slow.clear(background);
+            draw(&mut fast, color, true);
+            draw(&mut slow, color, false);
+            assert!(fast.buffer == slow.buffer, "differs with background {} color {}", background, color);
+        }
+    }
+}
+
+const RECTS: [((isize, isize), (isize, isize)); 8] = [
+    ((0, 0), (127, 63)),
+    ((5, 3), (5, 3)),
+    ((1, 1), (126, 62)),
+    ((6, 10), (11, 10)),
+    ((-4, -2), (7, 9)),
+    ((120, 50), (140, 80)),
+    ((13, 0), (70, 40)),
+    ((200, 0), (210, 5)),
+];
+
+#[test]
+fn fill_rect_matches_pixels() {
+    matches_pixels(|framebuffer, color, fast| {
+        for (upper_left, lower_right) in RECTS {
+            if fast {
+                framebuffer.fill_rect(upper_left, lower_right, color);
+            } else {
+                Pixels(framebuffer).fill_rect(upper_left, lower_right, color);
+            }
+        }
+    });
+}
+
+#[test]
+fn fill_span_matches_pixels() {
+    matches_pixels(|framebuffer, color, fast| {
+        for (upper_left, lower_right) in RECTS {
+            if fast {
+                framebuffer.fill_span(upper_left.0, lower_right.0, upper_left.1, color);
+            } else {
+                Pixels(framebuffer).fill_span(upper_left.0, lower_right.0, upper_left.1, color);
+            }
+        }
+    });
+}
+
+#[test]
+fn blit_bitmap_matches_pixels() {
+    // an odd width, so rows start part way through bytes
+    let bitmap: Vec<u8> = (0..40u32).map(|i| (i.wrapping_mul(0x9E) ^ (i >> 1)) as u8).collect();
+    matches_pixels(|framebuffer, color, fast| {
+        for position in [(0, 0), (3, 7), (-5, -3), (120, 58), (61, 20)] {
+            if fast {
+                framebuffer.blit_bitmap(position, &bitmap, 13, 24, color);
+            } else {
+                Pixels(framebuffer).blit_bitmap(position, &bitmap, 13, 24, color);
+            }
+        }
+    });
+}
+
+#[test]
+fn text_matches_pixels() {
+    matches_pixels(|framebuffer, color, fast| {
+        for (index, font) in [&BASIC_5PX, &UI_7PX, &DIGITS_16PX].iter().enumerate() {
+            for x in [-3, 0, 1, 100] {
+                let baseline = 6 + index as isize * 20;
+                if fast {
+                    font.draw_text_line(framebuffer, (x, baseline), "Wg0123456789 µs", color);
+                } else {
+                    font.draw_text_line(&mut Pixels(framebuffer), (x, baseline), "Wg0123456789 µs", color);
+                }
+            }
+        }
+    });
+}
+
+#[test]
+fn clipped_and_translated_match_pixels() {
+    let clip = || RectMask { upper_left: (10, 5), lower_right: (60, 30) };
+    matches_pixels(|framebuffer, color, fast| {
+        if fast {
+            let mut target = framebuffer.dt_ref().mask(clip()).translate((3, 2));
+            target.fill_rect((0, 0), (100, 100), !color);
+            BASIC_5PX.draw_text_line(&mut target, (8, 8), "Clipped text", color);
+            BASIC_5PX.draw_text_line(&mut target, (40, 20), "Cut off at the edge", color);
+        } else {
+            let mut target = Pixels(framebuffer).mask(clip()).translate((3, 2));
+            target.fill_rect((0, 0), (100, 100), !color);
+            BASIC_5PX.draw_text_line(&mut target, (8, 8), "Clipped text", color);
+            BASIC_5PX.draw_text_line(&mut target, (40, 20), "Cut off at the edge", color);
+        }
+    });
+}
+
+#[test]
+fn dithered_fill_matches_pixels() {
+    matches_pixels(|framebuffer, color, fast| {
+        for level in [0, 5, 16] {
+            let x = level as isize * 7;
+            if fast {
+                framebuffer.dt_ref().mask(DitherMask::new(level)).fill_rect((x, 0), (x + 20, 40), color);
+            } else {
+                Pixels(framebuffer).mask(DitherMask::new(level)).fill_rect((x, 0), (x + 20, 40), color);
+            }
+        }
+    });
+}
+
+#[test]
+fn framebuffer_clip_matches_pixels() {
+    matches_pixels(|framebuffer, color, fast| {
+        framebuffer.set_clip((9, 4), (70, 33));
+        if fast {
+            framebuffer.fill_rect((0, 0), (100, 100), !color);
+            BASIC_5PX.draw_text_line(framebuffer, (8, 8), "Clipped text", color);
+            UI_7PX.draw_text_line(framebuffer, (40, 36), "Cut off at the edge", color);
+        } else {
+            let mut target = Pixels(framebuffer);
+            target.fill_rect((0, 0), (100, 100), !color);
+            BASIC_5PX.draw_text_line(&mut target, (8, 8), "Clipped text", color);
+            UI_7PX.draw_text_line(&mut target, (40, 36), "Cut off at the edge", color);
+        }
+    });
+}
+
+#[test]
+fn clip_keeps_drawing_inside_it() {
+    let mut framebuffer = Framebuffer::new();
+    framebuffer.set_clip((10, 20), (29, 39));
+    framebuffer.fill_rect((-10, -10), (200, 200), true);
+    framebuffer.set((5, 5), true);
+    framebuffer.reset_clip();
+    for y in 0..64 {
+        for x in 0..128 {
+            assert_eq!(framebuffer.get((x, y)), (10..30).contains(&x) && (20..40).contains(&y), "{:?}", (x, y));
+        }
+    }
+
+    // off screen entirely, nothing gets drawn
+    framebuffer.set_clip((130, 0), (140, 10));
+    framebuffer.fill_rect((0, 0), (127, 63), false);
+    BASIC_5PX.draw_text_line(&mut framebuffer, (0, 8), "Nothing", true);
+    framebuffer.reset_clip();
+    assert!(framebuffer.get((15, 25)));
+}
+
+#[test]
+fn blit_framebuffer_matches_pixels() {
+    let mut source = Framebuffer::new();
+    source.clear(true);
+    BASIC_5PX.draw_text_line(&mut source, (3, 20), "Slide 0123456789", false);
+    source.fill_rect((100, 40), (127, 63), false);
+    for x_offset in [0, 1, 5, 6, 7, -1, -6, -13, 64, 127, -127, 128, -128] {
+        matches_pixels(|target, _, fast| {
+            if fast {
+                target.blit_framebuffer(&source, x_offset);
+            } else {
+                for y in 0..64 {
+                    for x in 0..128 {
+                        if source.get((x, y)) {
+                            target.set((x + x_offset, y), true);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
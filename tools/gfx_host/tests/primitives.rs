@@ -0,0 +1,261 @@
+use gfx_host::gfx::draw_target::{DitherMask, DrawTarget, _DTRef, _Maskable};
+use gfx_host::gfx::primitives::*;
+
+// a small target that prints as rows of # and ., to compare against the expected pictures
+struct Grid {
+    width: usize,
+    pixels: Vec<bool>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Self {
+        Self { width, pixels: vec![false; width * height] }
+    }
+
+    fn render(&self) -> String {
+        self.pixels.chunks(self.width)
+            .map(|row| row.iter().map(|pixel| if *pixel { '#' } else { '.' }).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl DrawTarget for Grid {
+    fn set_pixel(&mut self, position: (isize, isize), color: bool) {
+        if (0..self.width as isize).contains(&position.0) && (0..(self.pixels.len() / self.width) as isize).contains(&position.1) {
+            self.pixels[position.1 as usize * self.width + position.0 as usize] = color;
+        }
+    }
+
+    fn get_pixel(&self, position: (isize, isize)) -> bool {
+        self.pixels.get(position.1 as usize * self.width + position.0 as usize).copied().unwrap_or(false)
+    }
+}
+
+fn picture(rows: &str) -> String {
+    rows.trim().lines().map(|row| row.trim()).collect::<Vec<_>>().join("\n")
+}
+
+fn drawn(width: usize, height: usize, draw: impl FnOnce(&mut Grid)) -> String {
+    let mut grid = Grid::new(width, height);
+    draw(&mut grid);
+    grid.render()
+}
+
+#[test]
+fn circle() {
+    assert_eq!(drawn(9, 9, |grid| draw_circle(grid, (4, 4), 4, true)), picture("
+        ...###...
+        .##...##.
+        .#.....#.
+        #.......#
+        #.......#
+        #.......#
+        .#.....#.
+        .##...##.
+        ...###...
+    "));
+}
+
+#[test]
+fn filled_circle() {
+    assert_eq!(drawn(9, 9, |grid| draw_filled_circle(grid, (4, 4), 4, true)), picture("
+        ...###...
+        .#######.
+        .#######.
+        #########
+        #########
+        #########
+        .#######.
+        .#######.
+        ...###...
+    "));
+}
+
+#[test]
+fn arc_top_half() {
+    assert_eq!(drawn(9, 9, |grid| draw_arc(grid, (4, 4), 4, 180.0, 360.0, true)), picture("
+        ...###...
+        .##...##.
+        .#.....#.
+        #.......#
+        #.......#
+        .........
+        .........
+        .........
+        .........
+    "));
+}
+
+#[test]
+fn arc_wraps_through_zero() {
+    assert_eq!(drawn(9, 9, |grid| draw_arc(grid, (4, 4), 4, 270.0, 90.0, true)), picture("
+        ....##...
+        ......##.
+        .......#.
+        ........#
+        ........#
+        ........#
+        .......#.
+        ......##.
+        ....##...
+    "));
+}
+
+#[test]
+fn thick_arc_gauge() {
+    assert_eq!(drawn(11, 11, |grid| draw_thick_arc(grid, (5, 5), 5, 2, 135.0, 45.0, true)), picture("
+        ...#####...
+        ..#######..
+        .###...###.
+        ###.....###
+        ##.......##
+        ##.......##
+        ##.......##
+        ###.....###
+        .##.....##.
+        ...........
+        ...........
+    "));
+}
+
+#[test]
+fn rounded_rect() {
+    assert_eq!(drawn(10, 8, |grid| draw_rounded_rect(grid, (0, 0), (9, 7), 3, true)), picture("
+        ..######..
+        .#......#.
+        #........#
+        #........#
+        #........#
+        #........#
+        .#......#.
+        ..######..
+    "));
+}
+
+#[test]
+fn filled_rounded_rect() {
+    assert_eq!(drawn(10, 8, |grid| draw_filled_rounded_rect(grid, (0, 0), (9, 7), 3, true)), picture("
+        ..######..
+        .########.
+        ##########
+        ##########
+        ##########
+        ##########
+        .########.
+        ..######..
+    "));
+}
+
+#[test]
+fn rounded_rect_radius_limited() {
+    assert_eq!(drawn(6, 4, |grid| draw_rounded_rect(grid, (0, 0), (5, 3), 10, true)), picture("
+        .####.
+        #....#
+        #....#
+        .####.
+    "));
+}
+
+#[test]
+fn filled_triangle() {
+    assert_eq!(drawn(9, 6, |grid| draw_filled_triangle(grid, (0, 5), (4, 0), (8, 5), true)), picture("
+        ....#....
+        ...##....
+        ..####...
+        .######..
+        ########.
+        #########
+    "));
+}
+
+#[test]
+fn filled_concave_polygon() {
+    assert_eq!(drawn(9, 9, |grid| draw_filled_polygon(grid, &[(0, 0), (8, 0), (8, 8), (4, 4), (0, 8)], true)), picture("
+        #########
+        #########
+        #########
+        #########
+        #########
+        ####.####
+        ###...###
+        ##.....##
+        #.......#
+    "));
+}
+
+#[test]
+fn polygon_outline() {
+    assert_eq!(drawn(7, 5, |grid| draw_polygon(grid, &[(0, 0), (6, 0), (3, 4)], true)), picture("
+        #######
+        .#....#
+        ..#..#.
+        ...##..
+        ...#...
+    "));
+}
+
+#[test]
+fn thick_line_horizontal() {
+    assert_eq!(drawn(10, 5, |grid| draw_thick_line(grid, (1, 2), (8, 2), 3, true)), picture("
+        ..........
+        .########.
+        .########.
+        .########.
+        ..........
+    "));
+}
+
+#[test]
+fn thick_line_diagonal() {
+    assert_eq!(drawn(10, 8, |grid| draw_thick_line(grid, (1, 1), (8, 6), 3, true)), picture("
+        ..#.......
+        .####.....
+        ######....
+        .#######..
+        ..#######.
+        ....######
+        .....####.
+        .......#..
+    "));
+}
+
+#[test]
+fn line_steps_after_it_crosses_the_pixel_boundary() {
+    assert_eq!(drawn(9, 4, |grid| draw_line(grid, (0, 0), (8, 3), true)), picture("
+        ###......
+        ...###...
+        ......##.
+        ........#
+    "));
+}
+
+#[test]
+fn dither_quarter() {
+    assert_eq!(drawn(8, 4, |grid| draw_filled_rect(&mut grid.dt_ref().mask(DitherMask::new(4)), (0, 0), (7, 3), true)), picture("
+        #.#.#.#.
+        ........
+        #.#.#.#.
+        ........
+    "));
+}
+
+#[test]
+fn dither_half() {
+    assert_eq!(drawn(8, 4, |grid| draw_filled_rect(&mut grid.dt_ref().mask(DitherMask::new(8)), (0, 0), (7, 3), true)), picture("
+        #.#.#.#.
+        .#.#.#.#
+        #.#.#.#.
+        .#.#.#.#
+    "));
+}
+
+#[test]
+fn dither_from_fraction() {
+    assert_eq!(drawn(8, 4, |grid| draw_filled_rect(&mut grid.dt_ref().mask(DitherMask::from_fraction(0.75)), (0, 0), (7, 3), true)), picture("
+        ########
+        .#.#.#.#
+        ########
+        .#.#.#.#
+    "));
+}
@@ -0,0 +1,136 @@
+//! Times drawing a screen full of text with the firmware's fonts and framebuffer, against the
+//! way text used to be drawn: searching every glyph range for each character and setting each
+//! pixel through `DrawTarget::set_pixel`, which bounds checks and divides by 6 for every bit.
+//!
+//! ```text
+//! cargo run --release --target <host triple>
+//! ```
+//!
+//! This runs on the host, which has caches, a branch predictor and a much wider core than the
+//! RP2350, so the absolute times say nothing about the remote. The ratio between the two is a
+//! rough guide to the improvement, no more; check on the hardware before relying on a number.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use gfx_host::framebuffer::Framebuffer;
+use gfx_host::gfx::bitmap_font::{BitmapFont, Glyph};
+use gfx_host::gfx::draw_target::{blit_bitmap_pixels, DrawTarget};
+use gfx_host::gfx::fonts::{BASIC_5PX, UI_7PX};
+
+const ITERATIONS: u32 = 2_000;
+const BATCHES: usize = 20;
+
+// roughly the densest screens the remote draws, the event log and the parameter tables
+const SCREEN: [(&str, isize); 6] = [
+    ("12:04.5 Run On Time 250us Off 100ms", 7),
+    ("12:04.6 Limiter: duty cycle 2.4% ok", 15),
+    ("12:05.0 Stop, session 0.5s 5 bursts", 23),
+    ("Phase Delay -120ns Frequency 420kHz", 31),
+    ("Max Current 312.50A Power 80% (flat)", 39),
+    ("{[abcdefghijklmnopqrstuvwxyz]} 0123", 47),
+];
+
+// the framebuffer addressing as it was, a divide by 6 and a bounds check per pixel
+struct PixelTarget {
+    buffer: [u8; 6 * 8 * 22],
+}
+
+impl DrawTarget for PixelTarget {
+    fn set_pixel(&mut self, position: (isize, isize), color: bool) {
+        if position.0 < 0 || position.0 >= 128 || position.1 < 0 || position.1 >= 64 {
+            return;
+        }
+        const PIXEL_OFFSETS: [usize; 6] = [0, 2, 4, 5, 3, 1];
+        let (x, y) = (position.0 as usize, position.1 as usize);
+        let pixel = y * 6 + PIXEL_OFFSETS[x % 6];
+        let byte = pixel / 8 + (x / 6) * 8 * 6;
+        if color {
+            self.buffer[byte] |= 1 << (pixel % 8);
+        } else {
+            self.buffer[byte] &= !(1 << (pixel % 8));
+        }
+    }
+
+    fn get_pixel(&self, _position: (isize, isize)) -> bool {
+        false
+    }
+}
+
+// find_glyph as it was, searching the ranges in order
+fn search_ranges(font: &BitmapFont, c: char) -> Option<&'static Glyph> {
+    for range in font.ranges {
+        if (range.start_char as usize) <= (c as usize) && (c as usize) < (range.start_char as usize + range.glyphs.len()) {
+            return Some(range.glyphs[c as usize - range.start_char as usize]);
+        }
+    }
+    None
+}
+
+fn draw_screen_before(target: &mut PixelTarget, font: &BitmapFont) {
+    for (line, baseline) in SCREEN {
+        let mut x = 2;
+        for c in line.chars() {
+            if let Some(glyph) = search_ranges(font, c) {
+                blit_bitmap_pixels(target, (x, baseline - glyph.baseline), glyph.bitmap, glyph.width, glyph.height, true);
+                x += glyph.advance() as isize;
+            }
+        }
+    }
+}
+
+fn draw_screen_after(target: &mut Framebuffer, font: &BitmapFont) {
+    for (line, baseline) in SCREEN {
+        font.draw_text_line(target, (2, baseline), line, true);
+    }
+}
+
+// the fastest of several batches, which is the least disturbed by whatever else the host is doing
+fn time(mut f: impl FnMut()) -> Duration {
+    (0..BATCHES).map(|_| {
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            f();
+        }
+        start.elapsed() / ITERATIONS
+    }).min().unwrap_or_default()
+}
+
+fn report(name: &str, before: Duration, after: Duration) {
+    println!("{:<24} {:>9.2?} {:>9.2?} {:>6.1}x", name, before, after, before.as_secs_f64() / after.as_secs_f64());
+}
+
+fn main() {
+    println!("{:<24} {:>9} {:>9} {:>7}", "per screen", "before", "after", "speedup");
+
+    let text: String = SCREEN.iter().map(|(line, _)| *line).collect();
+    for (name, font) in [("glyph lookup 5px", &BASIC_5PX), ("glyph lookup 7px", &UI_7PX)] {
+        let before = time(|| {
+            for c in black_box(text.as_str()).chars() {
+                black_box(search_ranges(font, c));
+            }
+        });
+        let after = time(|| {
+            for c in black_box(text.as_str()).chars() {
+                black_box(font.find_glyph(c));
+            }
+        });
+        report(name, before, after);
+    }
+
+    for (name, font) in [("draw text 5px", &BASIC_5PX), ("draw text 7px", &UI_7PX)] {
+        let mut pixel_target = PixelTarget { buffer: [0; 6 * 8 * 22] };
+        let mut framebuffer = Framebuffer::new();
+        let before = time(|| {
+            pixel_target.buffer.fill(0);
+            draw_screen_before(black_box(&mut pixel_target), font);
+        });
+        let after = time(|| {
+            framebuffer.clear(false);
+            draw_screen_after(black_box(&mut framebuffer), font);
+        });
+        // both ways have to draw the same thing for the comparison to mean anything
+        assert!(pixel_target.buffer == framebuffer.buffer, "{} drew differently", name);
+        report(name, before, after);
+    }
+}
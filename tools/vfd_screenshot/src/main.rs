@@ -0,0 +1,142 @@
+//! Converts the `vfd-screenshot` dump that the remote prints over RTT into a PNG.
+//!
+//! Hold the two outer frame buttons on the remote to request a screenshot, save the defmt log
+//! output (e.g. from `probe-rs run`) and run:
+//!
+//! ```text
+//! cargo run --target <host triple> -- remote.log screenshot.png [--scale N]
+//! ```
+//!
+//! The log may contain any number of screenshots, the last complete one is converted.
+//! Pass `-` as the input to read the log from stdin.
+
+use std::fs::File;
+use std::io::{BufWriter, Read};
+use std::process::ExitCode;
+
+const DEFAULT_SCALE: usize = 4;
+
+struct Screenshot {
+    width: usize,
+    height: usize,
+    rows: Vec<Option<Vec<u8>>>,
+}
+
+impl Screenshot {
+    fn pixel(&self, x: usize, y: usize) -> bool {
+        match &self.rows[y] {
+            Some(row) => row.get(x / 8).map(|byte| byte & (0x80 >> (x % 8)) != 0).unwrap_or(false),
+            None => false,
+        }
+    }
+}
+
+fn parse_size(text: &str) -> Option<(usize, usize)> {
+    let (width, height) = text.trim().split_once('x')?;
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+fn parse_bytes(text: &str) -> Option<Vec<u8>> {
+    let start = text.find('[')?;
+    let end = text.rfind(']')?;
+    text[start + 1..end]
+        .split(',')
+        .map(|byte| {
+            let byte = byte.trim();
+            let byte = byte.strip_prefix("0x").unwrap_or(byte);
+            u8::from_str_radix(byte, 16).ok()
+        })
+        .collect()
+}
+
+fn parse_log(log: &str) -> Option<Screenshot> {
+    let mut current: Option<Screenshot> = None;
+    let mut last_complete = None;
+    for line in log.lines() {
+        if let Some(index) = line.find("vfd-screenshot begin") {
+            current = parse_size(&line[index + "vfd-screenshot begin".len()..]).map(|(width, height)| Screenshot {
+                width,
+                height,
+                rows: vec![None; height],
+            });
+        } else if let Some(index) = line.find("vfd-row") {
+            let Some(screenshot) = current.as_mut() else {
+                continue;
+            };
+            let rest = line[index + "vfd-row".len()..].trim_start();
+            let Some((row, bytes)) = rest.split_once(' ') else {
+                continue;
+            };
+            if let (Ok(row), Some(bytes)) = (row.parse::<usize>(), parse_bytes(bytes)) {
+                if row < screenshot.height {
+                    screenshot.rows[row] = Some(bytes);
+                }
+            }
+        } else if line.contains("vfd-screenshot end") {
+            if let Some(screenshot) = current.take() {
+                let missing = screenshot.rows.iter().filter(|row| row.is_none()).count();
+                if missing != 0 {
+                    eprintln!("warning: screenshot is missing {} rows, they are left blank", missing);
+                }
+                last_complete = Some(screenshot);
+            }
+        }
+    }
+    last_complete
+}
+
+fn write_png(screenshot: &Screenshot, path: &str, scale: usize) -> Result<(), String> {
+    let width = screenshot.width * scale;
+    let height = screenshot.height * scale;
+    let file = File::create(path).map_err(|e| format!("failed to create {}: {}", path, e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            data.push(if screenshot.pixel(x / scale, y / scale) { 0xFF } else { 0x00 });
+        }
+    }
+    writer.write_image_data(&data).map_err(|e| e.to_string())
+}
+
+fn run() -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut scale = DEFAULT_SCALE;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--scale" {
+            scale = args.next()
+                .and_then(|value| value.parse().ok())
+                .filter(|&value| value > 0)
+                .ok_or("--scale expects a positive integer")?;
+        } else {
+            positional.push(arg);
+        }
+    }
+    let [input, output] = &positional[..] else {
+        return Err("usage: vfd_screenshot <log file | -> <output.png> [--scale N]".into());
+    };
+    let mut log = String::new();
+    if input == "-" {
+        std::io::stdin().read_to_string(&mut log).map_err(|e| e.to_string())?;
+    } else {
+        File::open(input)
+            .and_then(|mut file| file.read_to_string(&mut log))
+            .map_err(|e| format!("failed to read {}: {}", input, e))?;
+    }
+    let screenshot = parse_log(&log).ok_or("no complete screenshot found in the log")?;
+    write_png(&screenshot, output, scale)
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}